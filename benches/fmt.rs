@@ -9,7 +9,7 @@ use test::Bencher;
 fn bench_fmt(b: &mut Bencher) {
     use std::fmt::Write;
     let mime = ::mime::TEXT_PLAIN_UTF_8;
-    b.bytes = mime.to_string().as_bytes().len() as u64;
+    b.bytes = mime.to_string().len() as u64;
     let mut s = String::with_capacity(64);
     b.iter(|| {
         let _ = write!(s, "{}", mime);
@@ -39,7 +39,16 @@ pub fn media_type(tokens: TokenStream) -> TokenStream {
     };
     let params = match mime.private_params_source() {
         mime_parse::ParamSource::None => quote! { $crate::private::ParamSource::None },
-        mime_parse::ParamSource::Utf8(sc) => quote! { $crate::private::ParamSource::Utf8(#sc) },
+        mime_parse::ParamSource::KnownCharset(sc, charset) => {
+            let charset = match charset {
+                mime_parse::KnownCharset::Utf8 => quote! { Utf8 },
+                mime_parse::KnownCharset::UsAscii => quote! { UsAscii },
+                mime_parse::KnownCharset::Iso8859_1 => quote! { Iso8859_1 },
+            };
+            quote! {
+                $crate::private::ParamSource::KnownCharset(#sc, $crate::private::KnownCharset::#charset)
+            }
+        },
         mime_parse::ParamSource::One(sc, ((na, nz), (va, vz))) => quote! {
             $crate::private::ParamSource::One(#sc, ((#na, #nz), (#va, #vz)))
         },
@@ -67,7 +76,7 @@ fn parse_mime_lit(value: &str) -> Result<mime_parse::Mime, String> {
     match mime {
         Ok(mime) => match mime.private_params_source() {
             mime_parse::ParamSource::None |
-            mime_parse::ParamSource::Utf8(_) => Ok(mime),
+            mime_parse::ParamSource::KnownCharset(..) => Ok(mime),
             mime_parse::ParamSource::One(..) => Ok(mime),
             _ => Err("multiple parameters not supported yet".into())
         },
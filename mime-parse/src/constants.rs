@@ -1,4 +1,6 @@
-use super::{InternParams, Mime, ParamSource, Source};
+use std::borrow::Cow;
+
+use super::{InternParams, KnownCharset, Mime, ParamSource, Source};
 
 macro_rules! mimes {
     ($($id:ident, $($piece:expr),+;)+) => (
@@ -38,7 +40,10 @@ macro_rules! mime_constant {
     );
 
     ($id:ident, $src:expr, $slash:expr, $plus:expr, $params:expr) => (
-        mime_constant!(FULL $id, $src, $slash, $plus, ParamSource::Utf8($params));
+        mime_constant!($id, $src, $slash, $plus, $params, KnownCharset::Utf8);
+    );
+    ($id:ident, $src:expr, $slash:expr, $plus:expr, $params:expr, $charset:expr) => (
+        mime_constant!(FULL $id, $src, $slash, $plus, ParamSource::KnownCharset($params, $charset));
     );
 
 
@@ -71,7 +76,10 @@ macro_rules! mime_constant_test {
     );
 
     ($id:ident, $src:expr, $slash:expr, $plus:expr, $params:expr) => (
-        mime_constant_test!(FULL $id, $src, $slash, $plus, ParamSource::Utf8($params));
+        mime_constant_test!($id, $src, $slash, $plus, $params, KnownCharset::Utf8);
+    );
+    ($id:ident, $src:expr, $slash:expr, $plus:expr, $params:expr, $charset:expr) => (
+        mime_constant_test!(FULL $id, $src, $slash, $plus, ParamSource::KnownCharset($params, $charset));
     );
 
     (FULL $id:ident, $src:expr, $slash:expr, $plus:expr, $params:expr) => ({
@@ -86,9 +94,9 @@ macro_rules! mime_constant_test {
         } else {
             assert!(!mime.as_ref().as_bytes().contains(&b'+'), "{:?} forgot plus", mime);
         }
-        if let ParamSource::Utf8(semicolon) = mime.params {
+        if let ParamSource::KnownCharset(semicolon, charset) = mime.params {
             assert_eq!(mime.as_ref().as_bytes()[semicolon as usize], b';');
-            assert_eq!(&mime.as_ref()[semicolon as usize ..], "; charset=utf-8");
+            assert_eq!(&mime.as_ref()[semicolon as usize ..], format!("; charset={}", charset.as_str()));
         } else if let ParamSource::None = mime.params {
             assert!(!mime.as_ref().as_bytes().contains(&b';'));
         } else {
@@ -98,7 +106,7 @@ macro_rules! mime_constant_test {
 
         // check that parsing can intern constants
         match mime.params {
-            ParamSource::None | ParamSource::Utf8(_) => {
+            ParamSource::None | ParamSource::KnownCharset(..) => {
                 let parsed = crate::Parser::can_range().parse($src).expect("parse const");
                 match parsed.source {
                     Source::Atom(_, $src) => (),
@@ -125,7 +133,8 @@ macro_rules! mime_constant_test {
 }
 
 impl Atoms {
-    pub(super) fn intern(s: &str, slash: u16, params: InternParams) -> Source {
+    pub(super) fn intern<'a>(s: impl Into<Cow<'a, str>>, slash: u16, params: InternParams) -> Source {
+        let s = s.into();
         let slash = slash as usize;
         debug_assert!(
             s.len() > slash,
@@ -134,48 +143,72 @@ impl Atoms {
             slash,
         );
 
-        match params {
-            InternParams::Utf8(semicolon) => {
-                Atoms::intern_charset_utf8(s, slash, semicolon)
+        let atom = match params {
+            InternParams::KnownCharset(semicolon, KnownCharset::Utf8) => {
+                Atoms::intern_charset_utf8(&s, slash, semicolon)
+            },
+            InternParams::KnownCharset(semicolon, charset) => {
+                Atoms::intern_charset_other(&s, slash, semicolon, charset)
             },
             InternParams::None => {
-                Atoms::intern_no_params(s, slash)
+                Atoms::intern_no_params(&s, slash)
             },
-        }
+        };
+        atom.unwrap_or_else(|| Atoms::dynamic(s))
     }
 
-    fn intern_charset_utf8(s: &str, slash: usize, semicolon: usize) -> Source {
+    #[allow(clippy::collapsible_if)]
+    fn intern_charset_utf8(s: &str, slash: usize, semicolon: usize) -> Option<Source> {
         use self::names::*;
         let top = &s[..slash];
         let sub = &s[slash + 1..semicolon];
 
         if top == TEXT {
             if sub == PLAIN {
-                return Atoms::TEXT_PLAIN_UTF_8;
+                return Some(Atoms::TEXT_PLAIN_UTF_8);
             }
             if sub == HTML {
-                return Atoms::TEXT_HTML_UTF_8;
+                return Some(Atoms::TEXT_HTML_UTF_8);
             }
             if sub == CSS {
-                return Atoms::TEXT_CSS_UTF_8;
+                return Some(Atoms::TEXT_CSS_UTF_8);
             }
             if sub == CSV {
-                return Atoms::TEXT_CSV_UTF_8;
+                return Some(Atoms::TEXT_CSV_UTF_8);
             }
             if sub == TAB_SEPARATED_VALUES {
-                return Atoms::TEXT_TAB_SEPARATED_VALUES_UTF_8;
+                return Some(Atoms::TEXT_TAB_SEPARATED_VALUES_UTF_8);
             }
         }
         if top == APPLICATION {
             if sub == JAVASCRIPT {
-                return Atoms::APPLICATION_JAVASCRIPT_UTF_8;
+                return Some(Atoms::APPLICATION_JAVASCRIPT_UTF_8);
+            }
+        }
+
+        None
+    }
+
+    fn intern_charset_other(s: &str, slash: usize, semicolon: usize, charset: KnownCharset) -> Option<Source> {
+        use self::names::*;
+        let top = &s[..slash];
+        let sub = &s[slash + 1..semicolon];
+
+        if top == TEXT {
+            match (sub, charset) {
+                (PLAIN, KnownCharset::UsAscii) => return Some(Atoms::TEXT_PLAIN_US_ASCII),
+                (PLAIN, KnownCharset::Iso8859_1) => return Some(Atoms::TEXT_PLAIN_ISO_8859_1),
+                (HTML, KnownCharset::UsAscii) => return Some(Atoms::TEXT_HTML_US_ASCII),
+                (HTML, KnownCharset::Iso8859_1) => return Some(Atoms::TEXT_HTML_ISO_8859_1),
+                _ => (),
             }
         }
 
-        Atoms::dynamic(s)
+        None
     }
 
-    fn intern_no_params(s: &str, slash: usize) -> Source {
+    #[allow(clippy::collapsible_if, clippy::collapsible_match, clippy::single_match)]
+    fn intern_no_params(s: &str, slash: usize) -> Option<Source> {
         use self::names::*;
         let top = &s[..slash];
         let sub = &s[slash + 1..];
@@ -186,46 +219,46 @@ impl Atoms {
                     match sub.len() {
                         1 => {
                             if sub.as_bytes()[0] == b'*' {
-                                return Atoms::TEXT_STAR;
+                                return Some(Atoms::TEXT_STAR);
                             }
                         }
                         3 => {
                             if sub == CSS {
-                                return Atoms::TEXT_CSS;
+                                return Some(Atoms::TEXT_CSS);
                             }
                             if sub == XML {
-                                return Atoms::TEXT_XML;
+                                return Some(Atoms::TEXT_XML);
                             }
                             if sub == CSV {
-                                return Atoms::TEXT_CSV;
+                                return Some(Atoms::TEXT_CSV);
                             }
                         },
                         4 => {
                             if sub == HTML {
-                                return Atoms::TEXT_HTML;
+                                return Some(Atoms::TEXT_HTML);
                             }
                         }
                         5 => {
                             if sub == PLAIN {
-                                return Atoms::TEXT_PLAIN;
+                                return Some(Atoms::TEXT_PLAIN);
                             }
                             if sub == VCARD {
-                                return Atoms::TEXT_VCARD;
+                                return Some(Atoms::TEXT_VCARD);
                             }
                         }
                         10 => {
                             if sub == JAVASCRIPT {
-                                return Atoms::TEXT_JAVASCRIPT;
+                                return Some(Atoms::TEXT_JAVASCRIPT);
                             }
                         }
                         12 => {
                             if sub == EVENT_STREAM {
-                                return Atoms::TEXT_EVENT_STREAM;
+                                return Some(Atoms::TEXT_EVENT_STREAM);
                             }
                         },
                         20 => {
                             if sub == TAB_SEPARATED_VALUES {
-                                return Atoms::TEXT_TAB_SEPARATED_VALUES;
+                                return Some(Atoms::TEXT_TAB_SEPARATED_VALUES);
                             }
                         }
                         _ => (),
@@ -234,12 +267,12 @@ impl Atoms {
                     match sub.len() {
                         4 => {
                             if sub == WOFF {
-                                return Atoms::FONT_WOFF;
+                                return Some(Atoms::FONT_WOFF);
                             }
                         },
                         5 => {
                             if sub == WOFF2 {
-                                return Atoms::FONT_WOFF2;
+                                return Some(Atoms::FONT_WOFF2);
                             }
                         },
                         _ => (),
@@ -251,28 +284,28 @@ impl Atoms {
                     match sub.len() {
                         1 => {
                             if sub.as_bytes()[0] == b'*' {
-                                return Atoms::IMAGE_STAR;
+                                return Some(Atoms::IMAGE_STAR);
                             }
                         }
                         3 => {
                             if sub == PNG {
-                                return Atoms::IMAGE_PNG;
+                                return Some(Atoms::IMAGE_PNG);
                             }
                             if sub == GIF {
-                                return Atoms::IMAGE_GIF;
+                                return Some(Atoms::IMAGE_GIF);
                             }
                             if sub == BMP {
-                                return Atoms::IMAGE_BMP;
+                                return Some(Atoms::IMAGE_BMP);
                             }
                         }
                         4 => {
                             if sub == JPEG {
-                                return Atoms::IMAGE_JPEG;
+                                return Some(Atoms::IMAGE_JPEG);
                             }
                         },
                         7 => {
                             if sub == SVG {
-                                return Atoms::IMAGE_SVG;
+                                return Some(Atoms::IMAGE_SVG);
                             }
                         },
                         _ => (),
@@ -282,7 +315,7 @@ impl Atoms {
                     match sub.len() {
                         1 => {
                             if sub.as_bytes()[0] == b'*' {
-                                return Atoms::VIDEO_STAR;
+                                return Some(Atoms::VIDEO_STAR);
                             }
                         },
                         _ => (),
@@ -291,7 +324,7 @@ impl Atoms {
                     match sub.len() {
                         1 => {
                             if sub.as_bytes()[0] == b'*' {
-                                return Atoms::AUDIO_STAR;
+                                return Some(Atoms::AUDIO_STAR);
                             }
                         },
                         _ => (),
@@ -303,37 +336,37 @@ impl Atoms {
                     match sub.len() {
                         3 => {
                             if sub == PDF {
-                                return Atoms::APPLICATION_PDF;
+                                return Some(Atoms::APPLICATION_PDF);
                             }
                         }
                         4 => {
                             if sub == JSON {
-                                return Atoms::APPLICATION_JSON;
+                                return Some(Atoms::APPLICATION_JSON);
                             }
                         },
                         7 => {
                             if sub == MSGPACK {
-                                return Atoms::APPLICATION_MSGPACK;
+                                return Some(Atoms::APPLICATION_MSGPACK);
                             }
                         },
                         10 => {
                             if sub == JAVASCRIPT {
-                                return Atoms::APPLICATION_JAVASCRIPT;
+                                return Some(Atoms::APPLICATION_JAVASCRIPT);
                             }
                         },
                         11 => {
                             if sub == "dns-message" {
-                                return Atoms::APPLICATION_DNS;
+                                return Some(Atoms::APPLICATION_DNS);
                             }
                         },
                         12 => {
                             if sub == OCTET_STREAM {
-                                return Atoms::APPLICATION_OCTET_STREAM;
+                                return Some(Atoms::APPLICATION_OCTET_STREAM);
                             }
                         }
                         21 => {
                             if sub == WWW_FORM_URLENCODED {
-                                return Atoms::APPLICATION_WWW_FORM_URLENCODED;
+                                return Some(Atoms::APPLICATION_WWW_FORM_URLENCODED);
                             }
                         }
                         _ => (),
@@ -343,11 +376,16 @@ impl Atoms {
             _ => (),
         }
 
-        Atoms::dynamic(s)
+        None
     }
 
-    fn dynamic(s: &str) -> Source {
-        Source::Dynamic(s.to_ascii_lowercase())
+    // The only parameter a source can have here is a known `charset`,
+    // so lowercasing all of it agrees with `lower_ascii_with_params`:
+    // names and `charset` values are lowercased, other values aren't.
+    fn dynamic(s: Cow<'_, str>) -> Source {
+        let mut s = s.into_owned();
+        s.make_ascii_lowercase();
+        Source::Dynamic(s)
     }
 }
 
@@ -436,8 +474,12 @@ mimes! {
     //@ MediaType:
     TEXT_PLAIN, "text/plain", 4;
     TEXT_PLAIN_UTF_8, "text/plain; charset=utf-8", 4, None, 10;
+    TEXT_PLAIN_US_ASCII, "text/plain; charset=us-ascii", 4, None, 10, KnownCharset::UsAscii;
+    TEXT_PLAIN_ISO_8859_1, "text/plain; charset=iso-8859-1", 4, None, 10, KnownCharset::Iso8859_1;
     TEXT_HTML, "text/html", 4;
     TEXT_HTML_UTF_8, "text/html; charset=utf-8", 4, None, 9;
+    TEXT_HTML_US_ASCII, "text/html; charset=us-ascii", 4, None, 9, KnownCharset::UsAscii;
+    TEXT_HTML_ISO_8859_1, "text/html; charset=iso-8859-1", 4, None, 9, KnownCharset::Iso8859_1;
     TEXT_CSS, "text/css", 4;
     TEXT_CSS_UTF_8, "text/css; charset=utf-8", 4, None, 8;
     TEXT_JAVASCRIPT, "text/javascript", 4;
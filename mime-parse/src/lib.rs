@@ -2,7 +2,9 @@
 //!
 //! Nothing to see here. Move along.
 
+use std::borrow::Cow;
 use std::error::Error;
+use std::iter::FusedIterator;
 use std::{fmt, slice};
 
 pub mod constants;
@@ -11,8 +13,20 @@ mod rfc7231;
 use self::constants::Atoms;
 use self::sealed::Sealed;
 
+#[derive(Clone, Copy)]
 pub struct Parser {
     can_range: bool,
+    rfc6838_names: bool,
+    ascii_quoted: bool,
+    preserve_case: bool,
+    preserve_param_names: bool,
+    ows_around_equals: bool,
+    reject_asterisks: bool,
+    rfc2231_names: bool,
+    permissive_values: bool,
+    max_param_name_len: Option<usize>,
+    max_param_value_len: Option<usize>,
+    allow_empty_subtype: bool,
 }
 
 #[derive(Clone)]
@@ -44,17 +58,49 @@ type IndexedPair = (Indexed, Indexed);
 #[derive(Clone)]
 pub enum ParamSource {
     None,
-    Utf8(u16),
+    KnownCharset(u16, KnownCharset),
     One(u16, IndexedPair),
     Two(u16, IndexedPair, IndexedPair),
     Custom(u16, Vec<IndexedPair>),
 }
 
 pub enum InternParams {
-    Utf8(usize),
+    KnownCharset(usize, KnownCharset),
     None,
 }
 
+/// A `charset` that can be the only parameter without allocating.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KnownCharset {
+    Utf8,
+    UsAscii,
+    Iso8859_1,
+}
+
+impl KnownCharset {
+    const ALL: [KnownCharset; 3] = [
+        KnownCharset::Utf8,
+        KnownCharset::UsAscii,
+        KnownCharset::Iso8859_1,
+    ];
+
+    /// The lowercase name, as it appears in the canonical source.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KnownCharset::Utf8 => "utf-8",
+            KnownCharset::UsAscii => "us-ascii",
+            KnownCharset::Iso8859_1 => "iso-8859-1",
+        }
+    }
+
+    fn lookup(value: &str) -> Option<KnownCharset> {
+        KnownCharset::ALL
+            .iter()
+            .copied()
+            .find(|cs| cs.as_str().eq_ignore_ascii_case(value))
+    }
+}
+
 #[derive(Debug)]
 pub enum ParseError {
     MissingSlash,
@@ -66,6 +112,59 @@ pub enum ParseError {
     },
     InvalidRange,
     TooLong,
+    NonAsciiQuoted {
+        pos: usize,
+        byte: Byte,
+    },
+    /// The source didn't start with `data:`, or had no `,` before the
+    /// data.
+    InvalidDataUrl,
+    /// A parameter was longer than a `Parser` limit. `pos` is the first
+    /// byte past the limit.
+    LimitExceeded {
+        which: Limit,
+        pos: usize,
+    },
+    /// A separately given type or subtype was invalid.
+    ///
+    /// `pos` is within that component, and `byte` is `None` if it was empty.
+    InvalidName {
+        component: Component,
+        pos: usize,
+        byte: Option<Byte>,
+    },
+    /// Nothing came after the `/`, before the end or the parameters.
+    ///
+    /// `pos` is right after the `/`.
+    EmptySubtype {
+        pos: usize,
+    },
+    /// A header that must have a single media type had more than one,
+    /// either as repeated field lines or separated by commas.
+    MultipleValues,
+}
+
+/// Which limit a `ParseError::LimitExceeded` is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    ParamNameLen,
+    ParamValueLen,
+}
+
+/// Which half of an essence an error is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Component {
+    Type,
+    Subtype,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Component::Type => "type",
+            Component::Subtype => "subtype",
+        })
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -94,14 +193,36 @@ impl fmt::Display for ParseError {
             ParseError::MissingSlash => "a slash (/) was missing between the type and subtype",
             ParseError::MissingEqual => "an equals sign (=) was missing between a parameter and its value",
             ParseError::MissingQuote => "a quote (\") was missing from a parameter value",
+            ParseError::InvalidToken { byte: Byte(b'*'), .. } => "unexpected asterisk",
             ParseError::InvalidToken { .. } => "invalid token",
             ParseError::InvalidRange => "unexpected asterisk",
             ParseError::TooLong => "the string is too long",
+            ParseError::NonAsciiQuoted { .. } => "non-ASCII byte in a quoted parameter value",
+            ParseError::InvalidDataUrl => "not a data: URL with a comma before the data",
+            ParseError::MultipleValues => "more than one value for a header that allows only one",
+            ParseError::LimitExceeded { which, pos } => {
+                let what = match which {
+                    Limit::ParamNameLen => "name",
+                    Limit::ParamValueLen => "value",
+                };
+                return write!(f, "a parameter {} was too long, at position {}", what, pos);
+            },
+            ParseError::EmptySubtype { pos } => {
+                return write!(f, "the subtype was empty, at position {}", pos);
+            },
+            ParseError::InvalidName { component, pos, byte } => {
+                return match byte {
+                    Some(byte) => write!(f, "invalid {}, {:?} at position {}", component, byte, pos),
+                    None => write!(f, "the {} was empty", component),
+                };
+            },
         };
-        if let ParseError::InvalidToken { pos, byte } = *self {
-            write!(f, "{}, {:?} at position {}", description, byte, pos)
-        } else {
-            f.write_str(description)
+        match *self {
+            ParseError::InvalidToken { pos, byte } |
+            ParseError::NonAsciiQuoted { pos, byte } => {
+                write!(f, "{}, {:?} at position {}", description, byte, pos)
+            },
+            _ => f.write_str(description),
         }
     }
 }
@@ -120,6 +241,16 @@ impl Mime {
         &self.source.as_ref()[self.slash as usize + 1..end]
     }
 
+    #[inline]
+    pub fn type_len(&self) -> usize {
+        self.slash as usize
+    }
+
+    #[inline]
+    pub fn subtype_len(&self) -> usize {
+        self.semicolon_or_end() - self.slash as usize - 1
+    }
+
     #[doc(hidden)]
     pub fn private_subtype_offset(&self) -> u16 {
         self.slash
@@ -137,9 +268,9 @@ impl Mime {
     }
 
     #[inline]
-    pub fn params(&self) -> Params {
+    pub fn params(&self) -> Params<'_> {
         let inner = match self.params {
-            ParamSource::Utf8(_) => ParamsInner::Utf8,
+            ParamSource::KnownCharset(_, charset) => ParamsInner::KnownCharset(charset),
             ParamSource::One(_, a) => ParamsInner::Inlined(&self.source, Inline::One(a)),
             ParamSource::Two(_, a, b) => ParamsInner::Inlined(&self.source, Inline::Two(a, b)),
             ParamSource::Custom(_, ref params) => {
@@ -154,13 +285,47 @@ impl Mime {
         Params(inner)
     }
 
+    /// Get the parameter at `index`, without walking the ones before it.
+    pub fn nth_param(&self, index: usize) -> Option<(&str, &str)> {
+        let pair = match (&self.params, index) {
+            (&ParamSource::KnownCharset(_, charset), 0) => return Some(("charset", charset.as_str())),
+            (&ParamSource::One(_, a), 0) |
+            (&ParamSource::Two(_, a, _), 0) |
+            (&ParamSource::Two(_, _, a), 1) => a,
+            (ParamSource::Custom(_, params), i) => *params.get(i)?,
+            _ => return None,
+        };
+        let (name, value) = pair;
+        Some((&self.source.as_ref()[range(name)], &self.source.as_ref()[range(value)]))
+    }
+
+    /// The byte ranges of each parameter's name and value in the source.
+    pub fn param_ranges(&self) -> impl Iterator<Item = (std::ops::Range<usize>, std::ops::Range<usize>)> + '_ {
+        let (inline, custom): ([Option<IndexedPair>; 2], &[IndexedPair]) = match self.params {
+            ParamSource::KnownCharset(i, charset) => {
+                let name = (i + 2, i + 2 + "charset".len() as u16);
+                let value = (name.1 + 1, name.1 + 1 + charset.as_str().len() as u16);
+                ([Some((name, value)), None], &[])
+            },
+            ParamSource::One(_, a) => ([Some(a), None], &[]),
+            ParamSource::Two(_, a, b) => ([Some(a), Some(b)], &[]),
+            ParamSource::Custom(_, ref params) => ([None, None], params),
+            ParamSource::None => ([None, None], &[]),
+        };
+
+        IntoIterator::into_iter(inline)
+            .flatten()
+            .chain(custom.iter().copied())
+            .map(|(name, value)| (range(name), range(value)))
+    }
+
     #[doc(hidden)]
     pub fn private_params_source(&self) -> &ParamSource {
         &self.params
     }
 
     pub fn param<'a>(&'a self, attr: &str) -> Option<&'a str> {
-        self.params().find(|e| attr == e.0).map(|e| e.1)
+        self.params().find(|e| attr.eq_ignore_ascii_case(e.0)).map(|e| e.1)
     }
 
     #[inline]
@@ -185,10 +350,191 @@ impl Mime {
         mtype
     }
 
+    pub fn is_rfc6838_name(&self) -> bool {
+        rfc7231::is_restricted_name(self.type_()) &&
+            rfc7231::is_restricted_name(self.subtype())
+    }
+
+    pub fn retain_params<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, &str) -> bool,
+    {
+        if !self.has_params() {
+            return;
+        }
+
+        // Each kept param is copied along with the separator before it, so
+        // the source can only get shorter.
+        let source = self.source.as_ref();
+        let mut end = self.semicolon_or_end();
+        let mut retained = source[..end].to_owned();
+        let (mut kept, mut dropped) = (0, 0);
+        for (name, value) in self.param_ranges() {
+            if f(&source[name], &source[value.clone()]) {
+                retained.push_str(&source[end..value.end]);
+                kept += 1;
+            } else {
+                dropped += 1;
+            }
+            end = value.end;
+        }
+
+        if dropped == 0 {
+            return;
+        }
+
+        *self = if kept == 0 {
+            self.clone().without_params()
+        } else {
+            // `self` may have been parsed allowing an empty subtype, or
+            // RFC 2231 names.
+            Parser::can_range()
+                .allow_empty_subtype()
+                .rfc2231_names()
+                .parse(&retained)
+                .expect("retained params are valid")
+        };
+    }
+
+    /// Keeps the essence and params of `self`, except those named by a
+    /// param of `other`, and then adds all of the params of `other`.
+    ///
+    /// The only possible error is `TooLong`.
+    pub fn merge_params(&self, other: &Mime) -> Result<Mime, ParseError> {
+        if !other.has_params() {
+            return Ok(self.clone());
+        }
+
+        let kept = self.params()
+            .filter(|&(name, _)| !other.params().any(|(n, _)| n.eq_ignore_ascii_case(name)));
+        Mime::rebuild(self.essence(), kept.chain(other.params()))
+    }
+
+    /// Replaces a dynamic source with an equal atom, if there is one.
+    ///
+    /// Only a lowercase essence, with either no params or just a known
+    /// `charset`, can match an atom. Returns whether the source is now
+    /// an atom.
+    pub fn try_intern(&mut self) -> bool {
+        if self.atom() != 0 {
+            return true;
+        }
+
+        let essence = self.essence();
+        if essence.bytes().any(|b| b.is_ascii_uppercase()) {
+            return false;
+        }
+
+        let canonical = match self.params().size_hint().0 {
+            0 => essence.to_owned(),
+            1 => {
+                let (name, value) = self.params().next().expect("size_hint is exact");
+                let value = value.strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .unwrap_or(value);
+                if name != "charset" || value.contains('\\') {
+                    return false;
+                }
+                format!("{}; charset={}", essence, value)
+            },
+            _ => return false,
+        };
+
+        match Parser::can_range().parse(&canonical) {
+            Ok(mime) if mime.atom() != 0 => {
+                *self = mime;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Replaces the top level type, keeping the subtype and parameters.
+    ///
+    /// Errors point into `new_type`. An empty type is reported like the
+    /// parser does, as an invalid `/` at position 0.
+    pub fn replace_type(&self, new_type: &str) -> Result<Mime, ParseError> {
+        rfc7231::check_token(new_type)?;
+
+        let rest = &self.source.as_ref()[self.slash as usize..];
+        let mut source = String::with_capacity(new_type.len() + rest.len());
+        source.push_str(new_type);
+        source.push_str(rest);
+        Mime::reparse_edited(&source)
+    }
+
+    /// Sets or removes the +suffix, keeping the parameters.
+    ///
+    /// Errors point into `suffix`. A suffix can't be empty, or contain
+    /// another `+`.
+    pub fn with_suffix(&self, suffix: Option<&str>) -> Result<Mime, ParseError> {
+        let essence = self.essence();
+        let base = match self.plus {
+            Some(plus) => &essence[..plus as usize],
+            None => essence,
+        };
+        let rest = &self.source.as_ref()[essence.len()..];
+
+        let mut source = String::from(base);
+        if let Some(suffix) = suffix {
+            rfc7231::check_token(suffix)?;
+            if let Some(pos) = suffix.find('+') {
+                return Err(ParseError::InvalidToken {
+                    pos,
+                    byte: Byte(b'+'),
+                });
+            }
+            if suffix.is_empty() {
+                return Err(ParseError::InvalidToken {
+                    pos: 0,
+                    byte: Byte(b'+'),
+                });
+            }
+            source.push('+');
+            source.push_str(suffix);
+        }
+        source.push_str(rest);
+        Mime::reparse_edited(&source)
+    }
+
+    /// Replaces the essence, keeping the parameters.
+    ///
+    /// The only possible errors are for an invalid `essence`, or `TooLong`.
+    pub fn replace_essence(&self, essence: &str) -> Result<Mime, ParseError> {
+        let rest = &self.source.as_ref()[self.semicolon_or_end()..];
+        let mut source = String::with_capacity(essence.len() + rest.len());
+        source.push_str(essence);
+        source.push_str(rest);
+        Mime::reparse_edited(&source)
+    }
+
+    /// Formats an essence and already valid parameters into a new `Mime`.
+    fn rebuild<'a, I>(essence: &str, params: I) -> Result<Mime, ParseError>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut source = String::from(essence);
+        for (name, value) in params {
+            source.push_str("; ");
+            source.push_str(name);
+            source.push('=');
+            source.push_str(value);
+        }
+        // `essence` and `params` came from a valid `Mime`, which may have
+        // been parsed allowing an empty subtype, or RFC 2231 names.
+        Parser::can_range().allow_empty_subtype().rfc2231_names().parse(&source)
+    }
+
+    /// Parses a media type edited from the parts of `self`, allowing what
+    /// `self` may have been parsed with, like `rebuild` does.
+    fn reparse_edited(source: &str) -> Result<Mime, ParseError> {
+        Parser::cannot_range().allow_empty_subtype().rfc2231_names().parse(source)
+    }
+
     #[inline]
     fn semicolon(&self) -> Option<usize> {
         match self.params {
-            ParamSource::Utf8(i) |
+            ParamSource::KnownCharset(i, _) |
             ParamSource::One(i, ..) |
             ParamSource::Two(i, ..) |
             ParamSource::Custom(i, _) => Some(i as usize),
@@ -256,7 +602,7 @@ impl fmt::Display for Mime {
 
 #[inline]
 fn as_u16(i: usize) -> u16 {
-    debug_assert!(i <= std::u16::MAX as usize, "as_u16 overflow");
+    debug_assert!(i <= u16::MAX as usize, "as_u16 overflow");
     i as u16
 }
 
@@ -265,6 +611,32 @@ fn range(index: (u16, u16)) -> std::ops::Range<usize> {
     index.0 as usize .. index.1 as usize
 }
 
+/// Checks a separate type and subtype, before they are joined into a `Mime`.
+///
+/// Both must be non-empty tokens starting with a letter or digit.
+pub fn validate_name(type_: &str, subtype: &str) -> Result<(), ParseError> {
+    rfc7231::check_name(type_, Component::Type)?;
+    rfc7231::check_name(subtype, Component::Subtype)
+}
+
+/// Quotes a parameter value, unless it's already a valid token.
+pub fn quote_value(value: &str) -> Cow<'_, str> {
+    if rfc7231::is_token_str(value) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
 // ===== impl Parser =====
 
 impl Parser {
@@ -272,6 +644,17 @@ impl Parser {
     pub fn can_range() -> Self {
         Parser {
             can_range: true,
+            rfc6838_names: false,
+            ascii_quoted: false,
+            preserve_case: false,
+            preserve_param_names: false,
+            ows_around_equals: false,
+            reject_asterisks: false,
+            rfc2231_names: false,
+            permissive_values: false,
+            max_param_name_len: None,
+            max_param_value_len: None,
+            allow_empty_subtype: false,
         }
     }
 
@@ -279,17 +662,190 @@ impl Parser {
     pub fn cannot_range() -> Self {
         Parser {
             can_range: false,
+            rfc6838_names: false,
+            ascii_quoted: false,
+            preserve_case: false,
+            preserve_param_names: false,
+            ows_around_equals: false,
+            reject_asterisks: false,
+            rfc2231_names: false,
+            permissive_values: false,
+            max_param_name_len: None,
+            max_param_value_len: None,
+            allow_empty_subtype: false,
         }
     }
 
+    /// Only allow RFC 6838 `restricted-name`s as the type and subtype.
+    #[inline]
+    pub fn rfc6838_names(mut self) -> Self {
+        self.rfc6838_names = true;
+        self
+    }
+
+    /// Reject non-ASCII bytes (`obs-text`) in quoted parameter values.
+    #[inline]
+    pub fn ascii_quoted(mut self) -> Self {
+        self.ascii_quoted = true;
+        self
+    }
+
+    /// Keep the original casing in the source, instead of lowercasing.
+    #[inline]
+    pub fn preserve_case(mut self) -> Self {
+        self.preserve_case = true;
+        self
+    }
+
+    /// Keep the original casing of parameter names in the source, while
+    /// still lowercasing the rest like usual.
+    #[inline]
+    pub fn preserve_param_names(mut self) -> Self {
+        self.preserve_param_names = true;
+        self
+    }
+
+    /// Allow spaces and tabs around the `=` of a parameter.
+    #[inline]
+    pub fn ows_around_equals(mut self) -> Self {
+        self.ows_around_equals = true;
+        self
+    }
+
+    /// Reject any `*`, even inside a quoted parameter value.
+    ///
+    /// The first one is reported as an `InvalidToken` at its position,
+    /// before any other check is done.
+    #[inline]
+    pub fn reject_asterisks(mut self) -> Self {
+        self.reject_asterisks = true;
+        self
+    }
+
+    /// Allow `*` in parameter names, as RFC 2231 uses for continuations
+    /// and extended values, such as `title*0*=`.
+    #[inline]
+    pub fn rfc2231_names(mut self) -> Self {
+        self.rfc2231_names = true;
+        self
+    }
+
+    /// Allow any byte that could be quoted in an unquoted value, up to
+    /// the next `;`. Such values are quoted in the resulting source.
+    #[inline]
+    pub fn permissive_values(mut self) -> Self {
+        self.permissive_values = true;
+        self
+    }
+
+    /// Reject parameter names longer than `max` bytes.
+    #[inline]
+    pub fn max_param_name_len(mut self, max: usize) -> Self {
+        self.max_param_name_len = Some(max);
+        self
+    }
+
+    /// Reject parameter values longer than `max` bytes, as written,
+    /// including any quotes.
+    #[inline]
+    pub fn max_param_value_len(mut self, max: usize) -> Self {
+        self.max_param_value_len = Some(max);
+        self
+    }
+
+    /// Allow an empty subtype, such as `text/`, instead of returning
+    /// `ParseError::EmptySubtype`.
+    #[inline]
+    pub fn allow_empty_subtype(mut self) -> Self {
+        self.allow_empty_subtype = true;
+        self
+    }
+
     pub fn parse(&self, src: impl Parse) -> Result<Mime, ParseError> {
         rfc7231::parse(self, src)
     }
+
+    /// Parse a media type, reusing an owned source's buffer when the
+    /// result isn't one of the known atoms.
+    pub fn parse_cow(&self, src: Cow<'_, str>) -> Result<Mime, ParseError> {
+        match src {
+            Cow::Borrowed(s) => rfc7231::parse(self, s),
+            Cow::Owned(s) => rfc7231::parse_owned(self, s),
+        }
+    }
+
+    /// Parse a media type, keeping only the params named in `keep`.
+    ///
+    /// Names are compared case-insensitively. The dropped params must
+    /// still be valid, but never make it into the source.
+    pub fn parse_keeping_params(&self, src: impl Parse, keep: &[&str]) -> Result<Mime, ParseError> {
+        rfc7231::parse_keeping_params(self, src, keep)
+    }
+
+    /// Parse a media type, using a static string from `atoms` as the
+    /// source if one matches exactly, instead of allocating.
+    ///
+    /// The built-in atoms are tried first. `atoms` must be sorted, since
+    /// it is binary searched, and each should be in the lowercase form
+    /// that parsing produces, or it will never match.
+    pub fn parse_preferring_atoms(&self, src: impl Parse, atoms: &[&'static str]) -> Result<Mime, ParseError> {
+        rfc7231::parse_preferring_atoms(self, src, atoms)
+    }
+
+    /// Parse the values of a repeated header that must have a single
+    /// media type, such as `Content-Type`.
+    ///
+    /// Empty values and list entries are ignored. More than one non-empty
+    /// entry, in one value or across several, is `MultipleValues`.
+    pub fn parse_header_multi(&self, values: &[&str]) -> Result<Mime, ParseError> {
+        rfc7231::parse_header_multi(self, values)
+    }
+
+    /// Parse a media type, and on failure keep going at each `;` to
+    /// report the errors in later parameters too.
+    pub fn parse_collecting_errors(&self, src: impl Parse) -> Result<Mime, Vec<ParseError>> {
+        rfc7231::parse_collecting_errors(self, src)
+    }
+
+    pub fn parse_params(&self, src: impl Parse) -> Result<Vec<(String, String)>, ParseError> {
+        rfc7231::parse_params(self, src)
+    }
+
+    /// Parse the media type of a `data:` URL, returning whether it was
+    /// base64 and the data after the comma.
+    pub fn parse_data_url<'a>(&self, src: &'a str) -> Result<(Mime, bool, &'a str), ParseError> {
+        rfc7231::parse_data_url(self, src)
+    }
+
+    /// Parse a media type up to the first `delimiter` outside of quotes,
+    /// returning the rest of the source, starting at the delimiter.
+    pub fn parse_returning_remainder<'a>(&self, src: &'a str, delimiter: u8) -> Result<(Mime, &'a str), ParseError> {
+        rfc7231::parse_returning_remainder(self, src, delimiter)
+    }
+
+    /// Parse a comma-separated list, such as an `Accept-Post` value.
+    pub fn parse_list(&self, src: impl Parse) -> Result<Vec<Mime>, ParseError> {
+        rfc7231::parse_list(self, src)
+    }
+
+    /// Lazily parse a comma-separated list, one entry at a time.
+    ///
+    /// Each entry is parsed like with `parse_list`, but an invalid one
+    /// doesn't stop the ones after it from being parsed.
+    pub fn parse_csv_header<'a>(&self, src: &'a str) -> CsvEntries<'a> {
+        CsvEntries {
+            opts: *self,
+            source: src,
+            start: Some(0),
+        }
+    }
 }
 
 
-fn lower_ascii_with_params(s: &str, semi: usize, params: &[IndexedPair]) -> String {
-    let mut owned = s.to_owned();
+// Every parse lowercases the essence, every parameter name, and the value
+// of a `charset` parameter, whichever `ParamSource` it ends up with. Other
+// values keep their case, since they may be case-sensitive.
+fn lower_ascii_with_params(mut owned: String, semi: usize, params: &[IndexedPair]) -> String {
     owned[..semi].make_ascii_lowercase();
 
     for &(name, value) in params {
@@ -310,7 +866,7 @@ fn lower_ascii_with_params(s: &str, semi: usize, params: &[IndexedPair]) -> Stri
 
 
 enum ParamsInner<'a> {
-    Utf8,
+    KnownCharset(KnownCharset),
     Inlined(&'a Source, Inline),
     Custom {
         source: &'a Source,
@@ -341,8 +897,8 @@ impl<'a> Iterator for Params<'a> {
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         match self.0 {
-            ParamsInner::Utf8 => {
-                let value = ("charset", "utf-8");
+            ParamsInner::KnownCharset(charset) => {
+                let value = ("charset", charset.as_str());
                 self.0 = ParamsInner::None;
                 Some(value)
             },
@@ -380,7 +936,7 @@ impl<'a> Iterator for Params<'a> {
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
         match self.0 {
-            ParamsInner::Utf8 => (1, Some(1)),
+            ParamsInner::KnownCharset(_) => (1, Some(1)),
             ParamsInner::Inlined(_, Inline::Done) => (0, Some(0)),
             ParamsInner::Inlined(_, Inline::One(..)) => (1, Some(1)),
             ParamsInner::Inlined(_, Inline::Two(..)) => (2, Some(2)),
@@ -390,6 +946,83 @@ impl<'a> Iterator for Params<'a> {
     }
 }
 
+impl<'a> DoubleEndedIterator for Params<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self.0 {
+            ParamsInner::Inlined(source, ref mut inline) => {
+                let next = match *inline {
+                    Inline::Done => None,
+                    Inline::One(one) => {
+                        *inline = Inline::Done;
+                        Some(one)
+                    },
+                    Inline::Two(one, two) => {
+                        *inline = Inline::One(one);
+                        Some(two)
+                    },
+                };
+                next.map(|(name, value)| {
+                    let name = &source.as_ref()[range(name)];
+                    let value = &source.as_ref()[range(value)];
+                    (name, value)
+                })
+            },
+            ParamsInner::Custom { source, ref mut params } => {
+                params.next_back().map(|&(name, value)| {
+                    let name = &source.as_ref()[range(name)];
+                    let value = &source.as_ref()[range(value)];
+                    (name, value)
+                })
+            },
+            // At most one param, so the back is the front.
+            ParamsInner::KnownCharset(_) | ParamsInner::None => self.next(),
+        }
+    }
+}
+
+// Every variant stays exhausted once it has returned `None`.
+impl<'a> FusedIterator for Params<'a> {}
+
+/// An iterator over the entries of a comma-separated list.
+///
+/// Returned by `Parser::parse_csv_header`.
+pub struct CsvEntries<'a> {
+    opts: Parser,
+    source: &'a str,
+    // Where the next entry starts, or `None` once the last one is parsed.
+    start: Option<usize>,
+}
+
+impl<'a> fmt::Debug for CsvEntries<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let remaining = self.start.map_or("", |start| &self.source[start..]);
+        fmt.debug_struct("CsvEntries")
+            .field("remaining", &remaining)
+            .finish()
+    }
+}
+
+impl<'a> Iterator for CsvEntries<'a> {
+    type Item = Result<Mime, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.start?;
+        let (entry, next) = rfc7231::parse_next_list_entry(&self.opts, self.source, start);
+        self.start = next;
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.start {
+            Some(start) => (1, Some(self.source[start..].matches(',').count() + 1)),
+            None => (0, Some(0)),
+        }
+    }
+}
+
+impl<'a> FusedIterator for CsvEntries<'a> {}
+
 mod sealed {
     pub trait Sealed {
         fn as_str(&self) -> &str;
@@ -398,19 +1031,19 @@ mod sealed {
 
 pub trait Parse: Sealed {}
 
-impl<'a> Sealed for &'a str {
+impl Sealed for &str {
     fn as_str(&self) -> &str {
         self
     }
 }
 
-impl<'a> Parse for &'a str {}
+impl Parse for &str {}
 
-impl<'a> Sealed for &'a String {
+impl Sealed for &String {
     fn as_str(&self) -> &str {
-        *self
+        self
     }
 }
 
-impl<'a> Parse for &'a String {}
+impl Parse for &String {}
 
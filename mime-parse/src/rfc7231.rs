@@ -1,9 +1,14 @@
+use std::borrow::Cow;
+
 use crate::{
     as_u16,
     constants,
     Atoms,
     Byte,
+    Component,
     InternParams,
+    KnownCharset,
+    Limit,
     lower_ascii_with_params,
     Mime,
     Parse,
@@ -52,8 +57,153 @@ use crate::{
 // So, clearly, ¯\_(Ä_/¯
 
 pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError> {
+    // Surrounding whitespace isn't part of an HTTP field value, but not
+    // everyone strips it before handing it over.
+    let s = src.as_str();
+    let trimmed = s.trim_start_matches(is_http_whitespace);
+    let offset = s.len() - trimmed.len();
+    let trimmed = trimmed.trim_end_matches(is_http_whitespace);
+
+    parse_trimmed(opts, trimmed, None).map_err(|err| map_pos(err, |pos| pos + offset))
+}
+
+pub(crate) fn parse_keeping_params(opts: &Parser, src: impl Parse, keep: &[&str]) -> Result<Mime, ParseError> {
     let s = src.as_str();
-    if s.len() > std::u16::MAX as usize {
+    let trimmed = s.trim_start_matches(is_http_whitespace);
+    let offset = s.len() - trimmed.len();
+    let trimmed = trimmed.trim_end_matches(is_http_whitespace);
+
+    parse_trimmed(opts, trimmed, Some(keep)).map_err(|err| map_pos(err, |pos| pos + offset))
+}
+
+fn parse_trimmed(opts: &Parser, s: &str, keep: Option<&[&str]>) -> Result<Mime, ParseError> {
+    if opts.reject_asterisks {
+        check_no_asterisks(s)?;
+    }
+
+    if let Some(keep) = keep {
+        // These all go back to the original source afterwards, which
+        // dropping params would have moved around.
+        if opts.permissive_values || opts.ows_around_equals || opts.preserve_case || opts.preserve_param_names {
+            let mut mime = parse_trimmed(opts, s, None)?;
+            mime.retain_params(|name, _| is_kept(keep, name));
+            return Ok(mime);
+        }
+    }
+
+    let mut mime = parse_media_type(opts, Cow::Borrowed(s), keep)?;
+
+    if opts.permissive_values {
+        if let Some(fixed) = requote_permissive(s, &mime) {
+            let strict = Parser {
+                permissive_values: false,
+                ..*opts
+            };
+            return parse_trimmed(&strict, &fixed, None);
+        }
+    }
+
+    if opts.ows_around_equals || opts.permissive_values {
+        if let Some(tight) = drop_ows_around_equals(s, &mime) {
+            let strict = Parser {
+                ows_around_equals: false,
+                ..*opts
+            };
+            return parse_trimmed(&strict, &tight, None);
+        }
+    }
+
+    check_names(opts, &mime)?;
+
+    if opts.preserve_case {
+        // Lowercasing doesn't move any of the indices, and an empty param
+        // list is the only thing ever chopped off the end.
+        let original = &s[..mime.source.as_ref().len()];
+        if mime.source.as_ref() != original {
+            mime.source = Source::Dynamic(original.to_owned());
+        }
+    } else if opts.preserve_param_names {
+        let original = &s[..mime.source.as_ref().len()];
+        let mut restored: Option<String> = None;
+        for (name, _) in mime.param_ranges() {
+            if mime.source.as_ref()[name.clone()] != original[name.clone()] {
+                restored
+                    .get_or_insert_with(|| mime.source.as_ref().to_owned())
+                    .replace_range(name.clone(), &original[name]);
+            }
+        }
+        if let Some(restored) = restored {
+            mime.source = Source::Dynamic(restored);
+        }
+    }
+
+    Ok(mime)
+}
+
+pub(crate) fn parse_owned(opts: &Parser, src: String) -> Result<Mime, ParseError> {
+    // Trimming, requoting, and keeping any original case all need the
+    // source afterwards, so only the plain case can give it away.
+    let is_trimmed = !src.starts_with(is_http_whitespace) && !src.ends_with(is_http_whitespace);
+    if !is_trimmed || opts.permissive_values || opts.preserve_case || opts.preserve_param_names {
+        return parse(opts, &src);
+    }
+
+    if opts.reject_asterisks {
+        check_no_asterisks(&src)?;
+    }
+
+    let mime = parse_media_type(opts, Cow::Owned(src), None)?;
+    check_names(opts, &mime)?;
+    Ok(mime)
+}
+
+fn check_names(opts: &Parser, mime: &Mime) -> Result<(), ParseError> {
+    if opts.rfc6838_names {
+        check_restricted_name(mime.type_(), 0)?;
+
+        let subtype = mime.subtype();
+        if !(opts.can_range && subtype == "*") {
+            check_restricted_name(subtype, mime.slash as usize + 1)?;
+        }
+    }
+    Ok(())
+}
+
+fn check_no_asterisks(s: &str) -> Result<(), ParseError> {
+    match s.bytes().position(|b| b == b'*') {
+        Some(pos) => Err(ParseError::InvalidToken {
+            pos,
+            byte: Byte(b'*'),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn map_pos(err: ParseError, f: impl FnOnce(usize) -> usize) -> ParseError {
+    match err {
+        ParseError::InvalidToken { pos, byte } => ParseError::InvalidToken {
+            pos: f(pos),
+            byte,
+        },
+        ParseError::NonAsciiQuoted { pos, byte } => ParseError::NonAsciiQuoted {
+            pos: f(pos),
+            byte,
+        },
+        ParseError::LimitExceeded { which, pos } => ParseError::LimitExceeded {
+            which,
+            pos: f(pos),
+        },
+        ParseError::EmptySubtype { pos } => ParseError::EmptySubtype {
+            pos: f(pos),
+        },
+        err => err,
+    }
+}
+
+// The source is moved into the result when it has to be lowercased, so an
+// owned `s` doesn't need to be copied.
+fn parse_media_type(opts: &Parser, s: Cow<'_, str>, keep: Option<&[&str]>) -> Result<Mime, ParseError> {
+    if s.len() > u16::MAX as usize {
         return Err(ParseError::TooLong);
     }
 
@@ -79,7 +229,7 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
             },
             None => return Err(ParseError::MissingSlash), // EOF and no toplevel is no Mime
             Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                pos: pos,
+                pos,
                 byte: Byte(byte),
             }),
         };
@@ -92,11 +242,14 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
             Some((i, b'+')) if i > start => {
                 plus = Some(as_u16(i));
             },
-            Some((i, b';')) if i > start => {
+            Some((i, b';')) | Some((i, b' ')) if i == start && !opts.allow_empty_subtype => {
+                return Err(ParseError::EmptySubtype { pos: i });
+            },
+            Some((i, b';')) => {
                 start = i;
                 break;
             },
-            Some((i, b' ')) if i > start => {
+            Some((i, b' ')) => {
                 start = i;
                 break;
             },
@@ -122,6 +275,9 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
             },
 
             Some((_, c)) if is_token(c) => (),
+            None if s.len() == start && !opts.allow_empty_subtype => {
+                return Err(ParseError::EmptySubtype { pos: start });
+            },
             None => {
                 return Ok(Mime {
                     source: Atoms::intern(s, slash, InternParams::None),
@@ -131,14 +287,20 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
                 });
             },
             Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                pos: pos,
+                pos,
                 byte: Byte(byte),
             })
         };
     }
 
     // params
-    let params = params_from_str(s, &mut iter, start)?;
+    let (params, dropped) = params_from_str(opts, &s, &mut iter, start, keep)?;
+    let (s, params) = if dropped {
+        let (compact, params) = compact_params(&s, start, params);
+        (Cow::Owned(compact), params)
+    } else {
+        (s, params)
+    };
 
     let source = match params {
         ParamSource::None => {
@@ -149,12 +311,21 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
                 let b = s.as_bytes()[start];
                 b == b';' || b == b' '
             });
-            Atoms::intern(&s[..start], slash, InternParams::None)
+            let s = match s {
+                Cow::Borrowed(s) => Cow::Borrowed(&s[..start]),
+                Cow::Owned(mut s) => {
+                    s.truncate(start);
+                    Cow::Owned(s)
+                },
+            };
+            Atoms::intern(s, slash, InternParams::None)
+        },
+        ParamSource::KnownCharset(params_start, charset) => {
+            Atoms::intern(s, slash, InternParams::KnownCharset(params_start as usize, charset))
         },
-        ParamSource::Utf8(params_start) => Atoms::intern(s, slash, InternParams::Utf8(params_start as usize)),
-        ParamSource::One(params_start, a) => Source::Dynamic(lower_ascii_with_params(s, params_start as usize, &[a])),
-        ParamSource::Two(params_start, a, b) => Source::Dynamic(lower_ascii_with_params(s, params_start as usize, &[a, b])),
-        ParamSource::Custom(params_start, ref indices) => Source::Dynamic(lower_ascii_with_params(s, params_start as usize, indices)),
+        ParamSource::One(params_start, a) => Source::Dynamic(lower_ascii_with_params(s.into_owned(), params_start as usize, &[a])),
+        ParamSource::Two(params_start, a, b) => Source::Dynamic(lower_ascii_with_params(s.into_owned(), params_start as usize, &[a, b])),
+        ParamSource::Custom(params_start, ref indices) => Source::Dynamic(lower_ascii_with_params(s.into_owned(), params_start as usize, indices)),
     };
 
     Ok(Mime {
@@ -165,13 +336,256 @@ pub(crate) fn parse(opts: &Parser, src: impl Parse) -> Result<Mime, ParseError>
     })
 }
 
+pub(crate) fn parse_params(opts: &Parser, src: impl Parse) -> Result<Vec<(String, String)>, ParseError> {
+    // The parameter state machine starts right after a `;`, which is
+    // optional here, so one is added when missing.
+    let s = src.as_str();
+    if opts.reject_asterisks {
+        check_no_asterisks(s)?;
+    }
+    let (owned, offset) = match s.strip_prefix(';') {
+        Some(_) => (s.to_owned(), 0),
+        None => (format!(";{}", s), 1),
+    };
+    if owned.len() > u16::MAX as usize {
+        return Err(ParseError::TooLong);
+    }
+
+    let mut iter = owned.bytes().enumerate();
+    iter.next();
+    let (params, _) = params_from_str(opts, &owned, &mut iter, 0, None)
+        .map_err(|err| map_pos(err, |pos| pos - offset))?;
+
+    let indices = match params {
+        ParamSource::None => return Ok(Vec::new()),
+        ParamSource::KnownCharset(_, charset) => return Ok(vec![("charset".into(), charset.as_str().into())]),
+        ParamSource::One(_, a) => vec![a],
+        ParamSource::Two(_, a, b) => vec![a, b],
+        ParamSource::Custom(_, indices) => indices,
+    };
+    let lower = lower_ascii_with_params(owned, 0, &indices);
+
+    Ok(indices
+        .into_iter()
+        .map(|(name, value)| (lower[range(name)].to_owned(), lower[range(value)].to_owned()))
+        .collect())
+}
+
+pub(crate) fn parse_list(opts: &Parser, src: impl Parse) -> Result<Vec<Mime>, ParseError> {
+    let s = src.as_str();
+    let mut list = Vec::new();
+    let mut start = Some(0);
+
+    while let Some(i) = start {
+        let (entry, next) = parse_next_list_entry(opts, s, i);
+        list.push(entry?);
+        start = next;
+    }
+    Ok(list)
+}
+
+/// Parse the list entry starting at `start`, also returning where the one
+/// after it starts, if there is one.
+pub(crate) fn parse_next_list_entry(opts: &Parser, s: &str, start: usize) -> (Result<Mime, ParseError>, Option<usize>) {
+    match find_unquoted(&s[start..], b',') {
+        Some(i) => {
+            let comma = start + i;
+            (parse_list_entry(opts, s, start, comma, comma), Some(comma + 1))
+        },
+        None => {
+            // A trailing empty entry is blamed on the comma before it.
+            let comma = start.saturating_sub(1);
+            (parse_list_entry(opts, s, start, s.len(), comma), None)
+        },
+    }
+}
+
+pub(crate) fn parse_data_url<'a>(opts: &Parser, s: &'a str) -> Result<(Mime, bool, &'a str), ParseError> {
+    // From the [fetch standard](https://fetch.spec.whatwg.org/#data-urls),
+    // the media type is everything up to the first comma, with a final
+    // `;base64` taken off, and an empty one means `text/plain;charset=US-ASCII`.
+    const SCHEME: &str = "data:";
+
+    match s.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => (),
+        _ => return Err(ParseError::InvalidDataUrl),
+    }
+    let rest = &s[SCHEME.len()..];
+    let comma = rest.find(',').ok_or(ParseError::InvalidDataUrl)?;
+    let (mut media_type, data) = (&rest[..comma], &rest[comma + 1..]);
+
+    let mut base64 = false;
+    if let Some(semicolon) = media_type.rfind(';') {
+        if media_type[semicolon + 1..].trim_matches(is_http_whitespace).eq_ignore_ascii_case("base64") {
+            base64 = true;
+            media_type = &media_type[..semicolon];
+        }
+    }
+
+    let trimmed = media_type.trim_start_matches(is_http_whitespace);
+    let mime = if trimmed.trim_end_matches(is_http_whitespace).is_empty() {
+        Ok(constants::TEXT_PLAIN_US_ASCII)
+    } else if trimmed.starts_with(';') {
+        const DEFAULT: &str = "text/plain";
+        let offset = SCHEME.len() + media_type.len() - trimmed.len();
+        parse(opts, &*format!("{}{}", DEFAULT, trimmed))
+            .map_err(|err| map_pos(err, |pos| pos - DEFAULT.len() + offset))
+    } else {
+        parse(opts, media_type).map_err(|err| map_pos(err, |pos| pos + SCHEME.len()))
+    }?;
+
+    Ok((mime, base64, data))
+}
+
+pub(crate) fn parse_returning_remainder<'a>(opts: &Parser, s: &'a str, delimiter: u8) -> Result<(Mime, &'a str), ParseError> {
+    let end = find_unquoted(s, delimiter).unwrap_or(s.len());
+    let mime = parse(opts, &s[..end])?;
+    Ok((mime, &s[end..]))
+}
+
+// Finds the first `delimiter` that isn't inside a quoted string.
+fn find_unquoted(s: &str, delimiter: u8) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in s.bytes().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if quoted {
+            match c {
+                b'\\' => escaped = true,
+                b'"' => quoted = false,
+                _ => (),
+            }
+        } else if c == b'"' {
+            quoted = true;
+        } else if c == delimiter {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+pub(crate) fn parse_preferring_atoms(opts: &Parser, src: impl Parse, atoms: &[&'static str]) -> Result<Mime, ParseError> {
+    debug_assert!(
+        atoms.windows(2).all(|w| w[0] <= w[1]),
+        "parse_preferring_atoms called with unsorted atoms: {:?}",
+        atoms,
+    );
+
+    let mut mime = parse(opts, src)?;
+    if let Source::Dynamic(ref s) = mime.source {
+        // Only an exact match keeps every index in the `Mime` valid.
+        if let Ok(i) = atoms.binary_search(&&**s) {
+            // Atom 0 is static, but is still compared like a dynamic one.
+            mime.source = Source::Atom(0, atoms[i]);
+        }
+    }
+    Ok(mime)
+}
+
+pub(crate) fn parse_header_multi(opts: &Parser, values: &[&str]) -> Result<Mime, ParseError> {
+    // Repeated field lines mean the same as one line with their values
+    // joined by commas, and empty list entries don't count.
+    let mut found = None;
+    for value in values {
+        let mut start = 0;
+        loop {
+            let end = find_unquoted(&value[start..], b',').map_or(value.len(), |i| start + i);
+            if !value[start..end].trim_matches(is_http_whitespace).is_empty() {
+                if found.is_some() {
+                    return Err(ParseError::MultipleValues);
+                }
+                found = Some((*value, start, end));
+            }
+            if end == value.len() {
+                break;
+            }
+            start = end + 1;
+        }
+    }
+
+    match found {
+        // Positions are within the value the entry came from.
+        Some((value, start, end)) => {
+            parse(opts, &value[start..end]).map_err(|err| map_pos(err, |pos| pos + start))
+        },
+        None => parse(opts, ""),
+    }
+}
+
+pub(crate) fn parse_collecting_errors(opts: &Parser, src: impl Parse) -> Result<Mime, Vec<ParseError>> {
+    let s = src.as_str();
+    let mut first = match parse(opts, s) {
+        Ok(mime) => return Ok(mime),
+        Err(err) => Some(err),
+    };
+
+    // To find more errors, resynchronize at every `;` outside of quotes,
+    // and check the essence and each parameter on its own. The part that
+    // fails first is taken to be where `first` came from, since its
+    // error is more accurate than checking the part alone.
+    let mut errors = Vec::new();
+    let mut start = 0;
+    let mut is_essence = true;
+    loop {
+        let end = find_unquoted(&s[start..], b';').map_or(s.len(), |i| start + i);
+        let part = &s[start..end];
+
+        let result = if is_essence {
+            parse(opts, part).map(drop)
+        } else if part.trim_matches(is_http_whitespace).is_empty() {
+            Ok(())
+        } else {
+            parse_params(opts, part).map(drop)
+        };
+        if let Err(err) = result {
+            match first.take() {
+                Some(first) => errors.push(first),
+                None => errors.push(map_pos(err, |pos| pos + start)),
+            }
+        }
+
+        if end == s.len() {
+            break;
+        }
+        start = end + 1;
+        is_essence = false;
+    }
+
+    errors.extend(first);
+    Err(errors)
+}
+
+fn parse_list_entry(opts: &Parser, s: &str, start: usize, end: usize, comma: usize) -> Result<Mime, ParseError> {
+    let entry = &s[start..end];
+    let is_only_entry = start == 0 && end == s.len();
+    if !is_only_entry && entry.trim_matches(is_http_whitespace).is_empty() {
+        return Err(ParseError::InvalidToken {
+            pos: comma,
+            byte: Byte(b','),
+        });
+    }
+    parse(opts, entry).map_err(|err| map_pos(err, |pos| pos + start))
+}
 
-fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut start: usize) -> Result<ParamSource, ParseError> {
+// Params not named in `keep` are still checked, but left out of the
+// result, and the returned flag says whether any were.
+fn params_from_str(
+    opts: &Parser,
+    s: &str,
+    iter: &mut impl Iterator<Item=(usize, u8)>,
+    mut start: usize,
+    keep: Option<&[&str]>,
+) -> Result<(ParamSource, bool), ParseError> {
     let params_start = as_u16(start);
     start += 1;
     let mut params = ParamSource::None;
+    let mut dropped = false;
     'params: while start < s.len() {
         let name;
+        let mut name_end = None;
         // name
         'name: loop {
             match iter.next() {
@@ -185,15 +599,21 @@ fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut star
                     start = i + 1;
                     continue 'params;
                 },
-                Some((_, c)) if is_token(c) => (),
+                // OWS between the name and `=`
+                Some((i, b' ')) | Some((i, b'\t')) if i > start && opts.ows_around_equals => {
+                    name_end.get_or_insert(i);
+                },
+                // `*` isn't a token here, for ranges, but RFC 2231 uses it
+                // in parameter names.
+                Some((_, c)) if (is_token(c) || (c == b'*' && opts.rfc2231_names)) && name_end.is_none() => (),
                 Some((i, b'=')) if i > start => {
-                    name = (as_u16(start), as_u16(i));
+                    name = (as_u16(start), as_u16(name_end.unwrap_or(i)));
                     start = i + 1;
                     break 'name;
                 },
                 None => return Err(ParseError::MissingEqual),
                 Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                    pos: pos,
+                    pos,
                     byte: Byte(byte),
                 }),
             }
@@ -209,9 +629,15 @@ fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut star
                 if is_quoted_pair {
                     is_quoted_pair = false;
                     match iter.next() {
+                        Some((pos, byte)) if opts.ascii_quoted && !byte.is_ascii() => {
+                            return Err(ParseError::NonAsciiQuoted {
+                                pos,
+                                byte: Byte(byte),
+                            });
+                        },
                         Some((_, ch)) if is_restricted_quoted_char(ch) => (),
                         Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                            pos: pos,
+                            pos,
                             byte: Byte(byte),
                         }),
                         None => return Err(ParseError::MissingQuote),
@@ -225,10 +651,16 @@ fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut star
                             break 'value;
                         },
                         Some((_, b'\\')) => is_quoted_pair = true,
+                        Some((pos, byte)) if opts.ascii_quoted && !byte.is_ascii() => {
+                            return Err(ParseError::NonAsciiQuoted {
+                                pos,
+                                byte: Byte(byte),
+                            });
+                        },
                         Some((_, c)) if is_restricted_quoted_char(c) => (),
                         None => return Err(ParseError::MissingQuote),
                         Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                            pos: pos,
+                            pos,
                             byte: Byte(byte),
                         }),
                     }
@@ -239,33 +671,58 @@ fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut star
                         is_quoted = true;
                         start = i;
                     },
+                    // OWS between `=` and the value
+                    Some((i, b' ')) if i == start && (opts.ows_around_equals || opts.permissive_values) => {
+                        start = i + 1;
+                    },
+                    Some((i, b'\t')) if i == start && opts.ows_around_equals => {
+                        start = i + 1;
+                    },
                     Some((_, c)) if is_token(c) => (),
-                    Some((i, b' ')) |
-                    Some((i, b';')) if i > start => {
+                    // Anything that could be quoted runs until the next `;`.
+                    Some((_, c)) if opts.permissive_values && c != b';' && is_restricted_quoted_char(c) => (),
+                    Some((i, b' ')) if i > start => {
                         value = (as_u16(start), as_u16(i));
                         start = i + 1;
                         break 'value;
                     }
+                    // An empty value (`a=; b=2`) is allowed, just like an
+                    // empty value at the end, or empty quotes.
+                    Some((i, b';')) => {
+                        value = (as_u16(start), as_u16(unquoted_value_end(opts, s, start, i)));
+                        start = i + 1;
+                        break 'value;
+                    }
                     None => {
-                        value = (as_u16(start), as_u16(s.len()));
+                        value = (as_u16(start), as_u16(unquoted_value_end(opts, s, start, s.len())));
                         start = s.len();
                         break 'value;
                     },
 
                     Some((pos, byte)) => return Err(ParseError::InvalidToken {
-                        pos: pos,
+                        pos,
                         byte: Byte(byte),
                     }),
                 }
             }
         }
 
+        check_limit(opts.max_param_name_len, name, Limit::ParamNameLen)?;
+        check_limit(opts.max_param_value_len, value, Limit::ParamValueLen)?;
+
+        if let Some(keep) = keep {
+            if !is_kept(keep, &s[range(name)]) {
+                dropped = true;
+                continue 'params;
+            }
+        }
+
         match params {
-            ParamSource::Utf8(i) => {
+            ParamSource::KnownCharset(i, known) => {
                 let i = i + 2;
                 let charset = (i, "charset".len() as u16 + i);
-                let utf8 = (charset.1 + 1, charset.1 + "utf-8".len() as u16 + 1);
-                params = ParamSource::Two(params_start, (charset, utf8), (name, value));
+                let known = (charset.1 + 1, charset.1 + known.as_str().len() as u16 + 1);
+                params = ParamSource::Two(params_start, (charset, known), (name, value));
             },
             ParamSource::One(sc, a) => {
                 params = ParamSource::Two(sc, a, (name, value));
@@ -278,17 +735,139 @@ fn params_from_str(s: &str, iter: &mut impl Iterator<Item=(usize, u8)>, mut star
             },
             ParamSource::None => {
                 if params_start + 2 == name.0 &&
-                    "charset".eq_ignore_ascii_case(&s[range(name)]) &&
-                    "utf-8".eq_ignore_ascii_case(&s[range(value)]) {
-                    params = ParamSource::Utf8(params_start);
-                    continue 'params;
+                    name.1 + 1 == value.0 &&
+                    "charset".eq_ignore_ascii_case(&s[range(name)]) {
+                    if let Some(charset) = KnownCharset::lookup(&s[range(value)]) {
+                        params = ParamSource::KnownCharset(params_start, charset);
+                        continue 'params;
+                    }
                 }
                 params = ParamSource::One(params_start, (name, value));
             },
         }
     }
-    Ok(params)
+    Ok((params, dropped))
+}
+
+fn is_kept(keep: &[&str], name: &str) -> bool {
+    keep.iter().any(|kept| kept.eq_ignore_ascii_case(name))
+}
+
+// Rewrites `s` with only the params that `params_from_str` kept, so that
+// the gaps left by the dropped ones don't end up in the source. Each kept
+// param is copied with the separator before it, so this can't get longer.
+fn compact_params(s: &str, start: usize, params: ParamSource) -> (String, ParamSource) {
+    let pairs = match params {
+        ParamSource::None => {
+            // Like any other empty param list, this gets chopped off.
+            return (s[..=start].to_owned(), ParamSource::None);
+        },
+        ParamSource::KnownCharset(_, charset) => {
+            // Only ever found right at the start, so everything after it
+            // was dropped.
+            let end = start + "; charset=".len() + charset.as_str().len();
+            return (s[..end].to_owned(), params);
+        },
+        ParamSource::One(_, a) => vec![a],
+        ParamSource::Two(_, a, b) => vec![a, b],
+        ParamSource::Custom(_, pairs) => pairs,
+    };
+
+    let mut compact = String::with_capacity(s.len());
+    compact.push_str(&s[..start]);
+    let mut indices = Vec::with_capacity(pairs.len());
+    for (name, value) in pairs {
+        let sep = s[start..name.0 as usize].rfind(';').map_or(start, |i| start + i);
+        // Both only move back, by the same amount.
+        let shift = as_u16(sep - compact.len());
+        compact.push_str(&s[sep..value.1 as usize]);
+        indices.push(((name.0 - shift, name.1 - shift), (value.0 - shift, value.1 - shift)));
+    }
+
+    let params_start = as_u16(start);
+    let mut indices = indices.into_iter();
+    let params = match indices.len() {
+        1 => {
+            let (name, value) = indices.next().unwrap();
+            let charset = if params_start + 2 == name.0 &&
+                &compact[start..start + 2] == "; " &&
+                "charset".eq_ignore_ascii_case(&compact[range(name)]) {
+                KnownCharset::lookup(&compact[range(value)])
+            } else {
+                None
+            };
+            match charset {
+                Some(charset) => ParamSource::KnownCharset(params_start, charset),
+                None => ParamSource::One(params_start, (name, value)),
+            }
+        },
+        2 => ParamSource::Two(params_start, indices.next().unwrap(), indices.next().unwrap()),
+        _ => ParamSource::Custom(params_start, indices.collect()),
+    };
+    (compact, params)
+}
+
+fn check_limit(max: Option<usize>, (start, end): (u16, u16), which: Limit) -> Result<(), ParseError> {
+    match max {
+        Some(max) if (end - start) as usize > max => Err(ParseError::LimitExceeded {
+            which,
+            pos: start as usize + max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+// A permissive value may end with whitespace before the `;`.
+fn unquoted_value_end(opts: &Parser, s: &str, start: usize, end: usize) -> usize {
+    if opts.permissive_values {
+        start + s[start..end].trim_end_matches(is_http_whitespace).len()
+    } else {
+        end
+    }
+}
+
+// Quotes any unquoted value that isn't a token, so the source is valid
+// without `permissive_values`. The parameters are joined by `; `.
+fn requote_permissive(s: &str, mime: &Mime) -> Option<String> {
+    let needs_quotes = |value: &str| !value.is_empty() && !value.starts_with('"') && !is_token_str(value);
+    if !mime.param_ranges().any(|(_, value)| needs_quotes(&s[value])) {
+        return None;
+    }
+
+    let mut fixed = s[..mime.semicolon_or_end()].to_owned();
+    for (name, value) in mime.param_ranges() {
+        let value = &s[value];
+        fixed.push_str("; ");
+        fixed.push_str(&s[name]);
+        fixed.push('=');
+        if needs_quotes(value) {
+            fixed.push_str(&crate::quote_value(value));
+        } else {
+            fixed.push_str(value);
+        }
+    }
+    Some(fixed)
+}
+
+// Removes any OWS that was allowed around a `=`, and whatever trails the
+// last param, so the source parses without `ows_around_equals`. The rest,
+// including the separators between params, is kept as it was.
+fn drop_ows_around_equals(s: &str, mime: &Mime) -> Option<String> {
+    if !mime.param_ranges().any(|(name, value)| name.end + 1 != value.start) {
+        return None;
+    }
+
+    let mut end = mime.semicolon_or_end();
+    let mut tight = s[..end].to_owned();
+    for (name, value) in mime.param_ranges() {
+        tight.push_str(&s[end..name.end]);
+        tight.push('=');
+        tight.push_str(&s[value.clone()]);
+        end = value.end;
+    }
+    Some(tight)
 }
+
 macro_rules! byte_map {
     ($($flag:expr,)*) => ([
         $($flag != 0,)*
@@ -314,10 +893,119 @@ static TOKEN_MAP: [bool; 256] = byte_map![
     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
 ];
 
+// The RFC 6838 `restricted-name-chars`, a subset of the `tchar`s above.
+static RESTRICTED_NAME_MAP: [bool; 256] = byte_map![
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 1, 0, 1, 1, 0, 1, 0, 0, 0, 0, 1, 0, 1, 1, 0,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0,
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 1, 1,
+    0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+];
+
 fn is_token(c: u8) -> bool {
     TOKEN_MAP[c as usize]
 }
 
+fn is_restricted_name_first(c: u8) -> bool {
+    c.is_ascii_alphanumeric()
+}
+
+fn is_restricted_name_char(c: u8) -> bool {
+    RESTRICTED_NAME_MAP[c as usize]
+}
+
+// The type and subtype are at the same positions in the parsed source as
+// in the original string, so `offset` can be used for error positions.
+fn check_restricted_name(s: &str, offset: usize) -> Result<(), ParseError> {
+    if s == "*" && offset == 0 {
+        // `*/*`, already checked to be a range
+        return Ok(());
+    }
+
+    let invalid = s.bytes().enumerate().find(|&(i, c)| {
+        if i == 0 {
+            !is_restricted_name_first(c)
+        } else {
+            i >= 127 || !is_restricted_name_char(c)
+        }
+    });
+
+    match invalid {
+        Some((i, byte)) => Err(ParseError::InvalidToken {
+            pos: offset + i,
+            byte: Byte(byte),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn is_token_str(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_token)
+}
+
+/// Finds the first byte of `s` that isn't a `tchar`.
+pub(crate) fn check_token(s: &str) -> Result<(), ParseError> {
+    match s.bytes().enumerate().find(|&(_, c)| !is_token(c)) {
+        Some((pos, byte)) => Err(ParseError::InvalidToken {
+            pos,
+            byte: Byte(byte),
+        }),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn check_name(s: &str, component: Component) -> Result<(), ParseError> {
+    let invalid = s.bytes().enumerate().find(|&(i, c)| {
+        if i == 0 {
+            !is_restricted_name_first(c)
+        } else {
+            !is_token(c)
+        }
+    });
+
+    match invalid {
+        Some((pos, byte)) => Err(ParseError::InvalidName {
+            component,
+            pos,
+            byte: Some(Byte(byte)),
+        }),
+        None if s.is_empty() => Err(ParseError::InvalidName {
+            component,
+            pos: 0,
+            byte: None,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks a type or subtype against the RFC 6838 `restricted-name` rule.
+pub(crate) fn is_restricted_name(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    match bytes.split_first() {
+        Some((&first, rest)) => {
+            bytes.len() <= 127 &&
+                is_restricted_name_first(first) &&
+                rest.iter().all(|&c| is_restricted_name_char(c))
+        },
+        None => false,
+    }
+}
+
+fn is_http_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
 fn is_restricted_quoted_char(c: u8) -> bool {
     c == 9 || (c > 31 && c != 127)
 }
@@ -332,7 +1020,7 @@ mod tests {
     fn test_lookup_tables() {
         for (i, &valid) in super::TOKEN_MAP.iter().enumerate() {
             let i = i as u8;
-            let should = match i {
+            let should = matches!(i,
                 b'a'..=b'z' |
                 b'A'..=b'Z' |
                 b'0'..=b'9' |
@@ -349,28 +1037,122 @@ mod tests {
                 b'_' |
                 b'`' |
                 b'|' |
-                b'~' => true,
-                _ => false
-            };
+                b'~'
+            );
             assert_eq!(valid, should, "{:?} ({}) should be {}", i as char, i, should);
         }
     }
 
     #[test]
-    fn text_plain() {
-        let mime = parse("text/plain").unwrap();
-        assert_eq!(mime.type_(), "text");
-        assert_eq!(mime.subtype(), "plain");
-        assert!(!mime.has_params());
-        assert_eq!(mime.as_ref(), "text/plain");
+    fn test_restricted_name_lookup_table() {
+        for (i, &valid) in super::RESTRICTED_NAME_MAP.iter().enumerate() {
+            let i = i as u8;
+            let should = matches!(i,
+                b'a'..=b'z' |
+                b'A'..=b'Z' |
+                b'0'..=b'9' |
+                b'!' |
+                b'#' |
+                b'$' |
+                b'&' |
+                b'-' |
+                b'^' |
+                b'_' |
+                b'.' |
+                b'+'
+            );
+            assert_eq!(valid, should, "{:?} ({}) should be {}", i as char, i, should);
+            if valid {
+                assert!(super::is_token(i), "{:?} ({}) must be a token", i as char, i);
+            }
+        }
     }
 
     #[test]
-    fn text_plain_uppercase() {
-        let mime = parse("TEXT/PLAIN").unwrap();
-        assert_eq!(mime.type_(), "text");
-        assert_eq!(mime.subtype(), "plain");
-        assert!(!mime.has_params());
+    fn restricted_names() {
+        use super::is_restricted_name;
+
+        assert!(is_restricted_name("text"));
+        assert!(is_restricted_name("vnd.api+json"));
+        assert!(is_restricted_name("x-foo_bar^1&2#3$4!"));
+        assert!(is_restricted_name("3gpp"));
+
+        assert!(!is_restricted_name(""));
+        assert!(!is_restricted_name("foo%bar"));
+        assert!(!is_restricted_name("foo'bar"));
+        assert!(!is_restricted_name("foo`bar"));
+        assert!(!is_restricted_name("foo|bar"));
+        assert!(!is_restricted_name("foo~bar"));
+        assert!(!is_restricted_name("*"));
+        assert!(!is_restricted_name("-foo"));
+        assert!(!is_restricted_name(".foo"));
+        assert!(!is_restricted_name("+json"));
+
+        assert!(is_restricted_name(&"a".repeat(127)));
+        assert!(!is_restricted_name(&"a".repeat(128)));
+    }
+
+    fn parse_rfc6838(src: &str, can_range: bool) -> Result<super::Mime, super::ParseError> {
+        let parser = if can_range {
+            super::Parser::can_range()
+        } else {
+            super::Parser::cannot_range()
+        };
+        parser.rfc6838_names().parse(src)
+    }
+
+    #[test]
+    fn rfc6838_names() {
+        parse_rfc6838("text/plain", false).unwrap();
+        parse_rfc6838("application/vnd.api+json; q=%~", false).unwrap();
+        parse_rfc6838("TEXT/X-FOO", false).unwrap();
+        parse_rfc6838("text/*", true).unwrap();
+        parse_rfc6838("*/*", true).unwrap();
+        parse_rfc6838("text/*", false).unwrap_err();
+        parse_rfc6838("*/*", false).unwrap_err();
+
+        // they are fine HTTP tokens...
+        for &(src, pos) in &[
+            ("text/foo%bar", 8),
+            ("text/foo'", 8),
+            ("text/`foo", 5),
+            ("te|xt/plain", 2),
+            ("text/foo~", 8),
+            ("_text/plain", 0),
+            ("text/.foo", 5),
+            ("text/+json", 5),
+        ] {
+            parse(src).expect(src);
+            match parse_rfc6838(src, true) {
+                Err(super::ParseError::InvalidToken { pos: p, .. }) => {
+                    assert_eq!(p, pos, "{:?}", src);
+                },
+                other => panic!("{:?} should be invalid: {:?}", src, other),
+            }
+        }
+
+        let long = format!("text/{}", "a".repeat(128));
+        match parse_rfc6838(&long, false) {
+            Err(super::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 5 + 127),
+            other => panic!("should be invalid: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn text_plain() {
+        let mime = parse("text/plain").unwrap();
+        assert_eq!(mime.type_(), "text");
+        assert_eq!(mime.subtype(), "plain");
+        assert!(!mime.has_params());
+        assert_eq!(mime.as_ref(), "text/plain");
+    }
+
+    #[test]
+    fn text_plain_uppercase() {
+        let mime = parse("TEXT/PLAIN").unwrap();
+        assert_eq!(mime.type_(), "text");
+        assert_eq!(mime.subtype(), "plain");
+        assert!(!mime.has_params());
         assert_eq!(mime.as_ref(), "text/plain");
     }
 
@@ -421,6 +1203,152 @@ mod tests {
         assert_eq!(mime.as_ref(), "text/plain; charset=utf-8; foo=BAR");
     }
 
+    #[test]
+    fn text_plain_known_charsets() {
+        use crate::{KnownCharset, ParamSource, Source};
+
+        for &(src, charset, canonical) in &[
+            ("text/plain; charset=utf-8", KnownCharset::Utf8, "text/plain; charset=utf-8"),
+            ("TEXT/HTML; Charset=US-ASCII", KnownCharset::UsAscii, "text/html; charset=us-ascii"),
+            ("text/plain; charset=ISO-8859-1", KnownCharset::Iso8859_1, "text/plain; charset=iso-8859-1"),
+            ("text/csv; charset=us-ascii", KnownCharset::UsAscii, "text/csv; charset=us-ascii"),
+        ] {
+            let mime = parse(src).unwrap();
+            match mime.params {
+                ParamSource::KnownCharset(_, c) => assert_eq!(c, charset, "{:?}", src),
+                _ => panic!("{:?} should use a known charset", src),
+            }
+            assert_eq!(mime.as_ref(), canonical);
+            assert_eq!(mime.params().collect::<Vec<_>>(), [("charset", charset.as_str())]);
+        }
+
+        // Only text/plain and text/html have atoms for these.
+        assert!(matches!(parse("text/html; charset=us-ascii").unwrap().source, Source::Atom(..)));
+        assert!(matches!(parse("text/plain; charset=iso-8859-1").unwrap().source, Source::Atom(..)));
+        assert!(matches!(parse("text/csv; charset=us-ascii").unwrap().source, Source::Dynamic(..)));
+
+        let extra = parse("text/plain; charset=us-ascii; format=flowed").unwrap();
+        assert_eq!(extra.param("charset"), Some("us-ascii"));
+        assert_eq!(extra.param("format"), Some("flowed"));
+        assert_eq!(extra.as_ref(), "text/plain; charset=us-ascii; format=flowed");
+
+        // Aliases and unknown charsets keep the general representation.
+        for src in &["text/plain; charset=latin1", "text/plain; charset=\"us-ascii\""] {
+            match parse(*src).unwrap().params {
+                ParamSource::One(..) => (),
+                _ => panic!("{:?} should have one plain param", src),
+            }
+        }
+    }
+
+    #[test]
+    fn param_name_lowercasing_is_consistent() {
+        use crate::ParamSource;
+
+        // Each source starts with a known charset, then gains more params,
+        // moving through every `ParamSource` with params.
+        for &(src, expected) in &[
+            ("Text/Plain; Charset=UTF-8", "text/plain; charset=utf-8"),
+            ("Text/Plain; Charset=UTF-8; Boundary=X", "text/plain; charset=utf-8; boundary=X"),
+            ("Text/Plain; Charset=UTF-8; Boundary=X; Format=Flowed", "text/plain; charset=utf-8; boundary=X; format=Flowed"),
+            ("Text/Plain; Boundary=X", "text/plain; boundary=X"),
+            ("Text/Plain; Boundary=X; Charset=UTF-8", "text/plain; boundary=X; charset=utf-8"),
+            ("Text/Plain; Charset=\"UTF-8\"; Boundary=X", "text/plain; charset=\"utf-8\"; boundary=X"),
+            ("Text/Plain; Charset=Latin1", "text/plain; charset=latin1"),
+        ] {
+            let borrowed = parse(src).unwrap();
+            let owned = super::Parser::cannot_range().parse_cow(src.to_owned().into()).unwrap();
+            assert_eq!(borrowed.as_ref(), expected);
+            assert_eq!(owned.as_ref(), expected);
+
+            for (name, _) in borrowed.params() {
+                assert!(!name.bytes().any(|b| b.is_ascii_uppercase()), "{:?} in {:?}", name, src);
+            }
+            assert_eq!(borrowed.param("BOUNDARY"), borrowed.param("boundary"));
+        }
+
+        let kinds = ["text/plain; Charset=UTF-8", "text/plain; Charset=UTF-8; A=B", "text/plain; Charset=UTF-8; A=B; C=D"]
+            .iter()
+            .map(|src| match parse(*src).unwrap().params {
+                ParamSource::KnownCharset(..) => "known",
+                ParamSource::Two(..) => "two",
+                ParamSource::Custom(..) => "custom",
+                _ => "other",
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, ["known", "two", "custom"]);
+
+        // Parsing only params follows the same rule.
+        let params = super::Parser::cannot_range().parse_params("Charset=UTF-8; Boundary=X").unwrap();
+        assert_eq!(params, [("charset".into(), "utf-8".into()), ("boundary".into(), "X".into())]);
+    }
+
+    #[test]
+    fn preserve_case() {
+        let parser = super::Parser::cannot_range().preserve_case();
+        for &src in &[
+            "TEXT/PLAIN",
+            "Text/Plain; Charset=UTF-8",
+            "text/plain; charset=US-ASCII; Format=Flowed",
+            "Application/Vnd.Api+JSON; A=1; B=\"2\"; C=3",
+        ] {
+            let mime = parser.parse(src).unwrap();
+            let lower = parse(src).unwrap();
+            assert_eq!(mime.as_ref(), src);
+            assert_eq!(mime.type_len(), lower.type_len(), "{:?}", src);
+            assert_eq!(mime.subtype_len(), lower.subtype_len(), "{:?}", src);
+            assert_eq!(mime.suffix().map(str::to_ascii_lowercase).as_deref(), lower.suffix(), "{:?}", src);
+            assert_eq!(mime.params().count(), lower.params().count(), "{:?}", src);
+        }
+
+        assert_eq!(parser.parse("Text/Plain;").unwrap().as_ref(), "Text/Plain");
+        assert!(matches!(parser.parse("text/plain").unwrap().source, crate::Source::Atom(..)));
+    }
+
+    #[test]
+    fn preserve_param_names() {
+        let parser = super::Parser::cannot_range().preserve_param_names();
+        let mime = parser.parse("Text/Plain; X-Custom-Param=A; Charset=UTF-8").unwrap();
+        assert_eq!(mime.as_ref(), "text/plain; X-Custom-Param=A; Charset=utf-8");
+        assert_eq!(mime.param("x-custom-param"), Some("A"));
+        assert_eq!(mime.param("CHARSET"), Some("utf-8"));
+
+        let owned = parser.parse_cow(std::borrow::Cow::Owned("text/plain; Charset=utf-8".to_owned())).unwrap();
+        assert_eq!(owned.as_ref(), "text/plain; Charset=utf-8");
+
+        // Lowercase names are still interned.
+        let atom = parser.parse("text/plain; charset=utf-8").unwrap();
+        assert!(matches!(atom.source, crate::Source::Atom(..)));
+    }
+
+    #[test]
+    fn param_names_lowercased_values_kept() {
+        let mime = parse(r#"message/external-body; access-type=URL; URL="ftp://EXAMPLE/Doc""#).unwrap();
+        assert_eq!(mime.essence(), "message/external-body");
+        assert_eq!(mime.param("access-type"), Some("URL"));
+        assert_eq!(mime.param("url"), Some(r#""ftp://EXAMPLE/Doc""#));
+        assert_eq!(mime.param("URL"), Some(r#""ftp://EXAMPLE/Doc""#));
+        assert_eq!(
+            mime.as_ref(),
+            r#"message/external-body; access-type=URL; url="ftp://EXAMPLE/Doc""#
+        );
+    }
+
+    #[test]
+    fn param_names_lowercased_many_params() {
+        let mime = parse("Message/External-Body; Access-Type=LOCAL-FILE; NAME=Foo.TXT; Site=Example.COM").unwrap();
+        let params = mime.params().collect::<Vec<_>>();
+        assert_eq!(params, [
+            ("access-type", "LOCAL-FILE"),
+            ("name", "Foo.TXT"),
+            ("site", "Example.COM"),
+        ]);
+        assert_eq!(
+            mime.as_ref(),
+            "message/external-body; access-type=LOCAL-FILE; name=Foo.TXT; site=Example.COM"
+        );
+    }
+
     #[test]
     fn charset_utf8_extra_spaces() {
         let mime = parse("text/plain  ;  charset=utf-8  ;  foo=bar").unwrap();
@@ -453,9 +1381,726 @@ mod tests {
         assert_eq!(mime.as_ref(), "audio/wave; codecs=\"\"");
     }
 
+    #[test]
+    fn param_value_empty() {
+        let mime = parse("text/plain; a=; b=2").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", ""), ("b", "2")]);
+        assert_eq!(mime.as_ref(), "text/plain; a=; b=2");
+
+        let mime = parse("text/plain; a=").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", "")]);
+
+        let mime = parse("text/plain; a=;").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", "")]);
+
+        let mime = parse("text/plain; a=\"\"; b=2").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", "\"\""), ("b", "2")]);
+
+        let mime = parse("text/plain; charset=").unwrap();
+        assert_eq!(mime.param("charset"), Some(""));
+    }
+
+    #[test]
+    fn param_value_many_quoted_pairs() {
+        // Sources are indexed with `u16`s, so this is as long as a value
+        // can get. Something like a 1 MB value is rejected as `TooLong`
+        // before it is scanned at all.
+        let mut s = String::from("text/plain; name=\"");
+        while s.len() < u16::MAX as usize - 2 {
+            s.push_str("\\\"");
+        }
+        s.push('"');
+        assert_eq!(s.len(), u16::MAX as usize);
+
+        let mime = parse(&s).expect("many quoted-pairs");
+        assert_eq!(mime.param("name").map(str::len), Some(s.len() - "text/plain; name=".len()));
+
+        // A dangling escape right before the end is still caught.
+        s.truncate(s.len() - 1);
+        s.push('\\');
+        parse(&s).unwrap_err();
+
+        s.push('"');
+        assert!(matches!(parse(&s), Err(super::ParseError::TooLong)));
+    }
+
+    #[test]
+    fn param_value_quoted_pairs_linear() {
+        // Counts the bytes the state machine pulls, which should be each
+        // one once, no matter how many escapes there are.
+        fn steps(pairs: usize) -> (usize, usize) {
+            let s = format!("; name=\"{}\"", "\\\"".repeat(pairs));
+            let count = std::cell::Cell::new(0);
+            let mut iter = s.bytes().enumerate().inspect(|_| count.set(count.get() + 1));
+            iter.next();
+            super::params_from_str(&super::Parser::cannot_range(), &s, &mut iter, 0, None)
+                .expect("many quoted-pairs");
+            (count.get(), s.len())
+        }
+
+        let (n, len) = steps(8 * 1024);
+        assert_eq!(n, len);
+        let (n2, len2) = steps(16 * 1024);
+        assert_eq!(n2, len2);
+        assert!(n2 <= 2 * n, "{} vs {}", n, n2);
+    }
+
+    #[test]
+    fn params_only() {
+        let parse_params = |s: &str| super::Parser::cannot_range().parse_params(s);
+
+        let params = parse_params("Name=foo; CHARSET=UTF-8; title=\"a b\"").unwrap();
+        assert_eq!(params, [
+            ("name".to_owned(), "foo".to_owned()),
+            ("charset".to_owned(), "utf-8".to_owned()),
+            ("title".to_owned(), "\"a b\"".to_owned()),
+        ]);
+
+        assert_eq!(parse_params("; a=1").unwrap(), [("a".to_owned(), "1".to_owned())]);
+        assert_eq!(parse_params("charset=utf-8").unwrap(), [("charset".to_owned(), "utf-8".to_owned())]);
+        assert!(parse_params("").unwrap().is_empty());
+        assert!(parse_params(";").unwrap().is_empty());
+
+        match parse_params("a=1; b c=2") {
+            Err(crate::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 6),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse_params(";a=1; b c=2") {
+            Err(crate::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 7),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        parse_params("a").unwrap_err();
+    }
+
+    #[test]
+    fn replace_type() {
+        let mime = parse("application/vnd.api+json; charset=utf-8").unwrap();
+        let text = mime.replace_type("TEXT").unwrap();
+        assert_eq!(text.as_ref(), "text/vnd.api+json; charset=utf-8");
+        assert_eq!(text.type_(), "text");
+        assert_eq!(text.subtype(), "vnd.api+json");
+        assert_eq!(text.suffix(), Some("json"));
+        assert_eq!(text.param("charset"), Some("utf-8"));
+        assert_eq!(text.replace_type("application").unwrap().as_ref(), mime.as_ref());
+    }
+
+    #[test]
+    fn with_suffix() {
+        let mime = parse("application/vnd.api; ext=bulk").unwrap();
+
+        let json = mime.with_suffix(Some("JSON")).unwrap();
+        assert_eq!(json.as_ref(), "application/vnd.api+json; ext=bulk");
+        assert_eq!(json.suffix(), Some("json"));
+        assert_eq!(json.param("ext"), Some("bulk"));
+
+        let cbor = json.with_suffix(Some("cbor")).unwrap();
+        assert_eq!(cbor.as_ref(), "application/vnd.api+cbor; ext=bulk");
+
+        let none = cbor.with_suffix(None).unwrap();
+        assert_eq!(none.as_ref(), mime.as_ref());
+        assert_eq!(none.suffix(), None);
+        assert_eq!(mime.with_suffix(None).unwrap().as_ref(), mime.as_ref());
+
+        for &(suffix, pos, byte) in &[("", 0, b'+'), ("a+b", 1, b'+'), ("x;", 1, b';'), ("j son", 1, b' ')] {
+            match mime.with_suffix(Some(suffix)) {
+                Err(super::ParseError::InvalidToken { pos: p, byte: b }) => {
+                    assert_eq!((p, b.0), (pos, byte), "{:?}", suffix);
+                },
+                other => panic!("{:?} => {:?}", suffix, other),
+            }
+        }
+    }
+
+    #[test]
+    fn replace_type_errors() {
+        let mime = parse("text/plain").unwrap();
+        for &(new_type, pos, byte) in &[
+            ("", 0, b'/'),
+            ("te/xt", 2, b'/'),
+            ("te xt", 2, b' '),
+            ("*", 0, b'*'),
+            ("text;", 4, b';'),
+        ] {
+            match mime.replace_type(new_type) {
+                Err(super::ParseError::InvalidToken { pos: p, byte: b }) => {
+                    assert_eq!((p, b.0), (pos, byte), "{:?}", new_type);
+                },
+                other => panic!("{:?} => {:?}", new_type, other),
+            }
+        }
+    }
+
+    #[test]
+    fn quoted_obs_text() {
+        // U+0080 is encoded as 0xC2 0x80.
+        let src = "text/plain; name=\"a\u{80}b\"";
+        let mime = parse(src).unwrap();
+        assert_eq!(mime.param("name"), Some("\"a\u{80}b\""));
+
+        let strict = super::Parser::cannot_range().ascii_quoted();
+        match strict.parse(src) {
+            Err(super::ParseError::NonAsciiQuoted { pos, byte }) => {
+                assert_eq!((pos, byte.0), (19, 0xc2));
+            },
+            other => panic!("{:?}", other),
+        }
+
+        // In a quoted-pair as well.
+        match strict.parse("text/plain; name=\"a\\\u{80}\"") {
+            Err(super::ParseError::NonAsciiQuoted { pos, .. }) => assert_eq!(pos, 20),
+            other => panic!("{:?}", other),
+        }
+
+        strict.parse("text/plain; name=\"a\tb\\\"c\"").unwrap();
+        match strict.parse_params(" name=\"\u{80}\"") {
+            Err(super::ParseError::NonAsciiQuoted { pos, .. }) => assert_eq!(pos, 7),
+            other => panic!("{:?}", other),
+        }
+        // Unquoted values were never allowed to have them.
+        match strict.parse("text/plain; name=\u{80}") {
+            Err(super::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 17),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_list() {
+        let list = super::Parser::cannot_range()
+            .parse_list("text/html, application/ld+json; profile=\"a,b\" ,\timage/png")
+            .unwrap();
+        let list = list.iter().map(|m| m.as_ref()).collect::<Vec<_>>();
+        assert_eq!(list, ["text/html", "application/ld+json; profile=\"a,b\"", "image/png"]);
+
+        let one = super::Parser::cannot_range().parse_list(" Text/Plain ").unwrap();
+        assert_eq!(one.len(), 1);
+        assert_eq!(one[0].as_ref(), "text/plain");
+
+        let escaped = super::Parser::cannot_range()
+            .parse_list(r#"text/plain; a="\",", text/html"#)
+            .unwrap();
+        assert_eq!(escaped.len(), 2);
+        assert_eq!(escaped[0].param("a"), Some(r#""\",""#));
+    }
+
+    #[test]
+    fn parse_list_errors() {
+        fn err_pos(src: &str) -> (usize, u8) {
+            match super::Parser::cannot_range().parse_list(src) {
+                Err(super::ParseError::InvalidToken { pos, byte }) => (pos, byte.0),
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+
+        assert_eq!(err_pos("text/html, , image/png"), (11, b','));
+        assert_eq!(err_pos(",text/html"), (0, b','));
+        assert_eq!(err_pos("text/html,"), (9, b','));
+        assert_eq!(err_pos("text/html,  "), (9, b','));
+        assert_eq!(err_pos("text/html, image/p@ng"), (18, b'@'));
+
+        super::Parser::cannot_range().parse_list("").unwrap_err();
+        match super::Parser::cannot_range().parse_list("text/html, */*") {
+            Err(super::ParseError::InvalidRange) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn reject_asterisks() {
+        fn err_pos(parser: &super::Parser, src: &str) -> (usize, u8) {
+            match parser.parse(src) {
+                Err(super::ParseError::InvalidToken { pos, byte }) => (pos, byte.0),
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+
+        // Without the mode, `*` is already never a token, but the error
+        // depends on where it shows up.
+        let lenient = super::Parser::cannot_range();
+        assert_eq!(err_pos(&lenient, "te*t/plain"), (2, b'*'));
+        assert_eq!(err_pos(&lenient, "text/pl*n"), (7, b'*'));
+        assert_eq!(err_pos(&lenient, "*/plain"), (0, b'*'));
+        assert_eq!(err_pos(&lenient, "text/*"), (5, b'*'));
+        assert_eq!(err_pos(&lenient, "text/plain; a*=b"), (13, b'*'));
+        assert_eq!(err_pos(&lenient, "text/plain; a=b*"), (15, b'*'));
+        match lenient.parse("*/*") {
+            Err(super::ParseError::InvalidRange) => (),
+            other => panic!("{:?}", other),
+        }
+        lenient.parse("text/plain; a=\"*\"").unwrap();
+
+        for parser in &[super::Parser::cannot_range().reject_asterisks(), super::Parser::can_range().reject_asterisks()] {
+            assert_eq!(err_pos(parser, "te*t/plain"), (2, b'*'));
+            assert_eq!(err_pos(parser, "text/pl*n"), (7, b'*'));
+            assert_eq!(err_pos(parser, "*/plain"), (0, b'*'));
+            assert_eq!(err_pos(parser, "*/*"), (0, b'*'));
+            assert_eq!(err_pos(parser, "text/*"), (5, b'*'));
+            assert_eq!(err_pos(parser, " text/*; q=1"), (6, b'*'));
+            assert_eq!(err_pos(parser, "text/plain; a*=b"), (13, b'*'));
+            assert_eq!(err_pos(parser, "text/plain; a=b*"), (15, b'*'));
+            assert_eq!(err_pos(parser, "text/plain; a=\"*\""), (15, b'*'));
+            // Reported ahead of the earlier bad byte.
+            assert_eq!(err_pos(parser, "te@t/pl*n"), (7, b'*'));
+            parser.parse("text/plain; charset=utf-8").unwrap();
+        }
+
+        let err = super::Parser::cannot_range().reject_asterisks().parse("text/pl*n").unwrap_err();
+        assert_eq!(err.to_string(), "unexpected asterisk, '*' at position 7");
+        match super::Parser::cannot_range().reject_asterisks().parse_params("a=\"*\"") {
+            Err(super::ParseError::InvalidToken { pos: 3, .. }) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_name() {
+        use crate::{validate_name, Component};
+
+        validate_name("text", "plain").unwrap();
+        validate_name("application", "vnd.api+json").unwrap();
+        validate_name("x-custom", "x_thing").unwrap();
+        validate_name("1d", "2d").unwrap();
+
+        for &(type_, subtype, component, pos, byte) in &[
+            ("", "plain", Component::Type, 0, None),
+            ("text", "", Component::Subtype, 0, None),
+            ("", "", Component::Type, 0, None),
+            ("-text", "plain", Component::Type, 0, Some(b'-')),
+            ("te xt", "plain", Component::Type, 2, Some(b' ')),
+            ("text/", "plain", Component::Type, 4, Some(b'/')),
+            ("*", "plain", Component::Type, 0, Some(b'*')),
+            ("text", "+json", Component::Subtype, 0, Some(b'+')),
+            ("text", "pl@in", Component::Subtype, 2, Some(b'@')),
+            ("text", "*", Component::Subtype, 0, Some(b'*')),
+            ("text", "plain; charset=utf-8", Component::Subtype, 5, Some(b';')),
+        ] {
+            match validate_name(type_, subtype) {
+                Err(super::ParseError::InvalidName { component: c, pos: p, byte: b }) => {
+                    assert_eq!((c, p, b.map(|b| b.0)), (component, pos, byte), "{:?}", (type_, subtype));
+                },
+                other => panic!("{:?} => {:?}", (type_, subtype), other),
+            }
+        }
+
+        assert_eq!(validate_name("text", "").unwrap_err().to_string(), "the subtype was empty");
+        assert_eq!(validate_name("te xt", "plain").unwrap_err().to_string(), "invalid type, ' ' at position 2");
+    }
+
+    #[test]
+    fn parse_data_url() {
+        let parser = super::Parser::cannot_range();
+        let data_url = |src| {
+            let (mime, base64, data) = parser.parse_data_url(src).unwrap();
+            (mime.as_ref().to_owned(), base64, data)
+        };
+
+        assert_eq!(data_url("data:,Hello"), ("text/plain; charset=us-ascii".into(), false, "Hello"));
+        assert_eq!(data_url("data:;base64,SGk="), ("text/plain; charset=us-ascii".into(), true, "SGk="));
+        assert_eq!(data_url("DATA: ,a,b"), ("text/plain; charset=us-ascii".into(), false, "a,b"));
+        assert_eq!(data_url("data:text/html,<p>"), ("text/html".into(), false, "<p>"));
+        assert_eq!(data_url("data:image/png;base64,iVBO"), ("image/png".into(), true, "iVBO"));
+        assert_eq!(data_url("data:image/png ; BASE64 ,iVBO"), ("image/png".into(), true, "iVBO"));
+        assert_eq!(
+            data_url("data:text/plain;charset=utf-8;base64,"),
+            ("text/plain;charset=utf-8".into(), true, "")
+        );
+        assert_eq!(data_url("data:;charset=utf-8,x"), ("text/plain;charset=utf-8".into(), false, "x"));
+        // Only a last `base64` is the flag.
+        assert_eq!(data_url("data:a/b;x=base64,"), ("a/b;x=base64".into(), false, ""));
+
+        for src in &["", "data", "text/plain,abc", "blob:text/plain,abc", "data:text/plain", "dat\u{e4}:,"] {
+            match parser.parse_data_url(src) {
+                Err(super::ParseError::InvalidDataUrl) => (),
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+        for &(src, pos, byte) in &[
+            ("data:te@t/plain,", 7, b'@'),
+            ("data:;ch@rset=utf-8,", 8, b'@'),
+            ("data: ;ch@rset=utf-8,", 9, b'@'),
+            ("data:text/plain;a=b@;base64,", 19, b'@'),
+            ("data:a/b;base64;x=y,", 15, b';'),
+        ] {
+            match parser.parse_data_url(src) {
+                Err(super::ParseError::InvalidToken { pos: p, byte: b }) => {
+                    assert_eq!((p, b.0), (pos, byte), "{:?}", src);
+                },
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+    }
+
+    #[test]
+    fn param_ranges() {
+        for src in &[
+            "text/plain",
+            "text/plain; charset=utf-8",
+            "text/plain; charset=us-ascii",
+            "text/html;charset=utf-8",
+            "a/b; x=1",
+            "a/b; x=1; y=\"2 3\"",
+            "a/b; x=1;y=2 ; z=; w=\"\"",
+        ] {
+            let mime = parse(*src).unwrap();
+            let source = mime.as_ref();
+            let from_ranges = mime
+                .param_ranges()
+                .map(|(name, value)| (&source[name], &source[value]))
+                .collect::<Vec<_>>();
+            assert_eq!(from_ranges, mime.params().collect::<Vec<_>>(), "{:?}", src);
+        }
+    }
+
+    #[test]
+    fn permissive_values() {
+        let permissive = super::Parser::cannot_range().permissive_values();
+        let parse = |src: &str| permissive.parse(src).map(|mime| mime.as_ref().to_owned());
+
+        assert_eq!(parse("text/plain; name=a b").unwrap(), r#"text/plain; name="a b""#);
+        assert_eq!(parse("text/plain; name=caf\u{e9}").unwrap(), "text/plain; name=\"caf\u{e9}\"");
+        assert_eq!(
+            parse("Text/Plain; Name=a b ;Other=\"q\"; x=1").unwrap(),
+            r#"text/plain; name="a b"; other="q"; x=1"#
+        );
+        assert_eq!(parse(r#"a/b; n=say "hi" \o/"#).unwrap(), r#"a/b; n="say \"hi\" \\o/""#);
+        assert_eq!(parse("a/b; n= a b  ;m=").unwrap(), r#"a/b; n="a b"; m="#);
+
+        // Already valid sources are left alone.
+        assert_eq!(parse("text/plain; charset=utf-8").unwrap(), "text/plain; charset=utf-8");
+        assert_eq!(parse("a/b;x=1 ; y=\"2 3\"").unwrap(), "a/b;x=1 ; y=\"2 3\"");
+
+        // The result is valid for the strict parser.
+        for src in &["a/b; n=a b", "a/b; n=caf\u{e9}; m=\"x\"", "a/b; n=x,y z"] {
+            let fixed = parse(src).unwrap();
+            let strict = super::Parser::cannot_range().parse(&fixed).unwrap();
+            assert_eq!(strict.as_ref(), fixed);
+            assert!(super::Parser::cannot_range().parse(*src).is_err(), "{:?}", src);
+        }
+
+        let mime = permissive.parse("a/b; n=a b").unwrap();
+        assert_eq!(mime.param("n"), Some("\"a b\""));
+
+        permissive.parse("a/b; n=a\u{7f}b").unwrap_err();
+        permissive.parse("a/b; n=a\tb").unwrap();
+        permissive.parse("a/b; n").unwrap_err();
+    }
+
+    #[test]
+    fn parse_returning_remainder() {
+        let parser = super::Parser::cannot_range();
+        let parse = |src, delimiter| {
+            let (mime, rest) = parser.parse_returning_remainder(src, delimiter).unwrap();
+            (mime.as_ref().to_owned(), rest)
+        };
+
+        assert_eq!(parse("text/plain", b'|'), ("text/plain".into(), ""));
+        assert_eq!(parse("text/plain|rest", b'|'), ("text/plain".into(), "|rest"));
+        assert_eq!(parse("text/plain |rest|more", b'|'), ("text/plain".into(), "|rest|more"));
+        assert_eq!(
+            parse("text/plain; charset=utf-8\r\nX-Other: 1", b'\r'),
+            ("text/plain; charset=utf-8".into(), "\r\nX-Other: 1")
+        );
+        assert_eq!(
+            parse(r#"a/b; x="1,2"; y=3, c/d"#, b','),
+            (r#"a/b; x="1,2"; y=3"#.into(), ", c/d")
+        );
+        assert_eq!(parse(r#"a/b; x="\",", z"#, b','), (r#"a/b; x="\",""#.into(), ", z"));
+
+        for &(src, pos, byte) in &[("te@t/plain|x", 2, b'@'), ("text/plain; a=b@|d", 15, b'@')] {
+            match parser.parse_returning_remainder(src, b'|') {
+                Err(super::ParseError::InvalidToken { pos: p, byte: b }) => assert_eq!((p, b.0), (pos, byte)),
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+        parser.parse_returning_remainder("|text/plain", b'|').unwrap_err();
+    }
+
+    #[test]
+    fn parse_cow_reuses_buffer() {
+        use std::borrow::Cow;
+
+        let parser = super::Parser::cannot_range();
+        let reused = |src: &str| {
+            let owned = src.to_owned();
+            let ptr = owned.as_ptr();
+            let mime = parser.parse_cow(Cow::Owned(owned)).unwrap();
+            (mime.as_ref().to_owned(), mime.as_ref().as_ptr() == ptr)
+        };
+
+        // Already canonical, or only lowercased in place.
+        assert_eq!(reused("application/x-thing"), ("application/x-thing".into(), true));
+        assert_eq!(reused("Application/X-Thing"), ("application/x-thing".into(), true));
+        assert_eq!(reused("text/plain; A=b; c=\"D\""), ("text/plain; a=b; c=\"D\"".into(), true));
+        assert_eq!(reused("Text/Foo; Charset=UTF-8"), ("text/foo; charset=utf-8".into(), true));
+        assert_eq!(reused("a/b;"), ("a/b".into(), true));
+
+        // Atoms and trimmed sources aren't the buffer.
+        assert_eq!(reused("text/plain"), ("text/plain".into(), false));
+        assert_eq!(reused("text/html; charset=utf-8"), ("text/html; charset=utf-8".into(), false));
+        assert_eq!(reused(" a/b; c=d"), ("a/b; c=d".into(), false));
+
+        let mime = parser.parse_cow(Cow::Borrowed("A/B; c=d")).unwrap();
+        assert_eq!(mime.as_ref(), "a/b; c=d");
+
+        for src in &["a/b c", "text", " a@/b"] {
+            let borrowed = parser.parse(*src).unwrap_err();
+            let owned = parser.parse_cow(Cow::Owned(src.to_string())).unwrap_err();
+            assert_eq!(owned.to_string(), borrowed.to_string(), "{:?}", src);
+        }
+        super::Parser::cannot_range()
+            .rfc6838_names()
+            .parse_cow(Cow::Owned("_ab/c".into()))
+            .unwrap_err();
+    }
+
+    #[test]
+    fn empty_subtype() {
+        let strict = super::Parser::cannot_range();
+        for &(src, pos) in &[("text/", 5), ("text/; a=b", 5), ("text/ ; a=b", 5), (" text/", 6)] {
+            match strict.parse(src) {
+                Err(super::ParseError::EmptySubtype { pos: p }) => assert_eq!(p, pos, "{:?}", src),
+                other => panic!("{:?} => {:?}", src, other),
+            }
+        }
+        match super::Parser::can_range().parse("image/; q=0.5") {
+            Err(err @ super::ParseError::EmptySubtype { .. }) => {
+                assert_eq!(err.to_string(), "the subtype was empty, at position 6");
+            },
+            other => panic!("{:?}", other),
+        }
+
+        let tolerant = super::Parser::cannot_range().allow_empty_subtype();
+        let mime = tolerant.parse("Text/").unwrap();
+        assert_eq!((mime.as_ref(), mime.type_(), mime.subtype()), ("text/", "text", ""));
+
+        let mime = tolerant.parse("text/; charset=utf-8; A=b").unwrap();
+        assert_eq!(mime.as_ref(), "text/; charset=utf-8; a=b");
+        assert_eq!(mime.subtype(), "");
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("charset", "utf-8"), ("a", "b")]);
+
+        assert_eq!(tolerant.parse("text/ ").unwrap().as_ref(), "text/");
+        assert_eq!(tolerant.parse("text/plain").unwrap().as_ref(), "text/plain");
+        match tolerant.parse("/") {
+            Err(super::ParseError::InvalidToken { pos: 0, .. }) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_preferring_atoms() {
+        use crate::Source;
+
+        static ATOMS: &[&str] = &["application/vnd.acme+json", "application/x-acme", "text/x-acme; charset=utf-8"];
+        let parse = |s: &str| super::parse_preferring_atoms(&super::Parser::cannot_range(), s, ATOMS).unwrap();
+
+        for (src, atom) in &[
+            ("application/x-acme", ATOMS[1]),
+            ("Application/VND.Acme+JSON", ATOMS[0]),
+            (" text/x-acme; CHARSET=utf-8 ", ATOMS[2]),
+        ] {
+            let mime = parse(src);
+            match mime.source {
+                Source::Atom(0, s) => assert!(std::ptr::eq(s, *atom), "{:?}", src),
+                _ => panic!("{:?} wasn't interned", src),
+            }
+        }
+
+        let mime = parse("application/x-acme; a=b");
+        assert!(matches!(mime.source, Source::Dynamic(_)));
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", "b")]);
+
+        // Built-in atoms are still used first.
+        let mime = parse("text/plain");
+        assert_ne!(mime.atom(), 0);
+    }
+
+    #[test]
+    fn parse_csv_header() {
+        let parser = super::Parser::cannot_range();
+        let entries = parser
+            .parse_csv_header("text/html, application/ld+json; profile=\"a,b\" ,\timage/png")
+            .map(|entry| entry.unwrap().as_ref().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(entries, ["text/html", "application/ld+json; profile=\"a,b\"", "image/png"]);
+
+        // Errors have positions in the whole source, and don't end the list.
+        let mut entries = parser.parse_csv_header("text/html, , image/p@ng, text/plain,");
+        assert_eq!(entries.size_hint(), (1, Some(5)));
+        assert_eq!(entries.next().unwrap().unwrap().as_ref(), "text/html");
+        assert_eq!(format!("{:?}", entries), r#"CsvEntries { remaining: " , image/p@ng, text/plain," }"#);
+        match entries.next() {
+            Some(Err(super::ParseError::InvalidToken { pos: 11, .. })) => (),
+            other => panic!("{:?}", other),
+        }
+        match entries.next() {
+            Some(Err(super::ParseError::InvalidToken { pos: 20, .. })) => (),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(entries.next().unwrap().unwrap().as_ref(), "text/plain");
+        match entries.next() {
+            Some(Err(super::ParseError::InvalidToken { pos: 35, .. })) => (),
+            other => panic!("{:?}", other),
+        }
+        assert!(entries.next().is_none());
+        assert!(entries.next().is_none());
+        assert_eq!(entries.size_hint(), (0, Some(0)));
+        assert_eq!(format!("{:?}", entries), r#"CsvEntries { remaining: "" }"#);
+
+        assert_eq!(parser.parse_csv_header("").count(), 1);
+    }
+
+    #[test]
+    fn parse_header_multi() {
+        let parser = super::Parser::cannot_range();
+
+        let mime = parser.parse_header_multi(&["text/html; charset=utf-8"]).unwrap();
+        assert_eq!(mime.as_ref(), "text/html; charset=utf-8");
+        let mime = parser.parse_header_multi(&["", " ,text/plain; a=\"b,c\", ", "\t"]).unwrap();
+        assert_eq!(mime.as_ref(), "text/plain; a=\"b,c\"");
+
+        for values in &[
+            &["text/html", "text/html"][..],
+            &["text/html", "application/json"],
+            &["text/html, text/plain"],
+            &["", "text/html,text/html"],
+        ] {
+            match parser.parse_header_multi(values) {
+                Err(super::ParseError::MultipleValues) => (),
+                other => panic!("{:?} => {:?}", values, other),
+            }
+        }
+
+        match parser.parse_header_multi(&["", ", text/h@ml"]) {
+            Err(super::ParseError::InvalidToken { pos: 8, .. }) => (),
+            other => panic!("{:?}", other),
+        }
+        parser.parse_header_multi(&[]).unwrap_err();
+        parser.parse_header_multi(&["", " , "]).unwrap_err();
+    }
+
+    #[test]
+    fn param_len_limits() {
+        let parser = super::Parser::cannot_range().max_param_name_len(4).max_param_value_len(6);
+        let limit = |src: &str| match parser.parse(src) {
+            Err(super::ParseError::LimitExceeded { which, pos }) => (which, pos),
+            other => panic!("{:?} => {:?}", src, other),
+        };
+
+        parser.parse("text/plain; name=abcdef").unwrap();
+        parser.parse("text/plain; name=\"abcd\"; a=1").unwrap();
+        parser.parse("text/plain; name=").unwrap();
+        assert_eq!(limit("text/plain; names=a"), (super::Limit::ParamNameLen, 16));
+        assert_eq!(limit("text/plain; name=abcdefg"), (super::Limit::ParamValueLen, 23));
+        assert_eq!(limit("text/plain; a=1; name=\"abcde\""), (super::Limit::ParamValueLen, 28));
+        assert_eq!(limit(" text/plain; a=1234567; b@d"), (super::Limit::ParamValueLen, 21));
+
+        let err = parser.parse("text/plain; name=abcdefg").unwrap_err();
+        assert_eq!(err.to_string(), "a parameter value was too long, at position 23");
+
+        // The limits don't apply to the essence, or without setting them.
+        parser.parse("application/vnd.very-long-subtype-name").unwrap();
+        super::Parser::cannot_range().parse("text/plain; names=abcdefg").unwrap();
+        match parser.parse_params("names=a") {
+            Err(super::ParseError::LimitExceeded { pos: 4, .. }) => (),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn rfc2231_param_names() {
+        let src = "message/external-body; access-type=URL; URL*0=\"ftp://\"; URL*1*=cs.utk%2e; title*=us-ascii'en'This";
+        parse(src).unwrap_err();
+
+        let parser = super::Parser::cannot_range().rfc2231_names();
+        let mime = parser.parse(src).unwrap();
+        let names = mime.params().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, ["access-type", "url*0", "url*1*", "title*"]);
+        assert_eq!(mime.param("URL*1*"), Some("cs.utk%2e"));
+
+        // Still not allowed in the essence, or in unquoted values.
+        parser.parse("text/pl*in; a=b").unwrap_err();
+        parser.parse("te*t/plain; a=b").unwrap_err();
+        parser.parse("text/plain; a=b*").unwrap_err();
+        parser.reject_asterisks().parse("text/plain; a*=b").unwrap_err();
+    }
+
+    #[test]
+    fn quote_value() {
+        use crate::quote_value;
+        use std::borrow::Cow;
+
+        assert!(matches!(quote_value("utf-8"), Cow::Borrowed("utf-8")));
+        assert!(matches!(quote_value("!#$%&'+-.^_`|~09azAZ"), Cow::Borrowed(_)));
+        // `*` is a `tchar`, but the parser only allows it in ranges.
+        assert_eq!(quote_value("a*"), "\"a*\"");
+        assert_eq!(quote_value(""), "\"\"");
+        assert_eq!(quote_value("a b"), "\"a b\"");
+        assert_eq!(quote_value("a;b=c"), "\"a;b=c\"");
+        assert_eq!(quote_value(r#"say "hi"\"#), r#""say \"hi\"\\""#);
+        assert_eq!(quote_value("caf\u{e9}"), "\"caf\u{e9}\"");
+
+        for value in &["", "a*", "a b", "a;b", "\"", "\\", r#"a\"b"#, "\t", "caf\u{e9}"] {
+            let src = format!("text/plain; v={}", quote_value(value));
+            let mime = parse(&src[..]).expect(&src);
+            let quoted = mime.param("v").unwrap();
+            assert_eq!(quoted, quote_value(value), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn params_fused() {
+        fn assert_fused(src: &str, count: usize) {
+            let mime = parse(src).unwrap();
+            let mut params = mime.params();
+            for _ in 0..count {
+                assert!(params.next().is_some(), "{:?}", src);
+            }
+            for _ in 0..3 {
+                assert_eq!(params.next(), None, "{:?}", src);
+                assert_eq!(params.size_hint(), (0, Some(0)), "{:?}", src);
+            }
+        }
+
+        assert_fused("text/plain", 0);
+        assert_fused("text/plain; charset=utf-8", 1);
+        assert_fused("text/plain; a=1", 1);
+        assert_fused("text/plain; a=1; b=2", 2);
+        assert_fused("text/plain; a=1; b=2; c=3", 3);
+    }
+
+    #[test]
+    fn params_double_ended() {
+        for &(src, count) in &[
+            ("text/plain", 0),
+            ("text/plain; charset=utf-8", 1),
+            ("text/plain; a=1", 1),
+            ("text/plain; a=1; b=2", 2),
+            ("text/plain; a=1; b=2; c=3", 3),
+            ("text/plain; a=1; b=2; c=3; d=4", 4),
+        ] {
+            let mime = parse(src).unwrap();
+            let forward = mime.params().collect::<Vec<_>>();
+            let mut backward = mime.params().rev().collect::<Vec<_>>();
+            backward.reverse();
+            assert_eq!(forward, backward, "{:?}", src);
+            assert_eq!(forward.len(), count, "{:?}", src);
+
+            // Taking from both ends meets in the middle.
+            let mut params = mime.params();
+            let mut both = Vec::new();
+            while let Some(front) = params.next() {
+                both.push(front);
+                if let Some(back) = params.next_back() {
+                    both.push(back);
+                }
+            }
+            assert_eq!(both.len(), count, "{:?}", src);
+            assert_eq!(params.next_back(), None, "{:?}", src);
+        }
+    }
+
     #[test]
     fn semi_colon_but_empty_params() {
-        static CASES: &'static [&'static str] = &[
+        static CASES: &[&str] = &[
             "text/event-stream;",
             "text/event-stream; ",
             "text/event-stream;       ",
@@ -471,6 +2116,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn surrounding_whitespace() {
+        static CASES: &[&str] = &[
+            " text/plain",
+            "text/plain ",
+            "  text/plain  ",
+            "\ttext/plain\t",
+            " \t text/plain \t ",
+        ];
+
+        for &case in CASES {
+            let mime = parse(case).expect(case);
+            assert_eq!(mime.as_ref(), "text/plain", "case = {:?}", case);
+        }
+
+        let mime = parse("\t text/plain; charset=utf-8; foo=\"a b\" \t").unwrap();
+        assert_eq!(mime.as_ref(), "text/plain; charset=utf-8; foo=\"a b\"");
+        assert_eq!(mime.param("foo"), Some("\"a b\""));
+
+        parse(" \t ").unwrap_err();
+    }
+
+    #[test]
+    fn surrounding_whitespace_error_position() {
+        match parse("  te xt/plain") {
+            Err(super::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 4),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match parse("\ttext/plain; a\t=b") {
+            Err(super::ParseError::InvalidToken { pos, .. }) => assert_eq!(pos, 14),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        parse("text /plain").unwrap_err();
+        parse("text/ plain").unwrap_err();
+        parse(" text/plain\t; charset=utf-8").unwrap_err();
+    }
+
     // parse errors
 
     #[test]
@@ -509,6 +2191,39 @@ mod tests {
         parse("text/plain;charset=\"\r\nutf-8\"").unwrap_err();
     }
 
+    #[test]
+    fn ows_around_equals() {
+        let parser = super::Parser::cannot_range().ows_around_equals();
+
+        let mime = parser.parse("text/plain; Charset = UTF-8").unwrap();
+        assert_eq!(mime.param("charset"), Some("utf-8"));
+        assert_eq!(mime.as_ref(), "text/plain; charset=utf-8");
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("charset", "utf-8")]);
+
+        let mime = parser.parse("text/plain; a =1; b= \"x y\" ;c  =  ; d=4").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [
+            ("a", "1"),
+            ("b", "\"x y\""),
+            ("c", ""),
+            ("d", "4"),
+        ]);
+
+        // Still no spaces inside a name.
+        match parser.parse("text/plain; a b=1") {
+            Err(super::ParseError::InvalidToken { pos, byte }) => assert_eq!((pos, byte.0), (14, b'b')),
+            other => panic!("{:?}", other),
+        }
+        parser.parse("text/plain; a =").unwrap();
+        parser.parse("text/plain; a ").unwrap_err();
+
+        // OWS is tabs too.
+        let mime = parser.parse("text/plain; charset\t=\tutf-8").unwrap();
+        assert_eq!(mime.param("charset"), Some("utf-8"));
+        let mime = parser.parse("text/plain; a \t= \t1; b=2").unwrap();
+        assert_eq!(mime.params().collect::<Vec<_>>(), [("a", "1"), ("b", "2")]);
+        super::Parser::cannot_range().parse("text/plain; charset\t=utf-8").unwrap_err();
+    }
+
     #[test]
     fn error_param_space_before_equals() {
         parse("text/plain; charset =utf-8").unwrap_err();
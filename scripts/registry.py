@@ -0,0 +1,104 @@
+#!/usr/bin/env python3
+"""Regenerate the `REGISTERED` table in src/registry.rs from IANA.
+
+Downloads the CSV file of every top-level type in the IANA Media Types
+registry, and replaces the table with each registered essence,
+lowercased and sorted, along with the date of the snapshot.
+
+    python3 scripts/registry.py
+
+Or, without network access, from CSV files downloaded beforehand from
+https://www.iana.org/assignments/media-types/ (`application.csv` and so
+on, one per top-level type):
+
+    python3 scripts/registry.py --from path/to/csvs
+
+Review the diff before committing it, since registrations are sometimes
+removed or renamed.
+"""
+
+import argparse
+import csv
+import datetime
+import io
+import pathlib
+import re
+import urllib.request
+
+BASE = "https://www.iana.org/assignments/media-types/{}.csv"
+TOP_LEVEL = [
+    "application",
+    "audio",
+    "font",
+    "haptics",
+    "image",
+    "message",
+    "model",
+    "multipart",
+    "text",
+    "video",
+]
+REGISTRY = pathlib.Path(__file__).resolve().parent.parent / "src" / "registry.rs"
+
+
+def fetch(top_level, directory):
+    if directory is not None:
+        return (directory / "{}.csv".format(top_level)).read_text(encoding="utf-8")
+    with urllib.request.urlopen(BASE.format(top_level)) as response:
+        return response.read().decode("utf-8")
+
+
+def essences(top_level, directory):
+    text = fetch(top_level, directory)
+    for row in csv.DictReader(io.StringIO(text)):
+        # The template is the full essence. A few rows, for types that
+        # were registered without one, only have a name.
+        template = (row.get("Template") or "").strip()
+        if not template:
+            name = row["Name"].split()[0]
+            template = "{}/{}".format(top_level, name)
+        yield template.lower()
+
+
+def main():
+    parser = argparse.ArgumentParser(description="Regenerate the IANA registry table.")
+    parser.add_argument(
+        "--from",
+        dest="directory",
+        type=pathlib.Path,
+        help="read <top-level>.csv files from this directory, instead of downloading them",
+    )
+    args = parser.parse_args()
+
+    table = sorted({
+        essence
+        for top_level in TOP_LEVEL
+        for essence in essences(top_level, args.directory)
+    })
+    entries = "".join('    "{}",\n'.format(essence) for essence in table)
+
+    source = REGISTRY.read_text()
+    source, count = re.subn(
+        r"(static REGISTERED: &\[&str\] = &\[\n).*?(\];)",
+        lambda m: m.group(1) + entries + m.group(2),
+        source,
+        count=1,
+        flags=re.S,
+    )
+    if count != 1:
+        raise SystemExit("couldn't find the REGISTERED table")
+    source, count = re.subn(
+        r"^// Snapshot: .*$",
+        "// Snapshot: generated by scripts/registry.py on {}.".format(datetime.date.today()),
+        source,
+        count=1,
+        flags=re.M,
+    )
+    if count != 1:
+        raise SystemExit("couldn't find the snapshot line")
+    REGISTRY.write_text(source)
+    print("wrote {} essences to {}".format(len(table), REGISTRY))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,301 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use mime_parse::{KnownCharset, Mime, ParamSource};
+
+use crate::{Value, UTF_8};
+
+/// What a media type says about the encoding of its content.
+///
+/// Returned by [`MediaType::encoding_hint`](crate::MediaType::encoding_hint).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EncodingHint<'a> {
+    /// A `charset=utf-8` parameter.
+    Utf8,
+    /// A `charset` parameter with some other value.
+    Named(Value<'a>),
+    /// There is no `charset` parameter.
+    None,
+}
+
+/// A character set, as used by the `charset` parameter.
+///
+/// Parsing a `Charset` maps the names and aliases of some common character
+/// sets from the [IANA registry][iana] to a single variant. Any other name
+/// becomes `Charset::Other`, lowercased.
+///
+/// The aliases covered are all those listed in the registry for each of
+/// the known variants, plus `utf8`, since it is so commonly sent instead
+/// of `utf-8`.
+///
+/// [iana]: https://www.iana.org/assignments/character-sets/character-sets.xhtml
+///
+/// # Example
+///
+/// ```
+/// use mime::Charset;
+///
+/// assert_eq!("UTF-8".parse::<Charset>().unwrap(), Charset::Utf8);
+/// assert_eq!("csUTF8".parse::<Charset>().unwrap(), Charset::Utf8);
+/// assert_eq!("latin1".parse::<Charset>().unwrap(), Charset::Iso8859_1);
+///
+/// let other = "X-Custom".parse::<Charset>().unwrap();
+/// assert_eq!(other, Charset::Other("x-custom".into()));
+/// assert_eq!(other.as_str(), "x-custom");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Charset {
+    /// `UTF-8`
+    Utf8,
+    /// `UTF-16`
+    Utf16,
+    /// `UTF-16BE`
+    Utf16Be,
+    /// `UTF-16LE`
+    Utf16Le,
+    /// `US-ASCII`
+    UsAscii,
+    /// `ISO-8859-1`
+    Iso8859_1,
+    /// `ISO-8859-2`
+    Iso8859_2,
+    /// `ISO-8859-15`
+    Iso8859_15,
+    /// `windows-1252`
+    Windows1252,
+    /// `Shift_JIS`
+    ShiftJis,
+    /// `EUC-JP`
+    EucJp,
+    /// `ISO-2022-JP`
+    Iso2022Jp,
+    /// `EUC-KR`
+    EucKr,
+    /// `GB2312`
+    Gb2312,
+    /// `Big5`
+    Big5,
+    /// `KOI8-R`
+    Koi8R,
+    /// Any other character set, lowercased.
+    Other(String),
+}
+
+// Sorted by alias, for binary searching.
+static ALIASES: &[(&str, Charset)] = &[
+    ("ansi_x3.4-1968", Charset::UsAscii),
+    ("ansi_x3.4-1986", Charset::UsAscii),
+    ("big5", Charset::Big5),
+    ("cp367", Charset::UsAscii),
+    ("cp819", Charset::Iso8859_1),
+    ("csascii", Charset::UsAscii),
+    ("csbig5", Charset::Big5),
+    ("cseuckr", Charset::EucKr),
+    ("cseucpkdfmtjapanese", Charset::EucJp),
+    ("csgb2312", Charset::Gb2312),
+    ("csiso2022jp", Charset::Iso2022Jp),
+    ("csiso885915", Charset::Iso8859_15),
+    ("csisolatin1", Charset::Iso8859_1),
+    ("csisolatin2", Charset::Iso8859_2),
+    ("cskoi8r", Charset::Koi8R),
+    ("csshiftjis", Charset::ShiftJis),
+    ("csutf16", Charset::Utf16),
+    ("csutf16be", Charset::Utf16Be),
+    ("csutf16le", Charset::Utf16Le),
+    ("csutf8", Charset::Utf8),
+    ("cswindows1252", Charset::Windows1252),
+    ("euc-jp", Charset::EucJp),
+    ("euc-kr", Charset::EucKr),
+    ("extended_unix_code_packed_format_for_japanese", Charset::EucJp),
+    ("gb2312", Charset::Gb2312),
+    ("ibm367", Charset::UsAscii),
+    ("ibm819", Charset::Iso8859_1),
+    ("iso-2022-jp", Charset::Iso2022Jp),
+    ("iso-8859-1", Charset::Iso8859_1),
+    ("iso-8859-15", Charset::Iso8859_15),
+    ("iso-8859-2", Charset::Iso8859_2),
+    ("iso-ir-100", Charset::Iso8859_1),
+    ("iso-ir-101", Charset::Iso8859_2),
+    ("iso-ir-6", Charset::UsAscii),
+    ("iso646-us", Charset::UsAscii),
+    ("iso_646.irv:1991", Charset::UsAscii),
+    ("iso_8859-1", Charset::Iso8859_1),
+    ("iso_8859-15", Charset::Iso8859_15),
+    ("iso_8859-1:1987", Charset::Iso8859_1),
+    ("iso_8859-2", Charset::Iso8859_2),
+    ("iso_8859-2:1987", Charset::Iso8859_2),
+    ("koi8-r", Charset::Koi8R),
+    ("l1", Charset::Iso8859_1),
+    ("l2", Charset::Iso8859_2),
+    ("latin-9", Charset::Iso8859_15),
+    ("latin1", Charset::Iso8859_1),
+    ("latin2", Charset::Iso8859_2),
+    ("ms_kanji", Charset::ShiftJis),
+    ("shift_jis", Charset::ShiftJis),
+    ("us", Charset::UsAscii),
+    ("us-ascii", Charset::UsAscii),
+    ("utf-16", Charset::Utf16),
+    ("utf-16be", Charset::Utf16Be),
+    ("utf-16le", Charset::Utf16Le),
+    ("utf-8", Charset::Utf8),
+    ("utf8", Charset::Utf8),
+    ("windows-1252", Charset::Windows1252),
+];
+
+impl Charset {
+    /// Get the preferred MIME name of this `Charset`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(mime::Charset::Utf8.as_str(), "utf-8");
+    /// assert_eq!(mime::Charset::ShiftJis.as_str(), "shift_jis");
+    /// ```
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Charset::Utf8 => "utf-8",
+            Charset::Utf16 => "utf-16",
+            Charset::Utf16Be => "utf-16be",
+            Charset::Utf16Le => "utf-16le",
+            Charset::UsAscii => "us-ascii",
+            Charset::Iso8859_1 => "iso-8859-1",
+            Charset::Iso8859_2 => "iso-8859-2",
+            Charset::Iso8859_15 => "iso-8859-15",
+            Charset::Windows1252 => "windows-1252",
+            Charset::ShiftJis => "shift_jis",
+            Charset::EucJp => "euc-jp",
+            Charset::Iso2022Jp => "iso-2022-jp",
+            Charset::EucKr => "euc-kr",
+            Charset::Gb2312 => "gb2312",
+            Charset::Big5 => "big5",
+            Charset::Koi8R => "koi8-r",
+            Charset::Other(ref name) => name,
+        }
+    }
+}
+
+impl FromStr for Charset {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Charset, Self::Err> {
+        let name = s.to_ascii_lowercase();
+        match ALIASES.binary_search_by(|&(alias, _)| alias.cmp(&name)) {
+            Ok(i) => Ok(ALIASES[i].1.clone()),
+            Err(_) => Ok(Charset::Other(name)),
+        }
+    }
+}
+
+impl fmt::Display for Charset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+pub(crate) fn encoding_hint(mime: &Mime) -> EncodingHint<'_> {
+    if let ParamSource::KnownCharset(_, KnownCharset::Utf8) = mime.private_params_source() {
+        return EncodingHint::Utf8;
+    }
+
+    match crate::value::param(mime, crate::CHARSET) {
+        Some(value) if value == UTF_8 => EncodingHint::Utf8,
+        Some(value) => EncodingHint::Named(value),
+        None => EncodingHint::None,
+    }
+}
+
+pub(crate) fn charset_or_default(mime: &Mime, default: Charset) -> Charset {
+    if let ParamSource::KnownCharset(_, known) = *mime.private_params_source() {
+        return match known {
+            KnownCharset::Utf8 => Charset::Utf8,
+            KnownCharset::UsAscii => Charset::UsAscii,
+            KnownCharset::Iso8859_1 => Charset::Iso8859_1,
+        };
+    }
+
+    match crate::value::param(mime, crate::CHARSET) {
+        Some(value) => match value.to_content().parse() {
+            Ok(charset) => charset,
+            Err(never) => match never {},
+        },
+        None => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_charset_aliases_sorted() {
+        for pair in super::ALIASES.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{:?} must be before {:?}", pair[0].0, pair[1].0);
+        }
+        for &(alias, ref charset) in super::ALIASES {
+            assert_eq!(alias.to_ascii_lowercase(), alias);
+            assert_eq!(&alias.parse::<Charset>().unwrap(), charset);
+        }
+    }
+
+    #[test]
+    fn test_charset_from_str() {
+        assert_eq!("utf-8".parse::<Charset>().unwrap(), Charset::Utf8);
+        assert_eq!("UTF8".parse::<Charset>().unwrap(), Charset::Utf8);
+        assert_eq!("ISO_8859-1:1987".parse::<Charset>().unwrap(), Charset::Iso8859_1);
+        assert_eq!("Latin1".parse::<Charset>().unwrap(), Charset::Iso8859_1);
+        assert_eq!("US-ASCII".parse::<Charset>().unwrap(), Charset::UsAscii);
+        assert_eq!("MS_Kanji".parse::<Charset>().unwrap(), Charset::ShiftJis);
+
+        let other = "X-Made-Up".parse::<Charset>().unwrap();
+        assert_eq!(other, Charset::Other("x-made-up".into()));
+        assert_eq!(other.to_string(), "x-made-up");
+    }
+
+    #[test]
+    fn test_charset_as_str_roundtrips() {
+        for (_, charset) in super::ALIASES {
+            assert_eq!(&charset.as_str().parse::<Charset>().unwrap(), charset);
+        }
+    }
+
+    #[test]
+    fn test_charset_or_default() {
+        assert_eq!(TEXT_PLAIN_UTF_8.charset_or_default(Charset::Iso8859_1), Charset::Utf8);
+        assert_eq!(TEXT_PLAIN.charset_or_default(Charset::Iso8859_1), Charset::Iso8859_1);
+        assert_eq!(APPLICATION_JSON.charset_or_default(Charset::Utf8), Charset::Utf8);
+
+        for &(src, ref expected) in &[
+            ("text/plain; charset=us-ascii", Charset::UsAscii),
+            ("text/html; charset=iso-8859-1", Charset::Iso8859_1),
+            ("text/html; charset=\"Shift_JIS\"", Charset::ShiftJis),
+            ("text/html; foo=bar; charset=latin1", Charset::Iso8859_1),
+            ("text/html; charset=utf8; foo=bar", Charset::Utf8),
+            ("text/html; CHARSET=X-Custom", Charset::Other("x-custom".into())),
+        ] {
+            let mt = MediaType::parse(src).unwrap();
+            assert_eq!(&mt.charset_or_default(Charset::Big5), expected, "{:?}", src);
+        }
+
+        let other = MediaType::parse("text/html; foo=utf-8").unwrap();
+        assert_eq!(other.charset_or_default(Charset::Windows1252), Charset::Windows1252);
+    }
+
+    #[test]
+    fn test_encoding_hint() {
+        assert_eq!(TEXT_PLAIN_UTF_8.encoding_hint(), EncodingHint::Utf8);
+        assert_eq!(TEXT_PLAIN.encoding_hint(), EncodingHint::None);
+
+        let quoted = MediaType::parse("text/plain; charset=\"UTF-8\"; foo=bar").unwrap();
+        assert_eq!(quoted.encoding_hint(), EncodingHint::Utf8);
+
+        let latin1 = MediaType::parse("text/html; foo=bar; charset=ISO-8859-1").unwrap();
+        match latin1.encoding_hint() {
+            EncodingHint::Named(name) => assert_eq!(name, "iso-8859-1"),
+            other => panic!("expected a named charset: {:?}", other),
+        }
+
+        let other = MediaType::parse("text/html; foo=utf-8").unwrap();
+        assert_eq!(other.encoding_hint(), EncodingHint::None);
+    }
+}
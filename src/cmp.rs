@@ -1,5 +1,7 @@
 use mime_parse::{Mime, Parser};
 
+use crate::Value;
+
 pub(crate) fn str_eq(mime: &Mime, s: &str) -> bool {
     if mime.has_params() {
         Parser::can_range()
@@ -13,6 +15,13 @@ pub(crate) fn str_eq(mime: &Mime, s: &str) -> bool {
     }
 }
 
+pub(crate) fn canonical_eq(mime: &Mime, s: &str) -> bool {
+    Parser::cannot_range()
+        .parse(s)
+        .map(|other_mime| mime_eq(mime, &other_mime))
+        .unwrap_or(false)
+}
+
 pub(crate) fn mime_eq(a: &Mime, b: &Mime) -> bool {
     match (a.private_atom(), b.private_atom()) {
         // If either atom is 0, it is "dynamic" and needs to be compared
@@ -25,8 +34,31 @@ pub(crate) fn mime_eq(a: &Mime, b: &Mime) -> bool {
 
 }
 
+pub(crate) fn type_eq(a: &Mime, b: &Mime) -> bool {
+    match (a.private_atom(), b.private_atom()) {
+        // Same atom means the same source, so certainly the same type.
+        (0, _) | (_, 0) => (),
+        (aa, ba) if aa == ba => return true,
+        _ => (),
+    }
+
+    a.type_().eq_ignore_ascii_case(b.type_())
+}
+
 fn essence_eq(a: &Mime, b: &Mime) -> bool {
-    a.essence() == b.essence()
+    // Sources are lowercased, unless parsed preserving case.
+    a.essence().eq_ignore_ascii_case(b.essence())
+}
+
+pub(crate) fn parameters_eq(a: &Mime, b: &Mime) -> bool {
+    match (a.private_atom(), b.private_atom()) {
+        (0, _) | (_, 0) => (),
+        // Same atom means the same source, so certainly the same params.
+        (aa, ba) if aa == ba => return true,
+        _ => (),
+    }
+
+    params_eq(a, b)
 }
 
 fn params_eq(a: &Mime, b: &Mime) -> bool {
@@ -45,7 +77,34 @@ fn params_eq(a: &Mime, b: &Mime) -> bool {
     //
     // A simple benchmark suggests a hashmap is faster after about
     // 10 parameters...
-    for (name, value) in crate::value::params(a) {
+    //
+    // A name can be repeated, so looking up each param of `a` in `b` isn't
+    // enough, since that only finds the first. Instead each one has to be
+    // in both as many times. With the same number of params, that covers
+    // all of `b` too.
+    crate::value::params(a).all(|(name, value)| {
+        let count = |mime| {
+            crate::value::params(mime)
+                .filter(|&(n, v)| n.eq_ignore_ascii_case(name) && v == value)
+                .count()
+        };
+        count(a) == count(b)
+    })
+}
+
+pub(crate) fn eq_ignoring_charset(a: &Mime, b: &Mime) -> bool {
+    fn others(mime: &Mime) -> impl Iterator<Item = (&str, Value<'_>)> {
+        crate::value::params(mime).filter(|(name, _)| !name.eq_ignore_ascii_case(crate::CHARSET))
+    }
+
+    essence_eq(a, b) &&
+        others(a).count() == others(b).count() &&
+        params_subset(others(a), b)
+}
+
+/// Checks that every one of `params` is also in `b`, with the same value.
+pub(crate) fn params_subset<'a>(params: impl Iterator<Item = (&'a str, Value<'a>)>, b: &Mime) -> bool {
+    for (name, value) in params {
         if crate::value::param(b, name) != Some(value) {
             return false;
         }
@@ -53,3 +112,44 @@ fn params_eq(a: &Mime, b: &Mime) -> bool {
 
     true
 }
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(hash: u64, bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(hash, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME))
+}
+
+fn fnv1a_lowercase(s: &str) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, s.bytes().map(|c| c.to_ascii_lowercase()))
+}
+
+/// An FNV-1a hash of the lowercased type.
+pub(crate) fn type_hash(mime: &Mime) -> u64 {
+    fnv1a_lowercase(mime.type_())
+}
+
+/// An FNV-1a hash of the lowercased essence, matching `essence_eq`.
+pub(crate) fn essence_hash(mime: &Mime) -> u64 {
+    fnv1a_lowercase(mime.essence())
+}
+
+/// An order-independent FNV-1a hash of the parameters, matching `params_eq`.
+pub(crate) fn params_fingerprint(mime: &Mime) -> u64 {
+    // Each parameter is hashed on its own, and the hashes summed, so the
+    // order they appear in doesn't change the result.
+    crate::value::params(mime).fold(0u64, |sum, (name, value)| {
+        let content = value.to_content();
+        let case_insensitive = name.eq_ignore_ascii_case(crate::CHARSET);
+        let hash = fnv1a_lowercase(name);
+        let hash = fnv1a(hash, std::iter::once(b'='));
+        let hash = fnv1a(hash, content.bytes().map(|c| {
+            if case_insensitive {
+                c.to_ascii_lowercase()
+            } else {
+                c
+            }
+        }));
+        sum.wrapping_add(hash)
+    })
+}
@@ -0,0 +1,136 @@
+// A heuristic for whether content of some media type is worth compressing,
+// such as with `Content-Encoding: gzip`. The checks go from most to least
+// specific: a listed essence, then the +suffix, then the top-level type.
+// Anything not covered is assumed to not be compressible.
+
+pub(crate) fn is_compressible(type_: &str, subtype: &str, suffix: Option<&str>) -> bool {
+    let is_listed = |list: &[(&str, &str)]| {
+        list.iter()
+            .any(|&(t, s)| t.eq_ignore_ascii_case(type_) && s.eq_ignore_ascii_case(subtype))
+    };
+
+    if is_listed(INCOMPRESSIBLE) {
+        return false;
+    }
+    if is_listed(COMPRESSIBLE) {
+        return true;
+    }
+    if let Some(suffix) = suffix {
+        if contains(INCOMPRESSIBLE_SUFFIXES, suffix) {
+            return false;
+        }
+        if contains(COMPRESSIBLE_SUFFIXES, suffix) {
+            return true;
+        }
+    }
+    contains(COMPRESSIBLE_TYPES, type_)
+}
+
+fn contains(list: &[&str], name: &str) -> bool {
+    list.iter().any(|s| s.eq_ignore_ascii_case(name))
+}
+
+// Every subtype of these is compressible, unless listed otherwise.
+static COMPRESSIBLE_TYPES: &[&str] = &[
+    "text",
+];
+
+static COMPRESSIBLE_SUFFIXES: &[&str] = &[
+    "json",
+    "json-seq",
+    "xml",
+    "yaml",
+];
+
+// These are already compressed.
+static INCOMPRESSIBLE_SUFFIXES: &[&str] = &[
+    "gzip",
+    "zip",
+    "zstd",
+];
+
+static COMPRESSIBLE: &[(&str, &str)] = &[
+    ("application", "ecmascript"),
+    ("application", "graphql"),
+    ("application", "javascript"),
+    ("application", "json"),
+    ("application", "json-seq"),
+    ("application", "manifest+json"),
+    ("application", "postscript"),
+    ("application", "rtf"),
+    ("application", "sql"),
+    ("application", "vnd.ms-fontobject"),
+    ("application", "wasm"),
+    ("application", "x-javascript"),
+    ("application", "x-ndjson"),
+    ("application", "x-www-form-urlencoded"),
+    ("application", "xml"),
+    ("application", "yaml"),
+    ("font", "collection"),
+    ("font", "otf"),
+    ("font", "ttf"),
+    ("image", "bmp"),
+    ("image", "vnd.microsoft.icon"),
+    ("image", "x-icon"),
+];
+
+static INCOMPRESSIBLE: &[(&str, &str)] = &[
+    ("application", "gzip"),
+    ("application", "x-7z-compressed"),
+    ("application", "x-bzip2"),
+    ("application", "x-gzip"),
+    ("application", "x-rar-compressed"),
+    ("application", "x-xz"),
+    ("application", "zip"),
+    ("application", "zstd"),
+    // Compressing an event stream buffers it, delaying the events.
+    ("text", "event-stream"),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_is_compressible() {
+        let compressible = &[
+            "text/html",
+            "text/plain; charset=utf-8",
+            "Text/CSV",
+            "application/json",
+            "application/vnd.api+json",
+            "application/atom+xml",
+            "image/svg+xml",
+            "application/javascript",
+            "font/ttf",
+            "image/x-icon",
+        ];
+        for src in compressible {
+            let mt = MediaType::parse(*src).unwrap();
+            assert!(mt.is_compressible(), "{:?}", src);
+        }
+
+        let incompressible = &[
+            "image/png",
+            "image/jpeg",
+            "video/mp4",
+            "audio/ogg",
+            "font/woff2",
+            "application/octet-stream",
+            "application/zip",
+            "application/gzip",
+            "application/epub+zip",
+            "application/vnd.thing+gzip",
+            "application/pdf",
+            "text/event-stream",
+            "multipart/form-data; boundary=x",
+        ];
+        for src in incompressible {
+            let mt = MediaType::parse(*src).unwrap();
+            assert!(!mt.is_compressible(), "{:?}", src);
+        }
+
+        let mt = MediaType::parse_preserving("Image/SVG+XML").unwrap();
+        assert!(mt.is_compressible());
+    }
+}
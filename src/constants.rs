@@ -54,6 +54,7 @@ macro_rules! mime_constant_test {
 
         // prevent ranges from being MediaTypes
         __mime.test_assert_asterisks();
+        __mime.test_assert_canonical();
     })
 }
 
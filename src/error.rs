@@ -6,7 +6,23 @@ use mime_parse::ParseError;
 /// An error type representing an invalid `MediaType` or `MediaRange`.
 #[derive(Debug)]
 pub struct InvalidMime {
-    pub(crate) inner: ParseError,
+    pub(crate) inner: Kind,
+}
+
+#[derive(Debug)]
+pub(crate) enum Kind {
+    Parse(ParseError),
+    /// A `charset` parameter wasn't one of the allowed ones. `pos` is the
+    /// start of the value.
+    UnsupportedCharset {
+        pos: usize,
+    },
+}
+
+impl From<ParseError> for Kind {
+    fn from(err: ParseError) -> Kind {
+        Kind::Parse(err)
+    }
 }
 
 impl Error for InvalidMime {
@@ -14,6 +30,41 @@ impl Error for InvalidMime {
 
 impl fmt::Display for InvalidMime {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "invalid MIME: {}", self.inner)
+        match self.inner {
+            Kind::Parse(ref err) => write!(f, "invalid MIME: {}", err),
+            Kind::UnsupportedCharset { pos } => write!(f, "invalid MIME: unsupported charset at position {}", pos),
+        }
+    }
+}
+
+/// An error for a parameter that is required, but missing.
+///
+/// Returned by [`MediaType::require_param`](crate::MediaType::require_param).
+#[derive(Debug)]
+pub struct MissingParam {
+    pub(crate) name: String,
+}
+
+impl MissingParam {
+    /// Get the name of the missing parameter, as it was requested.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let form = mime::MediaType::parse("multipart/form-data").unwrap();
+    /// let err = form.require_param(mime::BOUNDARY).unwrap_err();
+    /// assert_eq!(err.name(), "boundary");
+    /// ```
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Error for MissingParam {
+}
+
+impl fmt::Display for MissingParam {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "missing {:?} parameter", self.name)
     }
 }
@@ -97,41 +97,65 @@ use proc_macro_hack::proc_macro_hack;
 /// mime = { version = "0.4", features = ["macro"] }
 /// ```
 ///
+/// A single `charset=utf-8` parameter is stored the same compact way as
+/// the `*_UTF_8` constants, without any runtime cost.
+///
 /// # Example
 ///
 /// ```
 /// const VND_MYAPP: mime::MediaType = mime::media_type!("application/vnd.myapp+json");
+/// const MARKDOWN: mime::MediaType = mime::media_type!("text/markdown; charset=utf-8");
 /// ```
 #[cfg(feature = "macro")]
 #[proc_macro_hack]
 pub use mime_macro::media_type;
 
 pub use mime_parse::constants::names::*;
+pub use self::charset::{Charset, EncodingHint};
 pub use self::constants::mimes::*;
-pub use self::error::InvalidMime;
+pub use self::error::{InvalidMime, MissingParam};
 pub use self::range::MediaRange;
+pub use self::set::MediaTypeSet;
+pub use self::tree::Tree;
 pub use self::type_::MediaType;
-pub use self::value::{Value, UTF_8};
+pub use self::value::{Params, Value, UTF_8};
+pub use self::violation::Violation;
 
+mod charset;
 mod cmp;
+mod compress;
 mod constants;
 mod error;
 #[cfg(feature = "macro")]
 mod macros;
 mod range;
+mod registry;
+mod rfc2231;
+#[cfg(feature = "rfc2047")]
+mod rfc2047;
 #[cfg(feature = "serde1")]
 mod serde;
+mod set;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+mod tree;
 mod type_;
 mod value;
+mod violation;
 
 
 fn _assert_traits() {
     fn assert_send_sync<T: Send + Sync>() {}
 
+    assert_send_sync::<Charset>();
     assert_send_sync::<InvalidMime>();
     assert_send_sync::<MediaRange>();
     assert_send_sync::<MediaType>();
+    assert_send_sync::<MediaTypeSet>();
+    assert_send_sync::<MissingParam>();
+    assert_send_sync::<Params>();
     assert_send_sync::<Value>();
+    assert_send_sync::<Violation>();
 }
 
 /// **DO NOT IMPORT THIS MODULE OR ITS TYPES.**
@@ -142,10 +166,9 @@ fn _assert_traits() {
 #[cfg(feature = "macro")]
 pub mod private {
     #[doc(hidden)]
-    pub use mime_parse::{Mime, ParamSource, Source};
+    pub use mime_parse::{KnownCharset, Mime, ParamSource, Source};
 }
 
-#[cfg_attr(not(debug_assertions), allow(unused))]
 fn is_ascii_lowercase(s: &str) -> bool {
     !s.as_bytes().iter().any(u8::is_ascii_uppercase)
 }
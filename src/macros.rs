@@ -112,6 +112,37 @@ mod tests {
         assert_eq!(utf8, TEXT_PLAIN_UTF_8);
     }
 
+    #[test]
+    fn media_type_const_utf8() {
+        const MARKDOWN_UTF_8: MediaType = media_type!("text/markdown; charset=utf-8");
+
+        assert_eq!(MARKDOWN_UTF_8.type_(), TEXT);
+        assert_eq!(MARKDOWN_UTF_8.subtype(), "markdown");
+        assert_eq!(MARKDOWN_UTF_8.param(CHARSET), Some(UTF_8));
+        assert_eq!(MARKDOWN_UTF_8, MediaType::parse("text/markdown; charset=utf-8").unwrap());
+        match MARKDOWN_UTF_8.mime.private_params_source() {
+            crate::private::ParamSource::KnownCharset(13, crate::private::KnownCharset::Utf8) => (),
+            _ => panic!("charset=utf-8 should use the Utf8 fast path"),
+        }
+
+        // Even uppercase literals are normalized at compile time.
+        const UPPER: MediaType = media_type!("TEXT/MARKDOWN; CHARSET=UTF-8");
+        assert_eq!(UPPER.to_string(), "text/markdown; charset=utf-8");
+        assert_eq!(UPPER, MARKDOWN_UTF_8);
+    }
+
+    #[test]
+    fn media_type_const_known_charset() {
+        const HTML_ASCII: MediaType = media_type!("text/html; charset=US-ASCII");
+
+        assert_eq!(HTML_ASCII.param(CHARSET).unwrap(), "us-ascii");
+        assert_eq!(HTML_ASCII, MediaType::parse("text/html; charset=\"us-ascii\"").unwrap());
+        match HTML_ASCII.mime.private_params_source() {
+            crate::private::ParamSource::KnownCharset(9, crate::private::KnownCharset::UsAscii) => (),
+            _ => panic!("charset=us-ascii should be a known charset"),
+        }
+    }
+
     #[test]
     fn media_type_one_param() {
         let mt = media_type!("multipart/form-data; boundary=AbCd");
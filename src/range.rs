@@ -1,9 +1,10 @@
+use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
 
 use mime_parse::{Mime, Parse};
 
-use crate::{InvalidMime, MediaType, Value};
+use crate::{InvalidMime, MediaType, Params, Value};
 
 /// A parsed media range used to match media types.
 ///
@@ -55,7 +56,22 @@ impl MediaRange {
         mime_parse::Parser::can_range()
             .parse(source)
             .map(|mime| MediaRange { mime })
-            .map_err(|e| InvalidMime { inner: e })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Get the canonical string form of this `MediaRange`.
+    ///
+    /// This is the same as `as_ref()`, without needing `AsRef` in scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(mime::STAR_STAR.as_str(), "*/*");
+    /// assert_eq!(mime::MediaRange::parse("TEXT/*").unwrap().as_str(), "text/*");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.mime.as_ref()
     }
 
     /// Get the top level media type for this `MediaRange`.
@@ -133,7 +149,7 @@ impl MediaRange {
             return self.matches_params(mt);
         }
 
-        if type_ != mt.type_() {
+        if !type_.eq_ignore_ascii_case(mt.type_()) {
             return false;
         }
 
@@ -143,7 +159,7 @@ impl MediaRange {
             return self.matches_params(mt);
         }
 
-        if subtype != mt.subtype() {
+        if !subtype.eq_ignore_ascii_case(mt.subtype()) {
             return false;
         }
 
@@ -152,18 +168,90 @@ impl MediaRange {
         self.matches_params(mt)
     }
 
-    fn matches_params(&self, mt: &MediaType) -> bool {
-        for (name, value) in self.params() {
-            if name != "q" && mt.param(name) != Some(value) {
-                return false;
-            }
-        }
+    /// Compares how specific this `MediaRange` is compared to another.
+    ///
+    /// A concrete `type/subtype` is more specific than `type/*`, which is
+    /// more specific than `*/*`. Among ranges of the same kind, the one with
+    /// more parameters is more specific. The `q` parameter is a weight, not
+    /// a constraint, so it isn't counted.
+    ///
+    /// Quality values are otherwise ignored, so this can be combined with
+    /// whatever weighting the caller needs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use mime::MediaRange;
+    ///
+    /// let mut ranges = vec![
+    ///     mime::STAR_STAR,
+    ///     MediaRange::parse("text/plain").unwrap(),
+    ///     mime::TEXT_STAR,
+    ///     MediaRange::parse("text/plain; format=flowed").unwrap(),
+    /// ];
+    ///
+    /// // Most specific first.
+    /// ranges.sort_by(|a, b| b.specificity_cmp(a));
+    ///
+    /// assert_eq!(ranges[0], "text/plain; format=flowed");
+    /// assert_eq!(ranges[1], "text/plain");
+    /// assert_eq!(ranges[2], "text/*");
+    /// assert_eq!(ranges[3], "*/*");
+    ///
+    /// assert_eq!(mime::TEXT_STAR.specificity_cmp(&mime::IMAGE_STAR), Ordering::Equal);
+    /// ```
+    pub fn specificity_cmp(&self, other: &MediaRange) -> Ordering {
+        self.specificity().cmp(&other.specificity())
+    }
+
+    /// Writes the canonical form of this `MediaRange` to a writer.
+    ///
+    /// This is the same string as `Display` and `as_ref()`, written
+    /// without any formatting options or intermediate `String`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut header = String::from("Accept: ");
+    /// mime::IMAGE_STAR.write_to(&mut header).unwrap();
+    ///
+    /// assert_eq!(header, "Accept: image/*");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer does.
+    #[inline]
+    pub fn write_to<W: fmt::Write>(&self, mut w: W) -> fmt::Result {
+        w.write_str(self.as_ref())
+    }
 
-        true
+    fn specificity(&self) -> (u8, usize) {
+        let level = if self.type_() == crate::STAR {
+            0
+        } else if self.subtype() == crate::STAR {
+            1
+        } else {
+            2
+        };
+        let params = self.params()
+            .filter(|&(name, _)| name != "q")
+            .count();
+
+        (level, params)
+    }
+
+    fn matches_params(&self, mt: &MediaType) -> bool {
+        let params = self.params().filter(|&(name, _)| name != "q");
+        crate::cmp::params_subset(params, &mt.mime)
     }
 
     /// Look up a parameter by name.
     ///
+    /// Parameter names are case-insensitive, and are always normalized to
+    /// lowercase. Values keep their case, except for `charset`.
+    ///
     /// # Example
     ///
     /// ```
@@ -199,7 +287,7 @@ impl MediaRange {
     /// assert!(params.next().is_none());
     /// ```
     #[inline]
-    pub fn params(&self) -> impl Iterator<Item = (&str, Value)> {
+    pub fn params(&self) -> Params<'_> {
         crate::value::params(&self.mime)
     }
 
@@ -223,6 +311,13 @@ impl MediaRange {
     pub(super) fn test_assert_asterisks(&self) {
         // asterisks are allowed in MediaRange constants
     }
+
+    #[cfg(test)]
+    pub(super) fn test_assert_canonical(&self) {
+        let reparsed = MediaRange::parse(self.as_str()).unwrap();
+        assert_eq!(&reparsed, self);
+        assert_eq!(reparsed.as_str(), self.as_str(), "{:?} isn't canonical", self);
+    }
 }
 
 /// Any `MediaType` can freely be a `MediaRange`.
@@ -241,6 +336,16 @@ impl From<MediaType> for MediaRange {
     }
 }
 
+impl<'a> IntoIterator for &'a MediaRange {
+    type Item = (&'a str, Value<'a>);
+    type IntoIter = Params<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Params<'a> {
+        self.params()
+    }
+}
+
 impl PartialEq for MediaRange {
     fn eq(&self, other: &MediaRange) -> bool {
         crate::cmp::mime_eq(&self.mime, &other.mime)
@@ -260,7 +365,7 @@ impl<'a> PartialEq<&'a str> for MediaRange {
     }
 }
 
-impl<'a> PartialEq<MediaRange> for &'a str {
+impl PartialEq<MediaRange> for &str {
     #[inline]
     fn eq(&self, mr: &MediaRange) -> bool {
         mr == self
@@ -291,7 +396,11 @@ impl AsRef<str> for MediaRange {
 impl fmt::Debug for MediaRange {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&self.mime, f)
+        if f.alternate() {
+            crate::type_::debug_structured("MediaRange", &self.mime, f)
+        } else {
+            fmt::Debug::fmt(&self.mime, f)
+        }
     }
 }
 
@@ -362,5 +471,87 @@ mod tests {
         assert!(range.matches(&TEXT_HTML_UTF_8));
         assert!(!range.matches(&TEXT_HTML));
     }
+
+    #[test]
+    fn media_range_str_eq_is_symmetric() {
+        let range = MediaRange::parse("text/*; q=0.5").unwrap();
+        let same = "TEXT/*; Q=0.5";
+        let other = "text/*";
+
+        assert!(range == *same && *same == range);
+        assert!(range == same && same == range);
+        assert!(range != *other && *other != range);
+        assert!(range != other && other != range);
+        assert!("*/*" == STAR_STAR);
+    }
+
+    #[test]
+    fn media_range_as_str() {
+        let range = MediaRange::parse("text/*; q=0.5").unwrap();
+        assert_eq!(range.as_str(), "text/*; q=0.5");
+        assert_eq!(range.as_str(), range.as_ref());
+        assert_eq!(TEXT_STAR.as_str(), "text/*");
+    }
+
+    #[test]
+    fn media_range_into_iter() {
+        let range = MediaRange::parse("text/*; level=1; q=0.5").unwrap();
+        let mut names = Vec::new();
+        for (name, _) in &range {
+            names.push(name);
+        }
+        assert_eq!(names, ["level", "q"]);
+        assert_eq!((&STAR_STAR).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn media_range_debug() {
+        let range = MediaRange::parse("text/*; q=0.5").unwrap();
+
+        assert_eq!(format!("{:?}", range), r#""text/*; q=0.5""#);
+        assert_eq!(
+            format!("{:#?}", range),
+            r#"MediaRange {
+    type: "text",
+    subtype: "*",
+    suffix: None,
+    params: [
+        (
+            "q",
+            "0.5",
+        ),
+    ],
+}"#
+        );
+    }
+
+    #[test]
+    fn media_range_specificity_cmp() {
+        use std::cmp::Ordering;
+
+        // The examples from RFC 7231, section 5.3.2:
+        //
+        //     Accept: text/*, text/plain, text/plain;format=flowed, */*
+        let flowed = MediaRange::parse("text/plain;format=flowed").unwrap();
+        let plain = MediaRange::parse("text/plain").unwrap();
+
+        assert_eq!(flowed.specificity_cmp(&plain), Ordering::Greater);
+        assert_eq!(plain.specificity_cmp(&TEXT_STAR), Ordering::Greater);
+        assert_eq!(TEXT_STAR.specificity_cmp(&STAR_STAR), Ordering::Greater);
+        assert_eq!(STAR_STAR.specificity_cmp(&flowed), Ordering::Less);
+
+        // Parameters never outrank a more specific essence.
+        let star_utf8 = MediaRange::parse("text/*; charset=utf-8; level=1").unwrap();
+        assert_eq!(star_utf8.specificity_cmp(&plain), Ordering::Less);
+        assert_eq!(star_utf8.specificity_cmp(&TEXT_STAR), Ordering::Greater);
+
+        // Quality values aren't considered parameters.
+        let weighted = MediaRange::parse("text/plain; q=0.5").unwrap();
+        assert_eq!(weighted.specificity_cmp(&plain), Ordering::Equal);
+
+        // Different but equally specific ranges are equal.
+        let html = MediaRange::parse("text/html").unwrap();
+        assert_eq!(html.specificity_cmp(&plain), Ordering::Equal);
+    }
 }
 
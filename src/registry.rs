@@ -0,0 +1,420 @@
+use std::borrow::Cow;
+
+// Essences from the IANA Media Types registry:
+// https://www.iana.org/assignments/media-types/media-types.xhtml
+//
+// Snapshot: none yet, this is a curated subset picked by hand.
+//
+// The subset has common types from each top-level type, plus a handful
+// of widely used `vnd.` and `prs.` registrations. It isn't complete, even
+// for the standards tree, so some registered types are reported as
+// unregistered. `scripts/registry.py` replaces the table with the whole
+// registry and updates the snapshot line above. It should be run before
+// each release, so the table is never more than one release behind.
+//
+// Names are lowercased, since the registry lists some (like `EDIFACT`)
+// in uppercase. The table must stay sorted, so it can be binary searched.
+
+pub(crate) fn is_registered(essence: &str) -> bool {
+    let essence = lowercase(essence);
+    REGISTERED.binary_search(&&*essence).is_ok()
+}
+
+// Essences are already lowercase, unless parsed preserving case.
+fn lowercase(essence: &str) -> Cow<'_, str> {
+    if crate::is_ascii_lowercase(essence) {
+        Cow::Borrowed(essence)
+    } else {
+        Cow::Owned(essence.to_ascii_lowercase())
+    }
+}
+
+static REGISTERED: &[&str] = &[
+    "application/1d-interleaved-parityfec",
+    "application/3gpdash-qoe-report+xml",
+    "application/atom+xml",
+    "application/atomcat+xml",
+    "application/atomsvc+xml",
+    "application/cbor",
+    "application/cbor-seq",
+    "application/cms",
+    "application/cose",
+    "application/cose-key",
+    "application/cose-key-set",
+    "application/dash+xml",
+    "application/dicom",
+    "application/dns",
+    "application/dns+json",
+    "application/dns-message",
+    "application/ecmascript",
+    "application/edi-x12",
+    "application/edifact",
+    "application/epub+zip",
+    "application/example",
+    "application/geo+json",
+    "application/geo+json-seq",
+    "application/gzip",
+    "application/http",
+    "application/javascript",
+    "application/jose",
+    "application/jose+json",
+    "application/json",
+    "application/json-patch+json",
+    "application/json-seq",
+    "application/jwk+json",
+    "application/jwk-set+json",
+    "application/jwt",
+    "application/ld+json",
+    "application/link-format",
+    "application/manifest+json",
+    "application/marc",
+    "application/mathml+xml",
+    "application/mbox",
+    "application/merge-patch+json",
+    "application/mp4",
+    "application/mpeg4-generic",
+    "application/msword",
+    "application/n-quads",
+    "application/n-triples",
+    "application/ocsp-request",
+    "application/ocsp-response",
+    "application/octet-stream",
+    "application/oebps-package+xml",
+    "application/ogg",
+    "application/pdf",
+    "application/pem-certificate-chain",
+    "application/pgp-encrypted",
+    "application/pgp-keys",
+    "application/pgp-signature",
+    "application/pkcs10",
+    "application/pkcs12",
+    "application/pkcs7-mime",
+    "application/pkcs7-signature",
+    "application/pkcs8",
+    "application/pkix-cert",
+    "application/pkix-crl",
+    "application/pkix-pkipath",
+    "application/postscript",
+    "application/problem+json",
+    "application/problem+xml",
+    "application/rdf+xml",
+    "application/rtf",
+    "application/samlassertion+xml",
+    "application/samlmetadata+xml",
+    "application/scim+json",
+    "application/sdp",
+    "application/senml+json",
+    "application/sgml",
+    "application/smil+xml",
+    "application/soap+xml",
+    "application/sparql-query",
+    "application/sparql-results+xml",
+    "application/sql",
+    "application/ssml+xml",
+    "application/tei+xml",
+    "application/timestamp-query",
+    "application/timestamp-reply",
+    "application/vnd.android.package-archive",
+    "application/vnd.api+json",
+    "application/vnd.apple.mpegurl",
+    "application/vnd.google-earth.kml+xml",
+    "application/vnd.mozilla.xul+xml",
+    "application/vnd.ms-excel",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.oasis.opendocument.presentation",
+    "application/vnd.oasis.opendocument.spreadsheet",
+    "application/vnd.oasis.opendocument.text",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.rar",
+    "application/vnd.sqlite3",
+    "application/vnd.visio",
+    "application/wasm",
+    "application/x-www-form-urlencoded",
+    "application/xhtml+xml",
+    "application/xml",
+    "application/xml-dtd",
+    "application/xml-external-parsed-entity",
+    "application/xml-patch+xml",
+    "application/xslt+xml",
+    "application/yaml",
+    "application/yang",
+    "application/zip",
+    "application/zlib",
+    "application/zstd",
+    "audio/1d-interleaved-parityfec",
+    "audio/3gpp",
+    "audio/3gpp2",
+    "audio/aac",
+    "audio/ac3",
+    "audio/amr",
+    "audio/amr-wb",
+    "audio/basic",
+    "audio/example",
+    "audio/flac",
+    "audio/g722",
+    "audio/l16",
+    "audio/matroska",
+    "audio/mp4",
+    "audio/mp4a-latm",
+    "audio/mpa",
+    "audio/mpa-robust",
+    "audio/mpeg",
+    "audio/mpeg4-generic",
+    "audio/ogg",
+    "audio/opus",
+    "audio/pcma",
+    "audio/pcmu",
+    "audio/sp-midi",
+    "audio/speex",
+    "audio/vnd.dts",
+    "audio/vnd.wave",
+    "audio/vorbis",
+    "font/collection",
+    "font/otf",
+    "font/sfnt",
+    "font/ttf",
+    "font/woff",
+    "font/woff2",
+    "image/aces",
+    "image/avif",
+    "image/bmp",
+    "image/cgm",
+    "image/dicom-rle",
+    "image/emf",
+    "image/example",
+    "image/fits",
+    "image/g3fax",
+    "image/gif",
+    "image/heic",
+    "image/heic-sequence",
+    "image/heif",
+    "image/heif-sequence",
+    "image/ief",
+    "image/jls",
+    "image/jp2",
+    "image/jpeg",
+    "image/jpm",
+    "image/jpx",
+    "image/ktx",
+    "image/ktx2",
+    "image/naplps",
+    "image/png",
+    "image/svg+xml",
+    "image/t38",
+    "image/tiff",
+    "image/tiff-fx",
+    "image/vnd.adobe.photoshop",
+    "image/vnd.djvu",
+    "image/vnd.dvb.subtitle",
+    "image/vnd.dwg",
+    "image/vnd.dxf",
+    "image/vnd.microsoft.icon",
+    "image/vnd.wap.wbmp",
+    "image/webp",
+    "image/wmf",
+    "message/cpim",
+    "message/delivery-status",
+    "message/disposition-notification",
+    "message/example",
+    "message/external-body",
+    "message/global",
+    "message/global-delivery-status",
+    "message/global-disposition-notification",
+    "message/global-headers",
+    "message/http",
+    "message/imdn+xml",
+    "message/partial",
+    "message/rfc822",
+    "message/s-http",
+    "message/sip",
+    "message/sipfrag",
+    "message/tracking-status",
+    "model/3mf",
+    "model/e57",
+    "model/example",
+    "model/gltf+json",
+    "model/gltf-binary",
+    "model/iges",
+    "model/mesh",
+    "model/mtl",
+    "model/obj",
+    "model/stl",
+    "model/vnd.collada+xml",
+    "model/vnd.usdz+zip",
+    "model/vrml",
+    "model/x3d+fastinfoset",
+    "model/x3d+xml",
+    "model/x3d-vrml",
+    "multipart/alternative",
+    "multipart/appledouble",
+    "multipart/byteranges",
+    "multipart/digest",
+    "multipart/encrypted",
+    "multipart/example",
+    "multipart/form-data",
+    "multipart/header-set",
+    "multipart/mixed",
+    "multipart/multilingual",
+    "multipart/parallel",
+    "multipart/related",
+    "multipart/report",
+    "multipart/signed",
+    "multipart/voice-message",
+    "multipart/x-mixed-replace",
+    "text/1d-interleaved-parityfec",
+    "text/cache-manifest",
+    "text/calendar",
+    "text/css",
+    "text/csv",
+    "text/csv-schema",
+    "text/directory",
+    "text/dns",
+    "text/ecmascript",
+    "text/encaprtp",
+    "text/enriched",
+    "text/event-stream",
+    "text/example",
+    "text/fhirpath",
+    "text/flexfec",
+    "text/fwdred",
+    "text/gff3",
+    "text/grammar-ref-list",
+    "text/hl7v2",
+    "text/html",
+    "text/javascript",
+    "text/jcr-cnd",
+    "text/markdown",
+    "text/mizar",
+    "text/n3",
+    "text/parameters",
+    "text/parityfec",
+    "text/plain",
+    "text/provenance-notation",
+    "text/prs.fallenstein.rst",
+    "text/prs.lines.tag",
+    "text/raptorfec",
+    "text/red",
+    "text/rfc822-headers",
+    "text/richtext",
+    "text/rtf",
+    "text/rtp-enc-aescm128",
+    "text/rtploopback",
+    "text/rtx",
+    "text/sgml",
+    "text/shaclc",
+    "text/shex",
+    "text/spdx",
+    "text/strings",
+    "text/t140",
+    "text/tab-separated-values",
+    "text/troff",
+    "text/turtle",
+    "text/ulpfec",
+    "text/uri-list",
+    "text/vcard",
+    "text/vnd.a",
+    "text/vnd.curl",
+    "text/vnd.dvb.subtitle",
+    "text/vnd.graphviz",
+    "text/vnd.wap.wml",
+    "text/vtt",
+    "text/xml",
+    "text/xml-external-parsed-entity",
+    "video/1d-interleaved-parityfec",
+    "video/3gpp",
+    "video/3gpp2",
+    "video/example",
+    "video/h261",
+    "video/h263",
+    "video/h264",
+    "video/h265",
+    "video/jpeg",
+    "video/jpeg2000",
+    "video/matroska",
+    "video/mp2t",
+    "video/mp4",
+    "video/mpeg",
+    "video/mpeg4-generic",
+    "video/ogg",
+    "video/quicktime",
+    "video/raw",
+    "video/vc1",
+    "video/vnd.dvb.file",
+    "video/vp8",
+    "video/vp9",
+];
+
+// Deprecated essences, and the type that replaces them:
+//
+// - JavaScript: RFC 9239, section 6
+// - `text/directory`: RFC 6350, section 10.1
+// - fonts: RFC 8081, section 4.4
+// - `application/vnd.geo+json`: RFC 7946, section 12
+// - YAML: RFC 9512, section 2.1
+// - the rest are common unregistered `x-` names of registered types.
+//
+// Sorted by the deprecated essence.
+pub(crate) fn deprecated_alias(essence: &str) -> Option<&'static str> {
+    let essence = lowercase(essence);
+    DEPRECATED
+        .binary_search_by(|&(deprecated, _)| deprecated.cmp(&essence))
+        .ok()
+        .map(|i| DEPRECATED[i].1)
+}
+
+static DEPRECATED: &[(&str, &str)] = &[
+    ("application/ecmascript", "text/javascript"),
+    ("application/font-sfnt", "font/sfnt"),
+    ("application/font-woff", "font/woff"),
+    ("application/javascript", "text/javascript"),
+    ("application/vnd.geo+json", "application/geo+json"),
+    ("application/x-ecmascript", "text/javascript"),
+    ("application/x-gzip", "application/gzip"),
+    ("application/x-javascript", "text/javascript"),
+    ("application/x-yaml", "application/yaml"),
+    ("application/x-zip-compressed", "application/zip"),
+    ("image/x-icon", "image/vnd.microsoft.icon"),
+    ("text/directory", "text/vcard"),
+    ("text/ecmascript", "text/javascript"),
+    ("text/javascript1.0", "text/javascript"),
+    ("text/javascript1.1", "text/javascript"),
+    ("text/javascript1.2", "text/javascript"),
+    ("text/javascript1.3", "text/javascript"),
+    ("text/javascript1.4", "text/javascript"),
+    ("text/javascript1.5", "text/javascript"),
+    ("text/jscript", "text/javascript"),
+    ("text/livescript", "text/javascript"),
+    ("text/x-ecmascript", "text/javascript"),
+    ("text/x-javascript", "text/javascript"),
+    ("text/x-yaml", "application/yaml"),
+    ("text/yaml", "application/yaml"),
+];
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_deprecated_sorted() {
+        for pair in super::DEPRECATED.windows(2) {
+            assert!(pair[0].0 < pair[1].0, "{:?} must be before {:?}", pair[0].0, pair[1].0);
+        }
+        for &(deprecated, preferred) in super::DEPRECATED {
+            assert_eq!(deprecated.to_ascii_lowercase(), deprecated);
+            assert!(super::is_registered(preferred), "{:?} should be registered", preferred);
+            assert_eq!(super::deprecated_alias(preferred), None, "{:?} is deprecated", preferred);
+        }
+    }
+
+    #[test]
+    fn test_registered_sorted() {
+        for pair in super::REGISTERED.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} must be before {:?}", pair[0], pair[1]);
+        }
+        for essence in super::REGISTERED {
+            assert_eq!(essence.to_ascii_lowercase(), *essence);
+            let mt = crate::MediaType::parse(*essence).expect(essence);
+            assert!(!mt.has_params(), "{:?}", essence);
+        }
+    }
+}
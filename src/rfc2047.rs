@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+use crate::Charset;
+
+// From [RFC 2047](https://tools.ietf.org/html/rfc2047), an encoded-word
+// looks like this:
+//
+// >     encoded-word = "=?" charset "?" encoding "?" encoded-text "?="
+//
+// The encoding is `Q` (like quoted-printable, with `_` for a space) or
+// `B` (base64). Whitespace between adjacent encoded-words is dropped.
+
+/// Decodes a value made only of encoded-words, or returns `None` if it
+/// isn't one, or any part of it is malformed or in an unknown charset.
+pub(crate) fn decode(value: &str) -> Option<String> {
+    let mut decoded = String::new();
+    let mut words = value.split_ascii_whitespace().peekable();
+    words.peek()?;
+    for word in words {
+        decoded.push_str(&decode_word(word)?);
+    }
+    Some(decoded)
+}
+
+fn decode_word(word: &str) -> Option<Cow<'_, str>> {
+    let inner = word.strip_prefix("=?")?.strip_suffix("?=")?;
+    let mut parts = inner.splitn(3, '?');
+    let (charset, encoding, text) = (parts.next()?, parts.next()?, parts.next()?);
+    if text.contains('?') {
+        return None;
+    }
+
+    // RFC 2231 allows a language after the charset, as in `utf-8*en`.
+    let charset = charset.split('*').next()?;
+    let bytes = match encoding {
+        "Q" | "q" => decode_q(text)?,
+        "B" | "b" => decode_b(text)?,
+        _ => return None,
+    };
+
+    match charset.parse::<Charset>().ok()? {
+        Charset::Utf8 => String::from_utf8(bytes).ok().map(Cow::Owned),
+        Charset::UsAscii if bytes.is_ascii() => String::from_utf8(bytes).ok().map(Cow::Owned),
+        Charset::Iso8859_1 => Some(Cow::Owned(bytes.into_iter().map(char::from).collect())),
+        _ => None,
+    }
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => decoded.push(b' '),
+            b'=' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 2;
+            },
+            c => decoded.push(c),
+        }
+        i += 1;
+    }
+    Some(decoded)
+}
+
+fn decode_b(text: &str) -> Option<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        Some(u32::from(value))
+    }
+
+    let bytes = text.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let data = text.trim_end_matches('=').as_bytes();
+    if bytes.len() - data.len() > 2 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        let mut bits = 0;
+        for (i, &c) in chunk.iter().enumerate() {
+            bits |= sextet(c)? << (18 - 6 * i);
+        }
+        let len = chunk.len() * 6 / 8;
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..=len]);
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+
+    #[test]
+    fn test_rfc2047_q() {
+        assert_eq!(decode("=?UTF-8?Q?caf=C3=A9_au_lait?=").unwrap(), "café au lait");
+        assert_eq!(decode("=?iso-8859-1?q?caf=E9?=").unwrap(), "café");
+        assert_eq!(decode("=?us-ascii*en?Q?a=3Db?=").unwrap(), "a=b");
+        assert_eq!(decode("=?utf-8?Q??=").unwrap(), "");
+    }
+
+    #[test]
+    fn test_rfc2047_b() {
+        assert_eq!(decode("=?UTF-8?B?4oKsIHJhdGVz?=").unwrap(), "€ rates");
+        assert_eq!(decode("=?utf-8?b?YQ==?=").unwrap(), "a");
+        assert_eq!(decode("=?utf-8?b?YWI=?=").unwrap(), "ab");
+        assert_eq!(decode("=?utf-8?b?YWJj?=").unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_rfc2047_multiple_words() {
+        assert_eq!(decode("=?utf-8?q?a?= =?utf-8?b?Yg==?=").unwrap(), "ab");
+        assert_eq!(decode("=?utf-8?q?a?= plain"), None);
+    }
+
+    #[test]
+    fn test_rfc2047_malformed() {
+        for src in &[
+            "",
+            "plain",
+            "=?utf-8?q?unterminated",
+            "=?utf-8?x?abc?=",
+            "=?utf-8?q?bad=ZZ?=",
+            "=?utf-8?q?short=4?=",
+            "=?utf-8?q?a?b?=",
+            "=?utf-8?b?YQ?=",
+            "=?utf-8?b?Y===?=",
+            "=?utf-8?b?Y!==?=",
+            "=?utf-8?q?=FF?=",
+            "=?us-ascii?q?=E9?=",
+            "=?shift_jis?q?abc?=",
+        ] {
+            assert_eq!(decode(src), None, "{:?}", src);
+        }
+    }
+}
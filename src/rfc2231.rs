@@ -0,0 +1,219 @@
+use std::borrow::Cow;
+
+use mime_parse::Mime;
+
+use crate::{Charset, Value};
+
+// From [RFC 2231](https://tools.ietf.org/html/rfc2231), a long or
+// non-ASCII parameter value can be split into numbered segments, each of
+// which may be percent-encoded:
+//
+// >     title*0*=us-ascii'en'This%20is%20even%20more%20
+// >     title*1*=%2A%2A%2Afun%2A%2A%2A%20
+// >     title*2="isn't it!"
+//
+// The charset and language only prefix the first encoded segment. A
+// single `title*=` value is encoded the same way, but isn't split.
+
+struct Group<'a> {
+    name: &'a str,
+    plain: Option<Value<'a>>,
+    segments: Vec<Segment<'a>>,
+}
+
+struct Segment<'a> {
+    index: u32,
+    encoded: bool,
+    value: Value<'a>,
+}
+
+pub(crate) fn groups(mime: &Mime) -> Vec<(&str, Cow<'_, str>)> {
+    let mut groups: Vec<Group<'_>> = Vec::new();
+
+    for (name, value) in crate::value::params(mime) {
+        let (base, segment) = split_name(name);
+        let i = match groups.iter().position(|group| group.name.eq_ignore_ascii_case(base)) {
+            Some(i) => i,
+            None => {
+                groups.push(Group {
+                    name: base,
+                    plain: None,
+                    segments: Vec::new(),
+                });
+                groups.len() - 1
+            },
+        };
+        let group = &mut groups[i];
+
+        match segment {
+            Some((index, encoded)) => {
+                if group.segments.iter().all(|segment| segment.index != index) {
+                    group.segments.push(Segment { index, encoded, value });
+                }
+            },
+            None => {
+                group.plain.get_or_insert(value);
+            },
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|group| {
+            let value = if group.segments.is_empty() {
+                group.plain.map(|value| value.to_content()).unwrap_or_default()
+            } else {
+                Cow::Owned(join(group.segments))
+            };
+            (group.name, value)
+        })
+        .collect()
+}
+
+// Splits `name*1*` into `name` and its segment number, and whether it is
+// encoded. A name that doesn't look like either form is kept as is.
+fn split_name(name: &str) -> (&str, Option<(u32, bool)>) {
+    let star = match name.find('*') {
+        Some(star) if star > 0 => star,
+        _ => return (name, None),
+    };
+    let (base, rest) = (&name[..star], &name[star + 1..]);
+    if rest.is_empty() {
+        return (base, Some((0, true)));
+    }
+
+    let (digits, encoded) = match rest.strip_suffix('*') {
+        Some(digits) => (digits, true),
+        None => (rest, false),
+    };
+    let is_number = !digits.is_empty() &&
+        digits.bytes().all(|c| c.is_ascii_digit()) &&
+        (digits == "0" || !digits.starts_with('0'));
+    match digits.parse() {
+        Ok(index) if is_number => (base, Some((index, encoded))),
+        _ => (name, None),
+    }
+}
+
+fn join(mut segments: Vec<Segment<'_>>) -> String {
+    segments.sort_by_key(|segment| segment.index);
+
+    let mut charset = None;
+    let mut joined = String::new();
+    for (expected, segment) in (0..).zip(segments) {
+        // A missing segment ends the value.
+        if segment.index != expected {
+            break;
+        }
+
+        let content = segment.value.to_content();
+        if !segment.encoded {
+            joined.push_str(&content);
+            continue;
+        }
+
+        let mut encoded = &*content;
+        if segment.index == 0 {
+            let mut parts = content.splitn(3, '\'');
+            if let (Some(name), Some(_language), Some(rest)) = (parts.next(), parts.next(), parts.next()) {
+                charset = name.parse::<Charset>().ok();
+                encoded = rest;
+            }
+        }
+        joined.push_str(&decode(encoded, charset.as_ref()));
+    }
+    joined
+}
+
+fn decode<'a>(encoded: &'a str, charset: Option<&Charset>) -> Cow<'a, str> {
+    if !encoded.contains('%') {
+        return Cow::Borrowed(encoded);
+    }
+
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match hex.map(|hex| u8::from_str_radix(hex, 16)) {
+            Some(Ok(byte)) if bytes[i] == b'%' => {
+                decoded.push(byte);
+                i += 3;
+            },
+            _ => {
+                decoded.push(bytes[i]);
+                i += 1;
+            },
+        }
+    }
+
+    match charset {
+        Some(Charset::Iso8859_1) => Cow::Owned(decoded.into_iter().map(char::from).collect()),
+        _ => Cow::Owned(String::from_utf8_lossy(&decoded).into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn groups(src: &str) -> Vec<(String, String)> {
+        let mt = MediaType::parse_rfc2231(src).unwrap();
+        mt.test_assert_canonical();
+        mt.iter_rfc2231_groups()
+            .map(|(name, value)| (name.to_owned(), value.into_owned()))
+            .collect()
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|&(a, b)| (a.to_owned(), b.to_owned())).collect()
+    }
+
+    #[test]
+    fn test_rfc2231_continuations() {
+        assert_eq!(
+            groups("text/plain; foo*0=a; foo*1=b; bar=c"),
+            pairs(&[("foo", "ab"), ("bar", "c")])
+        );
+        assert_eq!(
+            groups("text/plain; bar=c; foo*0=\"a b\"; baz=d; foo*1=\" c\""),
+            pairs(&[("bar", "c"), ("foo", "a b c"), ("baz", "d")])
+        );
+        assert_eq!(groups("text/plain"), pairs(&[]));
+    }
+
+    #[test]
+    fn test_rfc2231_extended() {
+        assert_eq!(
+            groups("application/x-stuff; title*=us-ascii'en-us'This%20is%20%2A%2A%2Afun%2A%2A%2A"),
+            pairs(&[("title", "This is ***fun***")])
+        );
+        assert_eq!(
+            groups("application/x-stuff; title*0*=us-ascii'en'This%20is%20even%20more%20; title*1*=%2A%2A%2Afun%2A%2A%2A%20; title*2=\"isn't it!\""),
+            pairs(&[("title", "This is even more ***fun*** isn't it!")])
+        );
+        assert_eq!(groups("a/b; name*=UTF-8''%E2%82%AC%20rates"), pairs(&[("name", "\u{20ac} rates")]));
+        assert_eq!(groups("a/b; name*=iso-8859-1''caf%E9"), pairs(&[("name", "caf\u{e9}")]));
+        // A bad escape is kept as is.
+        assert_eq!(groups("a/b; name*=utf-8''100%25%zz%4"), pairs(&[("name", "100%%zz%4")]));
+    }
+
+    #[test]
+    fn test_rfc2231_out_of_order_and_gaps() {
+        assert_eq!(groups("a/b; foo*1=b; foo*0=a; foo*2=c"), pairs(&[("foo", "abc")]));
+        assert_eq!(groups("a/b; foo*0=a; foo*2=c"), pairs(&[("foo", "a")]));
+        assert_eq!(groups("a/b; foo*1=b"), pairs(&[("foo", "")]));
+        assert_eq!(groups("a/b; foo*0=a; foo*0=x; foo*1=b"), pairs(&[("foo", "ab")]));
+    }
+
+    #[test]
+    fn test_rfc2231_plain_and_odd_names() {
+        // Segments win over a plain value with the same name.
+        assert_eq!(groups("a/b; foo=plain; foo*0=a"), pairs(&[("foo", "a")]));
+        assert_eq!(groups("a/b; foo=x; foo=y"), pairs(&[("foo", "x")]));
+        assert_eq!(
+            groups("a/b; foo*x=1; *=2; foo*01=3; foo**=4"),
+            pairs(&[("foo*x", "1"), ("*", "2"), ("foo*01", "3"), ("foo**", "4")])
+        );
+    }
+}
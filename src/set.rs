@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::{MediaRange, MediaType};
+
+/// A set of `MediaType`s, for checking what is supported.
+///
+/// Media types are grouped by their lowercased type and subtype, so looking
+/// one up, or finding those matched by a `type/*` range, doesn't need to
+/// check every stored media type. Within a group, they are compared the
+/// same way as with `==`, so parameter order doesn't matter.
+///
+/// # Example
+///
+/// ```
+/// use mime::{MediaRange, MediaTypeSet};
+///
+/// let supported = vec![mime::TEXT_HTML_UTF_8, mime::APPLICATION_JSON]
+///     .into_iter()
+///     .collect::<MediaTypeSet>();
+///
+/// assert!(supported.contains(&mime::APPLICATION_JSON));
+/// assert!(!supported.contains(&mime::TEXT_HTML));
+///
+/// let range = MediaRange::parse("text/*").unwrap();
+/// assert_eq!(supported.find_match(&range), Some(&mime::TEXT_HTML_UTF_8));
+/// ```
+#[derive(Clone, Default)]
+pub struct MediaTypeSet {
+    groups: BTreeMap<(String, String), Vec<MediaType>>,
+    len: usize,
+}
+
+impl MediaTypeSet {
+    /// Create an empty `MediaTypeSet`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let set = mime::MediaTypeSet::new();
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> MediaTypeSet {
+        MediaTypeSet::default()
+    }
+
+    /// Add a `MediaType` to the set.
+    ///
+    /// Returns `false` if an equal one was already in the set, in which
+    /// case the set is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut set = mime::MediaTypeSet::new();
+    ///
+    /// assert!(set.insert(mime::TEXT_PLAIN_UTF_8));
+    /// assert!(!set.insert("text/plain; charset=UTF-8".parse().unwrap()));
+    /// assert!(set.insert(mime::TEXT_PLAIN));
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn insert(&mut self, mt: MediaType) -> bool {
+        let group = self.groups.entry(key(&mt)).or_default();
+        if group.contains(&mt) {
+            return false;
+        }
+        group.push(mt);
+        self.len += 1;
+        true
+    }
+
+    /// Checks if an equal `MediaType` is in the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut set = mime::MediaTypeSet::new();
+    /// set.insert(mime::IMAGE_PNG);
+    ///
+    /// assert!(set.contains(&mime::IMAGE_PNG));
+    /// assert!(set.contains(&"Image/PNG".parse().unwrap()));
+    /// assert!(!set.contains(&mime::IMAGE_JPEG));
+    /// ```
+    pub fn contains(&self, mt: &MediaType) -> bool {
+        self.groups
+            .get(&key(mt))
+            .is_some_and(|group| group.contains(mt))
+    }
+
+    /// Find a `MediaType` in the set that a `MediaRange` matches.
+    ///
+    /// If several match, the one with the lowest type and subtype (in
+    /// lowercase, alphabetically) is returned, and among those, the
+    /// first one inserted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaRange;
+    ///
+    /// let set = vec![mime::IMAGE_PNG, mime::IMAGE_GIF, mime::TEXT_PLAIN]
+    ///     .into_iter()
+    ///     .collect::<mime::MediaTypeSet>();
+    ///
+    /// assert_eq!(set.find_match(&MediaRange::parse("image/*").unwrap()), Some(&mime::IMAGE_GIF));
+    /// assert_eq!(set.find_match(&mime::STAR_STAR), Some(&mime::IMAGE_GIF));
+    /// assert_eq!(set.find_match(&MediaRange::parse("text/plain; charset=utf-8").unwrap()), None);
+    /// ```
+    pub fn find_match(&self, range: &MediaRange) -> Option<&MediaType> {
+        let candidates: Box<dyn Iterator<Item = &Vec<MediaType>>> = if range.type_() == crate::STAR {
+            Box::new(self.groups.values())
+        } else {
+            let type_ = range.type_().to_ascii_lowercase();
+            if range.subtype() == crate::STAR {
+                let start = (type_, String::new());
+                let groups = self.groups
+                    .range(start.clone()..)
+                    .take_while(move |((t, _), _)| *t == start.0)
+                    .map(|(_, group)| group);
+                Box::new(groups)
+            } else {
+                let subtype = range.subtype().to_ascii_lowercase();
+                Box::new(self.groups.get(&(type_, subtype)).into_iter())
+            }
+        };
+
+        candidates
+            .flatten()
+            .find(|mt| range.matches(mt))
+    }
+
+    /// Get how many media types are in the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let set = vec![mime::TEXT_PLAIN, mime::TEXT_PLAIN]
+    ///     .into_iter()
+    ///     .collect::<mime::MediaTypeSet>();
+    ///
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the set has no media types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::MediaTypeSet::new().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the media types in the set.
+    ///
+    /// They are in the same order [`find_match`](MediaTypeSet::find_match)
+    /// checks them in.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let set = vec![mime::TEXT_PLAIN, mime::IMAGE_PNG]
+    ///     .into_iter()
+    ///     .collect::<mime::MediaTypeSet>();
+    ///
+    /// let all = set.iter().collect::<Vec<_>>();
+    /// assert_eq!(all, [&mime::IMAGE_PNG, &mime::TEXT_PLAIN]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &MediaType> {
+        self.groups.values().flatten()
+    }
+}
+
+fn key(mt: &MediaType) -> (String, String) {
+    (mt.type_().to_ascii_lowercase(), mt.subtype().to_ascii_lowercase())
+}
+
+impl fmt::Debug for MediaTypeSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<MediaType> for MediaTypeSet {
+    fn extend<I: IntoIterator<Item = MediaType>>(&mut self, iter: I) {
+        for mt in iter {
+            self.insert(mt);
+        }
+    }
+}
+
+impl FromIterator<MediaType> for MediaTypeSet {
+    fn from_iter<I: IntoIterator<Item = MediaType>>(iter: I) -> Self {
+        let mut set = MediaTypeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn set(types: &[&str]) -> MediaTypeSet {
+        types.iter().map(|s| MediaType::parse(*s).unwrap()).collect()
+    }
+
+    fn range(s: &str) -> MediaRange {
+        MediaRange::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_set_contains() {
+        let set = set(&["text/plain; a=1; b=2", "text/plain", "Image/SVG+XML"]);
+
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&MediaType::parse("text/plain; b=2; a=1").unwrap()));
+        assert!(set.contains(&TEXT_PLAIN));
+        assert!(set.contains(&IMAGE_SVG));
+        assert!(set.contains(&MediaType::parse_preserving("image/Svg+Xml").unwrap()));
+        assert!(!set.contains(&MediaType::parse("text/plain; a=1").unwrap()));
+        assert!(!set.contains(&TEXT_HTML));
+    }
+
+    #[test]
+    fn test_set_insert_dedups() {
+        let mut set = MediaTypeSet::new();
+
+        assert!(set.insert(TEXT_PLAIN_UTF_8));
+        assert!(!set.insert(MediaType::parse("TEXT/PLAIN; CHARSET=\"utf-8\"").unwrap()));
+        assert!(set.insert(MediaType::parse_preserving("Text/Plain").unwrap()));
+        assert!(!set.insert(TEXT_PLAIN));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_set_find_match() {
+        let set = set(&["text/html; level=1", "text/plain", "image/png", "application/json"]);
+
+        assert_eq!(set.find_match(&range("text/*")).unwrap(), "text/html; level=1");
+        assert_eq!(set.find_match(&range("text/*; level=2")), None);
+        assert_eq!(set.find_match(&range("text/html")).unwrap(), "text/html; level=1");
+        assert_eq!(set.find_match(&range("TEXT/HTML; q=0.5")).unwrap(), "text/html; level=1");
+        assert_eq!(set.find_match(&range("image/*")).unwrap(), "image/png");
+        assert_eq!(set.find_match(&range("*/*")).unwrap(), "application/json");
+        assert_eq!(set.find_match(&range("text/html; level=2")), None);
+        assert_eq!(set.find_match(&range("video/*")), None);
+        assert_eq!(set.find_match(&range("image/gif")), None);
+        // `text/*` must not spill into a type sorting after it.
+        assert_eq!(set.find_match(&range("tex/*")), None);
+
+        assert_eq!(MediaTypeSet::new().find_match(&STAR_STAR), None);
+    }
+}
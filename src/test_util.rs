@@ -0,0 +1,115 @@
+//! Assertions for tests that use `MediaType`s.
+//!
+//! These are only for tests, and need the `test-util` feature, such as
+//! in a `[dev-dependencies]` entry:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! mime = { version = "0.4.0-a.0", features = ["test-util"] }
+//! ```
+
+use crate::MediaType;
+
+/// Asserts that `source` parses to a `MediaType` equal to `expected`.
+///
+/// # Example
+///
+/// ```
+/// use mime::test_util::assert_parses_to;
+///
+/// assert_parses_to(&mime::TEXT_PLAIN_UTF_8, "Text/Plain; Charset=UTF-8");
+/// ```
+///
+/// # Panics
+///
+/// Panics if `source` isn't a valid media type, or isn't equal to
+/// `expected`. The message shows both the parsed and expected forms.
+#[track_caller]
+pub fn assert_parses_to(expected: &MediaType, source: &str) {
+    match MediaType::parse(source) {
+        Ok(parsed) => assert!(
+            parsed == *expected,
+            "{:?} parsed to `{}`, but expected `{}`",
+            source,
+            parsed,
+            expected,
+        ),
+        Err(err) => panic!("{:?} doesn't parse, but expected `{}`: {}", source, expected, err),
+    }
+}
+
+/// Asserts that two `MediaType`s have the same parameters, in any order.
+///
+/// The type and subtype are ignored. Parameters are compared like with
+/// [`MediaType::parameters_eq_ignore_order`].
+///
+/// # Example
+///
+/// ```
+/// use mime::test_util::assert_params_eq;
+///
+/// let a = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+/// let b = mime::MediaType::parse("text/markdown; format=flowed; charset=UTF-8").unwrap();
+/// assert_params_eq(&a, &b);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the parameters differ, showing both lists.
+#[track_caller]
+pub fn assert_params_eq(left: &MediaType, right: &MediaType) {
+    fn list(mt: &MediaType) -> Vec<String> {
+        mt.params()
+            .map(|(name, value)| format!("{}={}", name, value.as_str_repr()))
+            .collect()
+    }
+
+    assert!(
+        left.parameters_eq_ignore_order(right),
+        "params differ:\n  left: {:?} (from `{}`)\n right: {:?} (from `{}`)",
+        list(left),
+        left,
+        list(right),
+        right,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_params_eq, assert_parses_to};
+    use crate::*;
+
+    #[test]
+    fn test_assert_parses_to() {
+        assert_parses_to(&TEXT_HTML_UTF_8, " text/html; charset=\"UTF-8\"");
+        assert_parses_to(&MediaType::parse("a/b; c=d").unwrap(), "A/B;c=d");
+    }
+
+    #[test]
+    #[should_panic(expected = "\"text/html\" parsed to `text/html`, but expected `text/plain`")]
+    fn test_assert_parses_to_unequal() {
+        assert_parses_to(&TEXT_PLAIN, "text/html");
+    }
+
+    #[test]
+    #[should_panic(expected = "\"text\" doesn't parse, but expected `text/plain`")]
+    fn test_assert_parses_to_invalid() {
+        assert_parses_to(&TEXT_PLAIN, "text");
+    }
+
+    #[test]
+    fn test_assert_params_eq() {
+        let a = MediaType::parse("text/plain; a=1; b=\"2\"").unwrap();
+        let b = MediaType::parse("image/png; b=2; A=1").unwrap();
+        assert_params_eq(&a, &b);
+        assert_params_eq(&TEXT_PLAIN, &IMAGE_PNG);
+    }
+
+    #[test]
+    #[should_panic(expected = "left: [\"a=1\"] (from `text/plain; a=1`)\n right: [\"a=2\"] (from `text/plain; a=2`)")]
+    fn test_assert_params_eq_unequal() {
+        let a = MediaType::parse("text/plain; a=1").unwrap();
+        let b = MediaType::parse("text/plain; a=2").unwrap();
+        assert_params_eq(&a, &b);
+    }
+}
@@ -0,0 +1,77 @@
+/// A registration tree of [RFC 6838][rfc], indicated by a subtype facet.
+///
+/// Used with [`MediaType::is_within`](crate::MediaType::is_within).
+///
+/// [rfc]: https://tools.ietf.org/html/rfc6838#section-3
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tree {
+    /// The standards tree, with no facet, such as `text/html`.
+    Standards,
+    /// The vendor tree, such as `application/vnd.api+json`.
+    Vendor,
+    /// The personal or vanity tree, such as `image/prs.btif`.
+    Personal,
+    /// The unregistered tree, such as `application/x.foo`, or the legacy
+    /// `x-` prefix, such as `application/x-www-form-urlencoded`.
+    Unregistered,
+}
+
+impl Tree {
+    pub(crate) fn of(subtype: &str) -> Tree {
+        let has_prefix = |prefix: &str| {
+            subtype
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        };
+
+        if has_prefix("vnd.") {
+            Tree::Vendor
+        } else if has_prefix("prs.") {
+            Tree::Personal
+        } else if has_prefix("x.") || has_prefix("x-") {
+            Tree::Unregistered
+        } else {
+            Tree::Standards
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_tree_standards() {
+        for src in &["text/html", "application/json", "image/svg+xml", "text/vnd", "text/xml-x"] {
+            let mt = MediaType::parse(*src).unwrap();
+            assert!(mt.is_within(Tree::Standards), "{:?}", src);
+            assert!(!mt.is_within(Tree::Vendor), "{:?}", src);
+        }
+    }
+
+    #[test]
+    fn test_tree_vendor() {
+        let mt = MediaType::parse("application/vnd.api+json").unwrap();
+        assert!(mt.is_within(Tree::Vendor));
+        assert!(!mt.is_within(Tree::Standards));
+
+        let mt = MediaType::parse_preserving("application/VND.MS-Excel").unwrap();
+        assert!(mt.is_within(Tree::Vendor));
+    }
+
+    #[test]
+    fn test_tree_personal() {
+        let mt = MediaType::parse("image/prs.btif").unwrap();
+        assert!(mt.is_within(Tree::Personal));
+        assert!(!mt.is_within(Tree::Vendor));
+    }
+
+    #[test]
+    fn test_tree_unregistered() {
+        for src in &["application/x.foo", "application/x-www-form-urlencoded", "font/X-Thing"] {
+            let mt = MediaType::parse_preserving(*src).unwrap();
+            assert!(mt.is_within(Tree::Unregistered), "{:?}", src);
+            assert!(!mt.is_within(Tree::Standards), "{:?}", src);
+        }
+    }
+}
@@ -1,9 +1,14 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::Range;
 use std::str::FromStr;
 
-use mime_parse::{Mime, Parse};
+use mime_parse::{Mime, ParamSource, Parse};
 
-use crate::{InvalidMime, Value};
+use crate::error::Kind;
+use crate::{Charset, EncodingHint, InvalidMime, MediaRange, MissingParam, Params, Tree, Value};
 
 /// A parsed media type (or "MIME").
 ///
@@ -64,6 +69,16 @@ impl MediaType {
     /// assert_eq!(mt, mime::TEXT_PLAIN);
     /// ```
     ///
+    /// Leading and trailing whitespace (spaces and tabs) is ignored.
+    ///
+    /// The type, subtype, every parameter name and the value of `charset`
+    /// are lowercased. Other parameter values keep their case.
+    ///
+    /// Parameters may have empty values, either as empty quotes
+    /// (`name=""`) or with nothing after the equals sign (`name=`). The
+    /// subtype can't be empty, but see
+    /// [`parse_allowing_empty_subtype`](MediaType::parse_allowing_empty_subtype).
+    ///
     /// # Errors
     ///
     /// Returns an error if the source is not a valid media type.
@@ -72,389 +87,4322 @@ impl MediaType {
         mime_parse::Parser::cannot_range()
             .parse(source)
             .map(|mime| MediaType { mime })
-            .map_err(|e| InvalidMime { inner: e })
+            .map_err(|e| InvalidMime { inner: e.into() })
     }
 
-    /// Get the top level media type for this `MediaType`.
+    /// Parse a possibly owned string as a `MediaType`.
+    ///
+    /// This is the same as [`parse`](MediaType::parse), except that an
+    /// owned `String` can be kept as the `MediaType`'s source, lowercased
+    /// in place, instead of being copied. That is only skipped when the
+    /// result is one of the common media types stored as a constant, or
+    /// when the source has surrounding whitespace.
     ///
     /// # Example
     ///
     /// ```
-    /// let mime = mime::TEXT_PLAIN;
-    /// assert_eq!(mime.type_(), "text");
-    /// assert_eq!(mime.type_(), mime::TEXT);
+    /// use std::borrow::Cow;
+    ///
+    /// let header = String::from("Application/Vnd.Thing; Level=1");
+    /// let mt = mime::MediaType::parse_cow(Cow::Owned(header)).unwrap();
+    /// assert_eq!(mt.as_str(), "application/vnd.thing; level=1");
+    ///
+    /// let mt = mime::MediaType::parse_cow(Cow::Borrowed("text/plain")).unwrap();
+    /// assert_eq!(mt, mime::TEXT_PLAIN);
     /// ```
-    #[inline]
-    pub fn type_(&self) -> &str {
-        self.mime.type_()
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_cow(source: Cow<'_, str>) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_cow(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
     }
 
-    /// Get the subtype of this `MediaType`.
+    /// Parse a string literal that is known to be a valid `MediaType`.
+    ///
+    /// Like [`parse`](MediaType::parse), known media types reuse their
+    /// constant, so they don't allocate. For a compile-time check, see
+    /// the `media_type!` macro.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string isn't a valid media type, since that means
+    /// the literal itself is wrong.
     ///
     /// # Example
     ///
     /// ```
-    /// let mime = mime::TEXT_PLAIN;
-    /// assert_eq!(mime.subtype(), "plain");
-    /// assert_eq!(mime.subtype(), mime::PLAIN);
+    /// let json = mime::MediaType::from_static("application/json");
+    /// assert_eq!(json, mime::APPLICATION_JSON);
     ///
-    /// let svg = mime::IMAGE_SVG;
-    /// assert_eq!(svg.subtype(), mime::SVG);
-    /// assert_eq!(svg.subtype(), "svg+xml");
+    /// let custom = mime::MediaType::from_static("application/vnd.my-app+json; v=2");
+    /// assert_eq!(custom.param("v").unwrap(), "2");
     /// ```
-    #[inline]
-    pub fn subtype(&self) -> &str {
-        self.mime.subtype()
+    pub fn from_static(source: &'static str) -> Self {
+        match MediaType::parse(source) {
+            Ok(mt) => mt,
+            Err(e) => panic!("invalid media type literal {:?}: {}", source, e),
+        }
     }
 
-    /// Get an optional +suffix for this `MediaType`.
+    /// Parse each string as a `MediaType`, keeping every result.
+    ///
+    /// The results are in the same order as the inputs, so all the
+    /// invalid ones can be reported at once.
     ///
     /// # Example
     ///
     /// ```
-    /// let svg = mime::IMAGE_SVG;
-    /// assert_eq!(svg.suffix(), Some(mime::XML));
-    /// assert_eq!(svg.suffix(), Some("xml"));
+    /// let results = mime::MediaType::parse_many(&["text/plain", "text", "image/png"]);
+    ///
+    /// assert_eq!(results[0].as_ref().unwrap(), &mime::TEXT_PLAIN);
+    /// assert!(results[1].is_err());
+    /// assert_eq!(results[2].as_ref().unwrap(), &mime::IMAGE_PNG);
+    /// ```
+    pub fn parse_many(inputs: &[&str]) -> Vec<Result<Self, InvalidMime>> {
+        inputs.iter().map(|s| MediaType::parse(*s)).collect()
+    }
+
+    /// Parse each string as a `MediaType`, stopping at the first error.
     ///
+    /// # Example
     ///
-    /// assert!(mime::TEXT_PLAIN.suffix().is_none());
     /// ```
-    #[inline]
-    pub fn suffix(&self) -> Option<&str> {
-        self.mime.suffix()
+    /// use mime::MediaType;
+    ///
+    /// let all = MediaType::parse_all_ok(&["text/plain", "image/png"]).unwrap();
+    /// assert_eq!(all, [mime::TEXT_PLAIN, mime::IMAGE_PNG]);
+    ///
+    /// let (index, _err) = MediaType::parse_all_ok(&["text/plain", "text", "*/*"]).unwrap_err();
+    /// assert_eq!(index, 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the index of the first invalid input, along with its error.
+    pub fn parse_all_ok(inputs: &[&str]) -> Result<Vec<Self>, (usize, InvalidMime)> {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(i, s)| MediaType::parse(*s).map_err(|e| (i, e)))
+            .collect()
     }
 
-    /// Look up a parameter by name.
+    /// Parse the first valid one of several candidate strings.
+    ///
+    /// Candidates are tried in order, such as a `Content-Type` header,
+    /// then a fallback header, then a default. Returns `None` if none
+    /// of them is a valid media type.
     ///
     /// # Example
     ///
     /// ```
-    /// let mime = mime::TEXT_PLAIN_UTF_8;
-    /// assert_eq!(mime.param(mime::CHARSET), Some(mime::UTF_8));
-    /// assert_eq!(mime.param("charset").unwrap(), "utf-8");
-    /// assert!(mime.param("boundary").is_none());
+    /// use mime::MediaType;
     ///
-    /// let mime = "multipart/form-data; boundary=ABCDEFG".parse::<mime::MediaType>().unwrap();
-    /// assert_eq!(mime.param(mime::BOUNDARY).unwrap(), "ABCDEFG");
+    /// let mt = MediaType::parse_first_of(&["", "image", "image/png", "text/plain"]);
+    /// assert_eq!(mt, Some(mime::IMAGE_PNG));
+    ///
+    /// assert_eq!(MediaType::parse_first_of(&["text", "*/*"]), None);
     /// ```
-    pub fn param<'a>(&'a self, attr: &str) -> Option<Value<'a>> {
-        crate::value::param(&self.mime, attr)
+    pub fn parse_first_of(candidates: &[&str]) -> Option<Self> {
+        candidates.iter().find_map(|s| MediaType::parse(*s).ok())
     }
 
+    /// Parse a string as a `MediaType`, reusing a static string from
+    /// `atoms` when one matches.
+    ///
+    /// Like the constants in this crate, a `MediaType` that matches one of
+    /// the built-in types or one of `atoms` doesn't allocate. This helps an
+    /// application that keeps seeing the same few types of its own.
+    ///
+    /// `atoms` must be sorted, as by [`slice::sort`], since it is binary
+    /// searched. Each atom only matches a source that parses to exactly the
+    /// same string, so it should be lowercase, with parameters written as
+    /// `; name=value`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// static ATOMS: &[&str] = &["application/vnd.acme+json", "application/x-acme"];
+    ///
+    /// let mt = MediaType::parse_preferring_atoms("Application/X-Acme", ATOMS).unwrap();
+    /// assert_eq!(mt, "application/x-acme");
+    /// assert!(std::ptr::eq(mt.as_ref(), ATOMS[1]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_preferring_atoms(source: impl Parse, atoms: &[&'static str]) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_preferring_atoms(source, atoms)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
 
-    /// Returns an iterator over the parameters.
+    /// Parse a string as a `MediaType`, using `cache` to skip parsing the
+    /// same string again.
+    ///
+    /// On a hit, this returns a clone of the cached `MediaType`. On a miss,
+    /// the source is parsed and, if valid, inserted with the source as its
+    /// key. Errors aren't cached. Keys are the exact source strings, so
+    /// `text/html` and `Text/HTML` are cached separately, though the
+    /// `MediaType`s are equal.
+    ///
+    /// Cloning a known media type, like the constants in this crate, is
+    /// cheap. Other clones copy the source string, but skip parsing.
+    ///
+    /// The cache is never pruned, so it grows with every distinct valid
+    /// source. Only use it with sources that come from a small set, or
+    /// clear it now and then, since a peer could otherwise send endless
+    /// new media types to use up memory.
     ///
     /// # Example
     ///
     /// ```
-    /// let pkcs7 = mime::MediaType::parse(
-    ///     "application/pkcs7-mime; smime-type=enveloped-data; name=smime.p7m"
+    /// use std::collections::HashMap;
+    /// use mime::MediaType;
+    ///
+    /// let mut cache = HashMap::new();
+    /// let mt = MediaType::parse_and_intern("application/vnd.api+json", &mut cache).unwrap();
+    /// let again = MediaType::parse_and_intern("application/vnd.api+json", &mut cache).unwrap();
+    ///
+    /// assert_eq!(mt, again);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_and_intern(source: &str, cache: &mut HashMap<String, MediaType>) -> Result<Self, InvalidMime> {
+        if let Some(mt) = cache.get(source) {
+            return Ok(mt.clone());
+        }
+
+        let mt = MediaType::parse(source)?;
+        cache.insert(source.to_owned(), mt.clone());
+        Ok(mt)
+    }
+
+    /// Parse the values of a repeated `Content-Type` header.
+    ///
+    /// HTTP treats repeated field lines as one value, joined by commas.
+    /// Since `Content-Type` allows only a single media type, having more
+    /// than one is an error, even if they are the same. This follows
+    /// [RFC 9110][rfc], which says a recipient can treat such a field as
+    /// invalid, rather than guessing which value was meant. Empty values
+    /// and empty list entries are ignored.
+    ///
+    /// [rfc]: https://www.rfc-editor.org/rfc/rfc9110#section-5.5
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let mt = MediaType::parse_header_multi(&["", "text/html; charset=utf-8"]).unwrap();
+    /// assert_eq!(mt, mime::TEXT_HTML_UTF_8);
+    ///
+    /// assert!(MediaType::parse_header_multi(&["text/html", "text/html"]).is_err());
+    /// assert!(MediaType::parse_header_multi(&["text/html, text/plain"]).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there isn't exactly one media type, or if it is
+    /// not valid. Error positions are within the value that had it.
+    pub fn parse_header_multi(values: &[&str]) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_header_multi(values)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, reporting every error found.
+    ///
+    /// Where [`parse`](MediaType::parse) stops at the first error, this
+    /// keeps checking the parameters after it, resuming at each `;` that
+    /// isn't inside a quoted value. This helps a linter or a form show all
+    /// the problems in a header at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let errors = MediaType::parse_collecting_errors("text/plain; a; b=\"c").unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    ///
+    /// let mt = MediaType::parse_collecting_errors("text/plain").unwrap();
+    /// assert_eq!(mt, mime::TEXT_PLAIN);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors in the order they appear in the source. The first
+    /// one is always the error that [`parse`](MediaType::parse) would return.
+    pub fn parse_collecting_errors(source: impl Parse) -> Result<Self, Vec<InvalidMime>> {
+        mime_parse::Parser::cannot_range()
+            .parse_collecting_errors(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|errors| errors.into_iter().map(|e| InvalidMime { inner: e.into() }).collect())
+    }
+
+    /// Parse a string as a `MediaType`, keeping only some parameters.
+    ///
+    /// Parameter names in `keep` are compared case-insensitively. The
+    /// other parameters are left out while parsing, instead of being
+    /// removed afterwards, so a lone `charset=utf-8` is stored as compactly
+    /// as the `*_UTF_8` constants.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse_trim_params(
+    ///     "multipart/form-data; boundary=secret; charset=utf-8",
+    ///     &["Charset"],
     /// ).unwrap();
     ///
-    /// let mut params = pkcs7.params();
+    /// assert_eq!(mt, "multipart/form-data; charset=utf-8");
+    /// assert!(mt.param("boundary").is_none());
+    /// ```
     ///
-    /// let (name, value) = params.next().unwrap();
-    /// assert_eq!(name, "smime-type");
-    /// assert_eq!(value, "enveloped-data");
+    /// # Errors
     ///
-    /// let (name, value) = params.next().unwrap();
-    /// assert_eq!(name, "name");
-    /// assert_eq!(value, "smime.p7m");
+    /// Returns an error if the whole source, including the dropped
+    /// parameters, isn't a valid media type.
+    pub fn parse_trim_params(source: impl Parse, keep: &[&str]) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_keeping_params(source, keep)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, or use `application/octet-stream`.
+    ///
+    /// This is the usual fallback for content of an unknown type. The
+    /// default is the [`APPLICATION_OCTET_STREAM`](crate::APPLICATION_OCTET_STREAM)
+    /// constant, so falling back doesn't allocate.
+    ///
+    /// # Example
     ///
-    /// assert!(params.next().is_none());
     /// ```
-    #[inline]
-    pub fn params(&self) -> impl Iterator<Item = (&str, Value)> {
-        crate::value::params(&self.mime)
+    /// use mime::MediaType;
+    ///
+    /// assert_eq!(MediaType::parse_or_octet_stream("image/png"), mime::IMAGE_PNG);
+    /// assert_eq!(MediaType::parse_or_octet_stream("not a mime"), mime::APPLICATION_OCTET_STREAM);
+    /// ```
+    pub fn parse_or_octet_stream(source: impl Parse) -> Self {
+        MediaType::parse_or(source, crate::APPLICATION_OCTET_STREAM)
     }
 
-    /// Returns true if the media type has at last one parameter.
+    /// Parse a string as a `MediaType`, or use a default if it's invalid.
     ///
     /// # Example
     ///
     /// ```
-    /// let plain_text: mime::MediaType = "text/plain".parse().unwrap();
-    /// assert_eq!(plain_text.has_params(), false);
+    /// use mime::MediaType;
     ///
-    /// let plain_text_utf8: mime::MediaType = "text/plain; charset=utf-8".parse().unwrap();
-    /// assert_eq!(plain_text_utf8.has_params(), true);
+    /// assert_eq!(MediaType::parse_or("text/csv", mime::TEXT_PLAIN), mime::TEXT_CSV);
+    /// assert_eq!(MediaType::parse_or("text", mime::TEXT_PLAIN), mime::TEXT_PLAIN);
     /// ```
-    #[inline]
-    pub fn has_params(&self) -> bool {
-        self.mime.has_params()
+    pub fn parse_or(source: impl Parse, default: MediaType) -> Self {
+        MediaType::parse(source).unwrap_or(default)
     }
 
-    /// Transforms the media type into its non-parametrized form.
+    /// Parse a string as a `MediaType`, and collect its parameters.
+    ///
+    /// This is a convenience over [`parse`](MediaType::parse) and
+    /// [`params`](MediaType::params), for when the parameters need to
+    /// outlive the `MediaType`. Values are unquoted, and every pair is
+    /// allocated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let (mt, params) = mime::MediaType::parse_and_collect(
+    ///     r#"multipart/form-data; boundary="a b""#
+    /// ).unwrap();
+    ///
+    /// assert_eq!(mt.essence(), "multipart/form-data");
+    /// assert_eq!(params, [("boundary".to_owned(), "a b".to_owned())]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_and_collect(source: impl Parse) -> Result<(Self, Vec<(String, String)>), InvalidMime> {
+        let mt = MediaType::parse(source)?;
+        let params = mt
+            .params()
+            .map(|(name, value)| (name.to_owned(), value.to_content().into_owned()))
+            .collect();
+        Ok((mt, params))
+    }
+
+    /// Parse a string as a `MediaType`, only allowing RFC 6838 names.
+    ///
+    /// This is stricter than [`parse`](MediaType::parse), which allows any
+    /// HTTP token in the type and subtype. See
+    /// [`is_rfc6838_name`](MediaType::is_rfc6838_name) for the rules.
     ///
     /// # Example
     ///
     /// ```
     /// use mime::MediaType;
     ///
-    /// let html_xml_utf8 = MediaType::parse("text/html+xml; charset=utf-8").unwrap();
-    /// assert!(html_xml_utf8.has_params());
+    /// assert_eq!(MediaType::parse_rfc6838("image/svg+xml").unwrap(), mime::IMAGE_SVG);
     ///
-    /// let html_xml = html_xml_utf8.without_params();
-    /// assert!(!html_xml.has_params());
+    /// assert!(MediaType::parse("text/foo%bar").is_ok());
+    /// assert!(MediaType::parse_rfc6838("text/foo%bar").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type, or if
+    /// the type or subtype isn't a valid RFC 6838 name.
+    pub fn parse_rfc6838(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .rfc6838_names()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a `Content-Type` header value as a `MediaType`.
+    ///
+    /// Header values in the wild are often a little off. On top of what
+    /// [`parse`](MediaType::parse) already accepts, this allows spaces and
+    /// tabs around the `=` of a parameter. All together, that's:
+    ///
+    /// - spaces and tabs before and after the value,
+    /// - empty parameters, including a trailing `;`,
+    /// - empty parameter values (`name=`),
+    /// - spaces or tabs on either side of `=` (`charset = utf-8`).
+    ///
+    /// Everything else is parsed as strictly as `parse`.
+    ///
+    /// # Example
     ///
-    /// assert_eq!(html_xml, "text/html+xml");
     /// ```
-    #[inline]
-    pub fn without_params(mut self) -> Self {
-        self.mime = self.mime.without_params();
-        self
+    /// let mt = mime::MediaType::parse_content_type_header(" text/html; charset = UTF-8; ").unwrap();
+    ///
+    /// assert_eq!(mt, mime::TEXT_HTML_UTF_8);
+    /// assert!(mime::MediaType::parse("text/html; charset = UTF-8").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type, even with
+    /// the leniencies above.
+    pub fn parse_content_type_header(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .ows_around_equals()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
     }
 
-    #[cfg(test)]
-    pub(super) fn test_assert_asterisks(&self) {
-        assert!(!self.as_ref().contains('*'), "{:?} contains an asterisk", self);
+    /// Parse a string as a `MediaType`, keeping its original casing.
+    ///
+    /// [`parse`](MediaType::parse) lowercases the type, subtype, parameter
+    /// names and `charset` value. This keeps the string as it was given,
+    /// so `as_ref()` and `Display` echo it back unchanged (apart from
+    /// surrounding whitespace, or an empty parameter list). Accessors like
+    /// [`type_`](MediaType::type_) return the original casing too.
+    ///
+    /// Comparisons still ignore case, so this is equal to the same
+    /// media type parsed with `parse`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse_preserving("Text/HTML; Charset=UTF-8").unwrap();
+    ///
+    /// assert_eq!(mt.to_string(), "Text/HTML; Charset=UTF-8");
+    /// assert_eq!(mt.subtype(), "HTML");
+    /// assert_eq!(mt.param("charset").unwrap(), "utf-8");
+    /// assert_eq!(mt, mime::TEXT_HTML_UTF_8);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_preserving(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .preserve_case()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, keeping the casing of parameter
+    /// names.
+    ///
+    /// This is for echoing back parameters to a peer that treats their
+    /// names as case-sensitive. Unlike with
+    /// [`parse_preserving`](MediaType::parse_preserving), the type, subtype
+    /// and `charset` value are still lowercased. Looking up a parameter
+    /// still ignores case.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse_preserving_param_names("Text/Plain; X-Custom-Param=Yes").unwrap();
+    ///
+    /// assert_eq!(mt.as_ref(), "text/plain; X-Custom-Param=Yes");
+    /// assert_eq!(mt.param("x-custom-param").unwrap(), "Yes");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_preserving_param_names(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .preserve_param_names()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, allowing RFC 2231 parameter names.
+    ///
+    /// [RFC 2231](https://tools.ietf.org/html/rfc2231) splits long or
+    /// encoded parameter values into segments with names like `title*0*`,
+    /// but `*` isn't allowed in a name by [`parse`](MediaType::parse).
+    /// Use [`iter_rfc2231_groups`](MediaType::iter_rfc2231_groups) to put
+    /// the segments back together.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let src = "text/plain; title*=us-ascii'en'Hi%20there";
+    /// assert!(mime::MediaType::parse(src).is_err());
+    ///
+    /// let mt = mime::MediaType::parse_rfc2231(src).unwrap();
+    /// assert_eq!(mt.param("title*").unwrap(), "us-ascii'en'Hi%20there");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type.
+    pub fn parse_rfc2231(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .rfc2231_names()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, rejecting non-ASCII quoted values.
+    ///
+    /// [`parse`](MediaType::parse) accepts the obsolete `obs-text` bytes
+    /// (`0x80` to `0xFF`) inside quoted parameter values, as RFC 7230
+    /// still allows receiving them. This only accepts ASCII `qdtext` and
+    /// quoted-pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let src = r#"text/plain; title="café""#;
+    /// assert!(MediaType::parse(src).is_ok());
+    /// assert!(MediaType::parse_ascii_quoted(src).is_err());
+    ///
+    /// assert!(MediaType::parse_ascii_quoted(r#"text/plain; title="cafe""#).is_ok());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type, or if a
+    /// quoted parameter value contains a non-ASCII byte.
+    pub fn parse_ascii_quoted(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .ascii_quoted()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, rejecting every `*`.
+    ///
+    /// A `*` is never part of a valid `MediaType`, but depending on where
+    /// it is, [`parse`](MediaType::parse) reports it differently. This
+    /// rejects the first one anywhere in the string, even in a quoted
+    /// parameter value, with an error giving its position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let err = MediaType::parse_strict_token_type("text/pl*n").unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid MIME: unexpected asterisk, '*' at position 7");
+    ///
+    /// assert!(MediaType::parse("text/plain; name=\"*\"").is_ok());
+    /// assert!(MediaType::parse_strict_token_type("text/plain; name=\"*\"").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source contains a `*`, or is otherwise
+    /// not a valid media type.
+    pub fn parse_strict_token_type(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .reject_asterisks()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, allowing unquoted values that
+    /// should have been quoted.
+    ///
+    /// Some servers send values with spaces or non-ASCII characters
+    /// without quoting them. Normally that is an error, but here an unquoted
+    /// value runs until the next `;`, and can have anything that would be
+    /// allowed in quotes. Such values are then quoted, so the result is the
+    /// same as if they had been sent correctly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let src = "text/plain; title=My Notes; charset=utf-8";
+    /// assert!(MediaType::parse(src).is_err());
+    ///
+    /// let mt = MediaType::parse_permissive_values(src).unwrap();
+    /// assert_eq!(mt, r#"text/plain; title="My Notes"; charset=utf-8"#);
+    /// assert_eq!(mt.param("title").unwrap(), "My Notes");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source isn't a valid media type, even when
+    /// allowing such values.
+    pub fn parse_permissive_values(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .permissive_values()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, adding a `charset` if it has none.
+    ///
+    /// An existing `charset` parameter is kept as is. Otherwise, one with
+    /// the `default` value is added after any other parameters, quoted if
+    /// needed. Common results like `text/plain; charset=utf-8` use the
+    /// same storage as the constants, so they don't allocate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let mt = MediaType::parse_with_default_charset("text/plain", "utf-8").unwrap();
+    /// assert_eq!(mt, mime::TEXT_PLAIN_UTF_8);
+    ///
+    /// let mt = MediaType::parse_with_default_charset("text/csv; charset=latin1", "utf-8").unwrap();
+    /// assert_eq!(mt.param(mime::CHARSET).unwrap(), "latin1");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type, or if
+    /// `default` can't be a parameter value even when quoted.
+    pub fn parse_with_default_charset(source: impl Parse, default: &str) -> Result<Self, InvalidMime> {
+        let mt = MediaType::parse(source)?;
+        if mt.param(crate::CHARSET).is_some() {
+            return Ok(mt);
+        }
+
+        let value = mime_parse::quote_value(default);
+        let mut with_charset = String::with_capacity(mt.byte_len() + "; charset=".len() + value.len());
+        with_charset.push_str(mt.as_str());
+        with_charset.push_str("; charset=");
+        with_charset.push_str(&value);
+        MediaType::parse_cow(Cow::Owned(with_charset))
+    }
+
+    /// Parse a string as a `MediaType`, and check its charset is allowed.
+    ///
+    /// If there is a `charset` parameter, its unquoted value must be one
+    /// of `allowed`, compared case-insensitively. Aliases aren't mapped,
+    /// so list each name that should be accepted. A media type without a
+    /// `charset` is always allowed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let allowed = ["utf-8", "us-ascii"];
+    ///
+    /// let mt = MediaType::parse_and_validate_charset("text/plain; charset=UTF-8", &allowed).unwrap();
+    /// assert_eq!(mt, mime::TEXT_PLAIN_UTF_8);
+    /// assert!(MediaType::parse_and_validate_charset("text/plain", &allowed).is_ok());
+    ///
+    /// let err = MediaType::parse_and_validate_charset("text/plain; charset=koi8-r", &allowed).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid MIME: unsupported charset at position 20");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid media type, or if a
+    /// `charset` isn't allowed, pointing at its value.
+    pub fn parse_and_validate_charset(source: &str, allowed: &[&str]) -> Result<Self, InvalidMime> {
+        let mt = MediaType::parse(source)?;
+
+        // The parsed source only differs from the input by the trimmed
+        // leading whitespace, so positions can be mapped back.
+        let offset = source.len() - source.trim_start_matches([' ', '\t']).len();
+        for (name, value, _, value_range) in mt.iter_with_positions() {
+            if !name.eq_ignore_ascii_case(crate::CHARSET) {
+                continue;
+            }
+            let content = value.to_content();
+            if !allowed.iter().any(|charset| charset.eq_ignore_ascii_case(&content)) {
+                return Err(InvalidMime {
+                    inner: Kind::UnsupportedCharset {
+                        pos: offset + value_range.start,
+                    },
+                });
+            }
+        }
+        Ok(mt)
+    }
+
+    /// Parse a string as a `MediaType`, allowing an empty subtype.
+    ///
+    /// Some broken clients send a media type like `text/`, which
+    /// [`parse`](MediaType::parse) rejects. Here it is accepted, and
+    /// [`subtype`](MediaType::subtype) is then an empty string. It may
+    /// still have parameters, as in `text/; charset=utf-8`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// assert!(MediaType::parse("text/").is_err());
+    ///
+    /// let mt = MediaType::parse_allowing_empty_subtype("text/; charset=utf-8").unwrap();
+    /// assert_eq!(mt.type_(), "text");
+    /// assert_eq!(mt.subtype(), "");
+    /// assert_eq!(mt.param("charset").unwrap(), "utf-8");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source isn't a valid media type, even with
+    /// an empty subtype allowed.
+    pub fn parse_allowing_empty_subtype(source: impl Parse) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .allow_empty_subtype()
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse bytes as a `MediaType`, replacing invalid UTF-8.
+    ///
+    /// This is lossy: each invalid UTF-8 sequence is replaced with
+    /// `U+FFFD`, the same as [`String::from_utf8_lossy`], and then the
+    /// result is parsed. For a strict parse, check the bytes with
+    /// [`std::str::from_utf8`] and use [`parse`](MediaType::parse).
+    ///
+    /// Quoted parameter values may contain any non-ASCII characters, so
+    /// bad bytes there no longer cause an error. A replacement character
+    /// isn't allowed in the type, subtype, or an unquoted value, so bad
+    /// bytes there are still reported, as is anything else invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse_bytes_lossy(b"text/plain; title=\"caf\xE9\"").unwrap();
+    /// assert_eq!(mt.param("title").unwrap(), "caf\u{FFFD}");
+    ///
+    /// assert!(mime::MediaType::parse_bytes_lossy(b"text/pl\xE9in").is_err());
+    /// assert!(mime::MediaType::parse_bytes_lossy(b"text").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the replaced source isn't a valid media type.
+    pub fn parse_bytes_lossy(source: &[u8]) -> Result<Self, InvalidMime> {
+        MediaType::parse_cow(String::from_utf8_lossy(source))
+    }
+
+    /// Parse the media type at the start of a `data:` URL.
+    ///
+    /// Returns the media type, whether the data is base64 encoded, and the
+    /// data itself, which is everything after the first comma. A final
+    /// `;base64` parameter is the base64 flag, and isn't kept in the media
+    /// type. Without a media type, it is `text/plain; charset=us-ascii`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let (mt, base64, data) = MediaType::parse_data_url("data:image/png;base64,iVBORw0K").unwrap();
+    /// assert_eq!(mt, mime::IMAGE_PNG);
+    /// assert!(base64);
+    /// assert_eq!(data, "iVBORw0K");
+    ///
+    /// let (mt, base64, data) = MediaType::parse_data_url("data:,Hello%2C%20World").unwrap();
+    /// assert_eq!(mt, "text/plain; charset=us-ascii");
+    /// assert!(!base64);
+    /// assert_eq!(data, "Hello%2C%20World");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string doesn't start with `data:`, has no
+    /// comma, or has an invalid media type.
+    pub fn parse_data_url(source: &str) -> Result<(Self, bool, &str), InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_data_url(source)
+            .map(|(mime, base64, data)| (MediaType { mime }, base64, data))
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a `MediaType` at the start of a string, returning what follows it.
+    ///
+    /// The media type ends at the first `delimiter` byte that isn't inside
+    /// a quoted parameter value, or at the end of the string. The remainder
+    /// starts at that delimiter, so it is empty only if there was none.
+    /// The delimiter should be a byte that can't otherwise appear in a
+    /// media type, such as `,`, `|` or `\r`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let (mt, rest) = MediaType::parse_returning_remainder("image/png|width=10", b'|').unwrap();
+    /// assert_eq!(mt, mime::IMAGE_PNG);
+    /// assert_eq!(rest, "|width=10");
+    ///
+    /// let (mt, rest) = MediaType::parse_returning_remainder(r#"text/plain; x="a|b"|more"#, b'|').unwrap();
+    /// assert_eq!(mt.param("x").unwrap(), "a|b");
+    /// assert_eq!(rest, "|more");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the part before the delimiter isn't a valid
+    /// media type. Positions in it are the same as in `source`.
+    pub fn parse_returning_remainder(source: &str, delimiter: u8) -> Result<(Self, &str), InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_returning_remainder(source, delimiter)
+            .map(|(mime, rest)| (MediaType { mime }, rest))
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a string as a `MediaType`, limiting the length of each parameter.
+    ///
+    /// Lengths are in bytes as written in the source, so a quoted value
+    /// counts its quotes. Parsing stops at the first parameter over a
+    /// limit, without looking at the rest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let src = "multipart/form-data; boundary=0123456789";
+    /// assert!(MediaType::parse_with_param_limits(src, 16, 10).is_ok());
+    ///
+    /// let err = MediaType::parse_with_param_limits(src, 16, 9).unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid MIME: a parameter value was too long, at position 39");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a parameter name is longer than `max_name_len`,
+    /// a value is longer than `max_value_len`, or the source is otherwise
+    /// not a valid media type.
+    pub fn parse_with_param_limits(
+        source: impl Parse,
+        max_name_len: usize,
+        max_value_len: usize,
+    ) -> Result<Self, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .max_param_name_len(max_name_len)
+            .max_param_value_len(max_value_len)
+            .parse(source)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Parse a comma-separated list of `MediaType`s.
+    ///
+    /// Some headers, such as `Accept-Post`, carry a list of concrete media
+    /// types. Commas inside quoted parameter values don't split entries,
+    /// and whitespace around each entry is ignored. The types are returned
+    /// in the order they appear.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let list = mime::MediaType::parse_list(
+    ///     r#"text/turtle, application/ld+json; profile="a,b""#
+    /// ).unwrap();
+    ///
+    /// assert_eq!(list.len(), 2);
+    /// assert_eq!(list[0], mime::MediaType::parse("text/turtle").unwrap());
+    /// assert_eq!(list[1].param("profile").unwrap(), "a,b");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any entry is empty or is not a valid media type.
+    pub fn parse_list(source: impl Parse) -> Result<Vec<MediaType>, InvalidMime> {
+        mime_parse::Parser::cannot_range()
+            .parse_list(source)
+            .map(|list| list.into_iter().map(|mime| MediaType { mime }).collect())
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Lazily parse a comma-separated list of `MediaType`s.
+    ///
+    /// Entries are split like with [`parse_list`](MediaType::parse_list),
+    /// but each one is only parsed when the iterator gets to it, so a
+    /// caller can stop early without parsing the rest. An invalid entry is
+    /// yielded as an error, and the entries after it are still parsed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut entries = mime::MediaType::parse_csv_header("text/html, image/p@ng, image/png");
+    ///
+    /// assert_eq!(entries.next().unwrap().unwrap(), mime::TEXT_HTML);
+    /// assert!(entries.next().unwrap().is_err());
+    /// assert_eq!(entries.next().unwrap().unwrap(), mime::IMAGE_PNG);
+    /// assert!(entries.next().is_none());
+    /// ```
+    pub fn parse_csv_header(source: &str) -> impl FusedIterator<Item = Result<MediaType, InvalidMime>> + '_ {
+        mime_parse::Parser::cannot_range()
+            .parse_csv_header(source)
+            .map(|entry| entry.map(|mime| MediaType { mime }).map_err(|e| InvalidMime { inner: e.into() }))
+    }
+
+    /// Parse a string containing only the parameters of a media type.
+    ///
+    /// Some headers, such as `Content-Disposition`, share the parameter
+    /// grammar of media types, without any `type/subtype` before them.
+    /// A leading `;` is optional.
+    ///
+    /// Names are lowercased, and values are unquoted, so this allocates.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let params = mime::MediaType::parse_params_only(
+    ///     r#"filename="my \"file\".txt"; Size=42"#
+    /// ).unwrap();
+    ///
+    /// assert_eq!(params[0], ("filename".to_owned(), "my \"file\".txt".to_owned()));
+    /// assert_eq!(params[1], ("size".to_owned(), "42".to_owned()));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source is not a valid parameter list.
+    pub fn parse_params_only(source: impl Parse) -> Result<Vec<(String, String)>, InvalidMime> {
+        let params = mime_parse::Parser::cannot_range()
+            .parse_params(source)
+            .map_err(|e| InvalidMime { inner: e.into() })?;
+
+        Ok(params
+            .into_iter()
+            .map(|(name, value)| {
+                let value = crate::value::unquote(&value).into_owned();
+                (name, value)
+            })
+            .collect())
+    }
+
+    /// Quotes a parameter value, if it needs it.
+    ///
+    /// Values that are a valid token are returned as they are. Anything
+    /// else is wrapped in double quotes, with `"` and `\` escaped. This is
+    /// the inverse of [`Value::to_content`].
+    ///
+    /// Control characters other than tab can't appear in a quoted-string
+    /// at all, so a value containing them won't parse, even once quoted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// assert_eq!(MediaType::quote_value("utf-8"), "utf-8");
+    /// assert_eq!(MediaType::quote_value("a b"), r#""a b""#);
+    /// assert_eq!(MediaType::quote_value(r#"say "hi""#), r#""say \"hi\"""#);
+    /// ```
+    pub fn quote_value(value: &str) -> Cow<'_, str> {
+        mime_parse::quote_value(value)
+    }
+
+    /// Checks a type and subtype given separately, such as from a config file.
+    ///
+    /// Both must be non-empty tokens that start with a letter or digit.
+    /// Errors say which of the two is invalid, with a position within it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// assert!(MediaType::validate_name("application", "vnd.api+json").is_ok());
+    ///
+    /// let err = MediaType::validate_name("application", "").unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid MIME: the subtype was empty");
+    ///
+    /// let err = MediaType::validate_name("app lication", "json").unwrap_err();
+    /// assert_eq!(err.to_string(), "invalid MIME: invalid type, ' ' at position 3");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either name is empty or not a valid token.
+    pub fn validate_name(type_: &str, subtype: &str) -> Result<(), InvalidMime> {
+        mime_parse::validate_name(type_, subtype).map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Get the canonical string form of this `MediaType`.
+    ///
+    /// This is the same as `as_ref()`, without needing `AsRef` in scope.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(mime::TEXT_PLAIN_UTF_8.as_str(), "text/plain; charset=utf-8");
+    ///
+    /// let mt = mime::MediaType::parse("Text/HTML ; Level=1").unwrap();
+    /// assert_eq!(mt.as_str(), "text/html ; level=1");
+    /// ```
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.mime.as_ref()
+    }
+
+    /// Display this `MediaType` with everything lowercased, including
+    /// parameter values.
+    ///
+    /// This writes the same as `as_str().to_ascii_lowercase()`, but
+    /// lowercases while formatting, without allocating. Width, fill,
+    /// alignment and precision work like they do for a `str`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("Text/HTML; Level=A").unwrap();
+    /// assert_eq!(mt.display_lowercase().to_string(), "text/html; level=a");
+    ///
+    /// let padded = format!("[{:>12}]", mime::MediaType::parse_preserving("Text/HTML").unwrap().display_lowercase());
+    /// assert_eq!(padded, "[   text/html]");
+    /// ```
+    pub fn display_lowercase(&self) -> impl fmt::Display + '_ {
+        DisplayLowercase(self.as_str())
+    }
+
+    /// Get the top level media type for this `MediaType`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN;
+    /// assert_eq!(mime.type_(), "text");
+    /// assert_eq!(mime.type_(), mime::TEXT);
+    /// ```
+    #[inline]
+    pub fn type_(&self) -> &str {
+        self.mime.type_()
+    }
+
+    /// Get the subtype of this `MediaType`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN;
+    /// assert_eq!(mime.subtype(), "plain");
+    /// assert_eq!(mime.subtype(), mime::PLAIN);
+    ///
+    /// let svg = mime::IMAGE_SVG;
+    /// assert_eq!(svg.subtype(), mime::SVG);
+    /// assert_eq!(svg.subtype(), "svg+xml");
+    /// ```
+    #[inline]
+    pub fn subtype(&self) -> &str {
+        self.mime.subtype()
+    }
+
+    /// Checks if the subtype contains `needle`, ignoring ASCII case.
+    ///
+    /// The subtype includes any +suffix, but not the parameters. This is a
+    /// loose check, so prefer [`suffix`](MediaType::suffix) or
+    /// [`is_within`](MediaType::is_within) when they fit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let rss = mime::MediaType::parse("application/rss+xml").unwrap();
+    /// assert!(rss.subtype_contains("xml"));
+    /// assert!(rss.subtype_contains("RSS+"));
+    /// assert!(!rss.subtype_contains("application"));
+    /// ```
+    pub fn subtype_contains(&self, needle: &str) -> bool {
+        let needle = needle.as_bytes();
+        needle.is_empty() ||
+            self.subtype()
+                .as_bytes()
+                .windows(needle.len())
+                .any(|window| window.eq_ignore_ascii_case(needle))
+    }
+
+    /// Get the length of the top level media type, in bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN_UTF_8;
+    /// assert_eq!(mime.type_len(), "text".len());
+    /// ```
+    #[inline]
+    pub fn type_len(&self) -> usize {
+        self.mime.type_len()
+    }
+
+    /// Get the length of the subtype, including any +suffix, in bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN_UTF_8;
+    /// assert_eq!(mime.subtype_len(), "plain".len());
+    ///
+    /// let svg = mime::IMAGE_SVG;
+    /// assert_eq!(svg.subtype_len(), "svg+xml".len());
+    /// ```
+    #[inline]
+    pub fn subtype_len(&self) -> usize {
+        self.mime.subtype_len()
+    }
+
+    /// Get the length of the canonical string form, in bytes.
+    ///
+    /// This is the length written by `Display` or
+    /// [`write_to`](MediaType::write_to), so it can be used to size a
+    /// buffer up front.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN_UTF_8;
+    /// assert_eq!(mime.byte_len(), "text/plain; charset=utf-8".len());
+    /// ```
+    #[inline]
+    pub fn byte_len(&self) -> usize {
+        self.mime.as_ref().len()
+    }
+
+    /// Get an optional +suffix for this `MediaType`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let svg = mime::IMAGE_SVG;
+    /// assert_eq!(svg.suffix(), Some(mime::XML));
+    /// assert_eq!(svg.suffix(), Some("xml"));
+    ///
+    ///
+    /// assert!(mime::TEXT_PLAIN.suffix().is_none());
+    /// ```
+    #[inline]
+    pub fn suffix(&self) -> Option<&str> {
+        self.mime.suffix()
+    }
+
+    /// Get the type, the subtype without any +suffix, and the suffix.
+    ///
+    /// Unlike [`subtype`](MediaType::subtype), the middle part never
+    /// includes the suffix. None of them include parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("application/vnd.api+json; charset=utf-8").unwrap();
+    /// assert_eq!(mt.split_type_subtype_suffix(), ("application", "vnd.api", Some("json")));
+    ///
+    /// assert_eq!(mime::TEXT_PLAIN.split_type_subtype_suffix(), ("text", "plain", None));
+    /// ```
+    pub fn split_type_subtype_suffix(&self) -> (&str, &str, Option<&str>) {
+        let source = self.mime.as_ref();
+        let slash = self.mime.type_len();
+        let end = slash + 1 + self.mime.subtype_len();
+        match self.mime.private_suffix_offset() {
+            Some(plus) => {
+                let plus = usize::from(plus);
+                (&source[..slash], &source[slash + 1..plus], Some(&source[plus + 1..end]))
+            },
+            None => (&source[..slash], &source[slash + 1..end], None),
+        }
+    }
+
+    /// Checks if this is JSON, either as a `json` subtype or a `+json` suffix.
+    ///
+    /// The top level type isn't checked, so `text/json` counts too.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::APPLICATION_JSON.is_json());
+    /// assert!(mime::MediaType::parse("application/vnd.api+json").unwrap().is_json());
+    /// assert!(!mime::MediaType::parse("application/jsonl").unwrap().is_json());
+    /// ```
+    pub fn is_json(&self) -> bool {
+        self.is_subtype_or_suffix(crate::JSON)
+    }
+
+    /// Checks if this is XML, either as an `xml` subtype or a `+xml` suffix.
+    ///
+    /// The top level type isn't checked, so both `text/xml` and
+    /// `application/xml` count.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::TEXT_XML.is_xml());
+    /// assert!(mime::IMAGE_SVG.is_xml());
+    /// assert!(!mime::TEXT_HTML.is_xml());
+    /// ```
+    pub fn is_xml(&self) -> bool {
+        self.is_subtype_or_suffix(crate::XML)
+    }
+
+    /// Guess whether content of this type is worth compressing.
+    ///
+    /// This is meant for deciding whether to send a response with a
+    /// `Content-Encoding` like `gzip`. Parameters are ignored, and the
+    /// checks go in this order:
+    ///
+    /// 1. A short list of known essences: archives like `application/zip`
+    ///    and `text/event-stream` aren't compressible, while others like
+    ///    `application/javascript` or `font/ttf` are.
+    /// 2. A `+gzip`, `+zip`, or `+zstd` suffix isn't compressible, and a
+    ///    `+json`, `+json-seq`, `+xml`, or `+yaml` suffix is.
+    /// 3. Any other `text` subtype is compressible.
+    ///
+    /// Everything else, including images, audio, video, and
+    /// `application/octet-stream`, is assumed to already be compressed,
+    /// or to be unknown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::TEXT_HTML.is_compressible());
+    /// assert!(mime::MediaType::parse("application/vnd.api+json").unwrap().is_compressible());
+    /// assert!(!mime::IMAGE_PNG.is_compressible());
+    /// ```
+    pub fn is_compressible(&self) -> bool {
+        crate::compress::is_compressible(self.type_(), self.subtype(), self.suffix())
+    }
+
+    fn is_subtype_or_suffix(&self, name: &str) -> bool {
+        self.subtype().eq_ignore_ascii_case(name) ||
+            self.suffix().is_some_and(|suffix| suffix.eq_ignore_ascii_case(name))
+    }
+
+    /// Get the dot-separated facets of the subtype, without any +suffix.
+    ///
+    /// A subtype without dots is a single facet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("application/vnd.company.product+json").unwrap();
+    /// let facets = mt.subtype_facets().collect::<Vec<_>>();
+    /// assert_eq!(facets, ["vnd", "company", "product"]);
+    ///
+    /// assert_eq!(mime::IMAGE_SVG.subtype_facets().collect::<Vec<_>>(), ["svg"]);
+    /// ```
+    pub fn subtype_facets(&self) -> impl Iterator<Item = &str> {
+        let subtype = self.subtype();
+        let base = match self.suffix() {
+            Some(suffix) => &subtype[..subtype.len() - suffix.len() - 1],
+            None => subtype,
+        };
+        base.split('.')
+    }
+
+    /// Returns a copy of this `MediaType` with a different +suffix.
+    ///
+    /// `Some(suffix)` adds or replaces the suffix, and `None` removes it.
+    /// The parameters are kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let api = mime::MediaType::parse("application/vnd.api; ext=bulk").unwrap();
+    ///
+    /// let json = api.with_suffix(Some("json")).unwrap();
+    /// assert_eq!(json, "application/vnd.api+json; ext=bulk");
+    ///
+    /// assert_eq!(json.with_suffix(None).unwrap(), api);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the suffix is empty, isn't a valid token, or
+    /// contains a `+`.
+    pub fn with_suffix(&self, suffix: Option<&str>) -> Result<MediaType, InvalidMime> {
+        self.mime
+            .with_suffix(suffix)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Checks if this `MediaType` has the same top level type as another.
+    ///
+    /// The subtype, suffix, and parameters are ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::IMAGE_PNG.same_type(&mime::IMAGE_JPEG));
+    /// assert!(!mime::IMAGE_PNG.same_type(&mime::TEXT_PLAIN));
+    /// ```
+    #[inline]
+    pub fn same_type(&self, other: &MediaType) -> bool {
+        crate::cmp::type_eq(&self.mime, &other.mime)
+    }
+
+    /// Returns a copy of this `MediaType` with a different top level type.
+    ///
+    /// The subtype, any +suffix, and the parameters are kept. The new type
+    /// is lowercased like when parsing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = mime::MediaType::parse("application/json; charset=utf-8").unwrap();
+    /// let legacy = json.replace_type("text").unwrap();
+    ///
+    /// assert_eq!(legacy, "text/json; charset=utf-8");
+    /// assert_eq!(legacy.type_(), mime::TEXT);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `new_type` is empty or isn't a valid token.
+    pub fn replace_type(&self, new_type: &str) -> Result<MediaType, InvalidMime> {
+        self.mime
+            .replace_type(new_type)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Checks if the type and subtype are valid RFC 6838 names.
+    ///
+    /// Parsing follows the HTTP grammar, where names can be any token.
+    /// Media type registrations are stricter: names must start with a
+    /// letter or digit, can't be longer than 127 characters, and can't
+    /// contain `%`, `'`, `` ` ``, `|`, or `~`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::IMAGE_SVG.is_rfc6838_name());
+    ///
+    /// let lenient = mime::MediaType::parse("text/foo%bar").unwrap();
+    /// assert!(!lenient.is_rfc6838_name());
+    /// ```
+    pub fn is_rfc6838_name(&self) -> bool {
+        self.mime.is_rfc6838_name()
+    }
+
+    /// Checks if the subtype is in the given registration tree.
+    ///
+    /// The tree is picked from the facet at the start of the subtype, as
+    /// described in [RFC 6838][rfc]. Both the `x.` facet and the older
+    /// `x-` prefix count as the unregistered tree. This doesn't check that
+    /// the type is actually registered, see
+    /// [`is_registered`](MediaType::is_registered) for that.
+    ///
+    /// [rfc]: https://tools.ietf.org/html/rfc6838#section-3
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::{MediaType, Tree};
+    ///
+    /// assert!(mime::TEXT_HTML.is_within(Tree::Standards));
+    ///
+    /// let vnd = MediaType::parse("application/vnd.api+json").unwrap();
+    /// assert!(vnd.is_within(Tree::Vendor));
+    ///
+    /// let legacy = MediaType::parse("application/x-tar").unwrap();
+    /// assert!(legacy.is_within(Tree::Unregistered));
+    /// ```
+    pub fn is_within(&self, tree: Tree) -> bool {
+        Tree::of(self.subtype()) == tree
+    }
+
+    /// Checks if this is a media type registered with IANA.
+    ///
+    /// Parameters are ignored. This checks against a curated subset of the
+    /// [IANA Media Types registry][iana] bundled with this crate. It has
+    /// common types of each top-level type, and a few common vendor
+    /// (`vnd.`) types, so some registered types, and any registered after
+    /// the list was made, return `false`.
+    ///
+    /// [iana]: https://www.iana.org/assignments/media-types/media-types.xhtml
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::APPLICATION_JSON.is_registered());
+    /// assert!(mime::TEXT_PLAIN_UTF_8.is_registered());
+    ///
+    /// let unregistered = mime::MediaType::parse("application/x-made-up").unwrap();
+    /// assert!(!unregistered.is_registered());
+    /// ```
+    pub fn is_registered(&self) -> bool {
+        crate::registry::is_registered(self.mime.essence())
+    }
+
+    /// Get the preferred replacement, if this is a deprecated media type.
+    ///
+    /// Parameters are carried over to the replacement. The mapping is a
+    /// small, fixed table, covering types obsoleted by an RFC (such as
+    /// `application/javascript`, replaced by `text/javascript` in RFC 9239),
+    /// and common `x-` names of registered types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let legacy = mime::MediaType::parse("application/x-javascript; charset=utf-8").unwrap();
+    /// assert_eq!(legacy.deprecated_alias().unwrap().unwrap(), "text/javascript; charset=utf-8");
+    ///
+    /// assert!(mime::TEXT_JAVASCRIPT.deprecated_alias().unwrap().is_none());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the replacement is longer, and the result
+    /// would be longer than a `MediaType` can be, which is 65,535 bytes.
+    pub fn deprecated_alias(&self) -> Result<Option<MediaType>, InvalidMime> {
+        let preferred = match crate::registry::deprecated_alias(self.mime.essence()) {
+            Some(preferred) => preferred,
+            None => return Ok(None),
+        };
+        self.mime
+            .replace_essence(preferred)
+            .map(|mime| Some(MediaType { mime }))
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Checks for style and correctness issues that parsing allows.
+    ///
+    /// See [`Violation`](crate::Violation) for what is checked. Each
+    /// issue found is reported, in the order of the checks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::Violation;
+    ///
+    /// assert_eq!(mime::TEXT_PLAIN_UTF_8.validate(), Ok(()));
+    ///
+    /// let mt = mime::MediaType::parse("application/javascript; charset=utf-8").unwrap();
+    /// assert_eq!(mt.validate(), Err(vec![
+    ///     Violation::CharsetOnNonText { pos: 24 },
+    ///     Violation::DeprecatedAlias { preferred: "text/javascript" },
+    /// ]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns every violation found, if there are any.
+    pub fn validate(&self) -> Result<(), Vec<crate::Violation>> {
+        let violations = crate::violation::validate(self);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Look up a parameter by name.
+    ///
+    /// Parameter names are case-insensitive, and are always normalized to
+    /// lowercase. Values keep their case, except for `charset`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mime = mime::TEXT_PLAIN_UTF_8;
+    /// assert_eq!(mime.param(mime::CHARSET), Some(mime::UTF_8));
+    /// assert_eq!(mime.param("charset").unwrap(), "utf-8");
+    /// assert!(mime.param("boundary").is_none());
+    ///
+    /// let mime = "multipart/form-data; boundary=ABCDEFG".parse::<mime::MediaType>().unwrap();
+    /// assert_eq!(mime.param(mime::BOUNDARY).unwrap(), "ABCDEFG");
+    /// ```
+    pub fn param<'a>(&'a self, attr: &str) -> Option<Value<'a>> {
+        crate::value::param(&self.mime, attr)
+    }
+
+    /// Look up a parameter that must be present.
+    ///
+    /// This is the same as [`param`](MediaType::param), but a missing
+    /// parameter is an error naming it, so it can be returned with `?`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let form = mime::MediaType::parse("multipart/form-data; boundary=ABC").unwrap();
+    /// assert_eq!(form.require_param(mime::BOUNDARY).unwrap(), "ABC");
+    ///
+    /// let err = mime::TEXT_PLAIN.require_param(mime::CHARSET).unwrap_err();
+    /// assert_eq!(err.to_string(), r#"missing "charset" parameter"#);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no parameter with this name.
+    pub fn require_param<'a>(&'a self, attr: &str) -> Result<Value<'a>, MissingParam> {
+        self.param(attr).ok_or_else(|| MissingParam {
+            name: attr.to_owned(),
+        })
+    }
+
+    /// Look up a parameter by name, and get its unquoted value in lowercase.
+    ///
+    /// Only `charset` values are case-insensitive by default, so
+    /// [`param`](MediaType::param) keeps the case of all others. This is
+    /// for parameters that some other protocol defines as case-insensitive.
+    /// It only allocates if the value has uppercase ASCII or quoted-pairs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// let mt = mime::MediaType::parse(r#"application/x-thing; mode=Strict; level="high""#).unwrap();
+    ///
+    /// assert_eq!(mt.param("mode").unwrap(), "Strict");
+    /// assert_eq!(mt.param_case_insensitive_value("mode").unwrap(), "strict");
+    /// assert!(matches!(mt.param_case_insensitive_value("level"), Some(Cow::Borrowed("high"))));
+    /// assert!(mt.param_case_insensitive_value("other").is_none());
+    /// ```
+    pub fn param_case_insensitive_value(&self, attr: &str) -> Option<Cow<'_, str>> {
+        self.param(attr).map(|value| match value.to_content() {
+            Cow::Borrowed(content) if crate::is_ascii_lowercase(content) => Cow::Borrowed(content),
+            content => Cow::Owned(content.to_ascii_lowercase()),
+        })
+    }
+
+    /// Look up a parameter by name, decoding RFC 2047 encoded-words.
+    ///
+    /// Media types shouldn't use encoded-words, but headers copied from
+    /// email sometimes have values like `"=?UTF-8?Q?caf=C3=A9?="`. If the
+    /// unquoted value is made only of encoded-words, in `Q` or `B`
+    /// encoding, they are decoded. Only the UTF-8, US-ASCII, and
+    /// ISO-8859-1 charsets are supported.
+    ///
+    /// Anything else, including a malformed encoded-word or another
+    /// charset, is returned unquoted but otherwise as is.
+    ///
+    /// This requires the `rfc2047` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse(
+    ///     r#"text/plain; name="=?UTF-8?Q?caf=C3=A9.txt?="; title="=?utf-8?q?bad=ZZ?=""#
+    /// ).unwrap();
+    ///
+    /// assert_eq!(mt.param_decoded("name").unwrap(), "café.txt");
+    /// assert_eq!(mt.param_decoded("title").unwrap(), "=?utf-8?q?bad=ZZ?=");
+    /// assert!(mt.param_decoded("charset").is_none());
+    /// ```
+    #[cfg(feature = "rfc2047")]
+    pub fn param_decoded(&self, attr: &str) -> Option<Cow<'_, str>> {
+        self.param(attr).map(|value| {
+            let content = value.to_content();
+            match crate::rfc2047::decode(&content) {
+                Some(decoded) => Cow::Owned(decoded),
+                None => content,
+            }
+        })
+    }
+
+    /// Get a hint of how the content of this `MediaType` is encoded.
+    ///
+    /// This tells apart the fast UTF-8 case, some other named charset,
+    /// and no `charset` parameter at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::EncodingHint;
+    ///
+    /// assert_eq!(mime::TEXT_PLAIN_UTF_8.encoding_hint(), EncodingHint::Utf8);
+    /// assert_eq!(mime::TEXT_PLAIN.encoding_hint(), EncodingHint::None);
+    ///
+    /// let latin1 = mime::MediaType::parse("text/plain; charset=iso-8859-1").unwrap();
+    /// match latin1.encoding_hint() {
+    ///     EncodingHint::Named(name) => assert_eq!(name, "iso-8859-1"),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn encoding_hint(&self) -> EncodingHint<'_> {
+        crate::charset::encoding_hint(&self.mime)
+    }
+
+    /// Get the raw bytes of a parameter value, including any quotes.
+    ///
+    /// There are three ways to get at a parameter value:
+    ///
+    /// - `param_bytes` returns the value exactly as it's stored, with
+    ///   surrounding quotes and quoted-pairs (`"a \"b\""`).
+    /// - [`param`](MediaType::param) returns a [`Value`], which compares by
+    ///   its content, and can give either form.
+    /// - [`Value::to_content`] returns the logical, unescaped value
+    ///   (`a "b"`).
+    ///
+    /// The stored value is what was parsed, except that `charset` values
+    /// are lowercased.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse(r#"text/plain; title="a \"b\"""#).unwrap();
+    ///
+    /// assert_eq!(mt.param_bytes("title"), Some(&br#""a \"b\"""#[..]));
+    /// assert_eq!(mt.param("title").unwrap().to_content(), r#"a "b""#);
+    /// assert!(mt.param_bytes("charset").is_none());
+    /// ```
+    pub fn param_bytes(&self, attr: &str) -> Option<&[u8]> {
+        self.param(attr).map(|value| value.as_str_repr().as_bytes())
+    }
+
+    /// Get the value of the `charset` parameter, if any.
+    ///
+    /// Quoted values are unquoted, so this only allocates if the value
+    /// contains quoted-pairs that need unescaping.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// assert_eq!(mime::TEXT_PLAIN_UTF_8.charset().unwrap(), "utf-8");
+    /// assert!(mime::TEXT_PLAIN.charset().is_none());
+    ///
+    /// let quoted = mime::MediaType::parse(r#"text/plain; charset="latin1""#).unwrap();
+    /// assert!(matches!(quoted.charset(), Some(Cow::Borrowed("latin1"))));
+    /// ```
+    pub fn charset(&self) -> Option<Cow<'_, str>> {
+        if let ParamSource::KnownCharset(_, charset) = *self.mime.private_params_source() {
+            return Some(Cow::Borrowed(charset.as_str()));
+        }
+        self.param(crate::CHARSET).map(|value| value.to_content())
+    }
+
+    /// Get the `charset` parameter as a [`Charset`](crate::Charset), or a default.
+    ///
+    /// This is for when the absence of a `charset` means something,
+    /// such as UTF-8 for JSON, or ISO-8859-1 for some legacy text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::Charset;
+    ///
+    /// assert_eq!(mime::TEXT_PLAIN_UTF_8.charset_or_default(Charset::Iso8859_1), Charset::Utf8);
+    /// assert_eq!(mime::TEXT_PLAIN.charset_or_default(Charset::Iso8859_1), Charset::Iso8859_1);
+    ///
+    /// let sjis = mime::MediaType::parse("text/plain; charset=MS_Kanji").unwrap();
+    /// assert_eq!(sjis.charset_or_default(Charset::Utf8), Charset::ShiftJis);
+    /// ```
+    pub fn charset_or_default(&self, default: Charset) -> Charset {
+        crate::charset::charset_or_default(&self.mime, default)
+    }
+
+    /// Checks if a parameter has a specific value.
+    ///
+    /// The name is compared case-insensitively. Like [`Value`](crate::Value),
+    /// the value is unquoted, and only compared case-insensitively for the
+    /// `charset` parameter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("multipart/form-data; charset=utf-8; boundary=abc").unwrap();
+    ///
+    /// assert!(mt.param_eq("charset", "UTF-8"));
+    /// assert!(mt.param_eq("boundary", "abc"));
+    /// assert!(!mt.param_eq("boundary", "ABC"));
+    /// assert!(!mt.param_eq("name", "abc"));
+    /// ```
+    pub fn param_eq(&self, name: &str, value: &str) -> bool {
+        self.param(name).is_some_and(|v| v == value)
+    }
+
+    /// Returns an iterator over the parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let pkcs7 = mime::MediaType::parse(
+    ///     "application/pkcs7-mime; smime-type=enveloped-data; name=smime.p7m"
+    /// ).unwrap();
+    ///
+    /// let mut params = pkcs7.params();
+    ///
+    /// let (name, value) = params.next().unwrap();
+    /// assert_eq!(name, "smime-type");
+    /// assert_eq!(value, "enveloped-data");
+    ///
+    /// let (name, value) = params.next().unwrap();
+    /// assert_eq!(name, "name");
+    /// assert_eq!(value, "smime.p7m");
+    ///
+    /// assert!(params.next().is_none());
+    /// ```
+    #[inline]
+    pub fn params(&self) -> Params<'_> {
+        crate::value::params(&self.mime)
+    }
+
+    /// Iterate the parameters of this `MediaType`, from last to first.
+    ///
+    /// When a parameter is repeated, some protocols use the last value.
+    /// This is the same as `params().rev()`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/plain; a=1; b=2; a=3").unwrap();
+    ///
+    /// let (_, last_a) = mt.param_iter_rev().find(|&(name, _)| name == "a").unwrap();
+    /// assert_eq!(last_a, "3");
+    /// ```
+    #[inline]
+    pub fn param_iter_rev(&self) -> std::iter::Rev<Params<'_>> {
+        self.params().rev()
+    }
+
+    /// Get the parameter at a position, counting from 0.
+    ///
+    /// This doesn't need to look at the parameters before it, so indexing
+    /// is as cheap as for a slice.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    ///
+    /// let (name, value) = mt.nth_param(1).unwrap();
+    /// assert_eq!(name, "format");
+    /// assert_eq!(value, "flowed");
+    /// assert_eq!(mt.nth_param(0).unwrap().1, mime::UTF_8);
+    /// assert!(mt.nth_param(2).is_none());
+    /// ```
+    pub fn nth_param(&self, index: usize) -> Option<(&str, Value<'_>)> {
+        crate::value::nth_param(&self.mime, index)
+    }
+
+    /// Iterate the parameters, along with where each is in the source.
+    ///
+    /// Each item is the name, the value, and the byte ranges of the name
+    /// and value in [`as_str`](MediaType::as_str). The value range covers
+    /// the value as written, including any quotes. The ranges are always
+    /// on `char` boundaries.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse(r#"text/plain; a=1; b="x y""#).unwrap();
+    ///
+    /// let (name, value, name_range, value_range) = mt.iter_with_positions().nth(1).unwrap();
+    /// assert_eq!((name, value.to_content()), ("b", "x y".into()));
+    /// assert_eq!(&mt.as_str()[name_range], "b");
+    /// assert_eq!(&mt.as_str()[value_range], r#""x y""#);
+    /// ```
+    pub fn iter_with_positions(&self) -> impl Iterator<Item = (&str, Value<'_>, Range<usize>, Range<usize>)> {
+        crate::value::params_with_positions(&self.mime)
+    }
+
+    /// Collects the parameters into a map, sorted by name.
+    ///
+    /// Values are unquoted. If a parameter name appears more than once,
+    /// the last value wins.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse(
+    ///     r#"text/plain; z=last; charset=utf-8; title="a \"b\"""#
+    /// ).unwrap();
+    ///
+    /// let map = mt.parameters_map();
+    /// let names = map.keys().map(String::as_str).collect::<Vec<_>>();
+    /// assert_eq!(names, ["charset", "title", "z"]);
+    /// assert_eq!(map["title"], r#"a "b""#);
+    /// ```
+    pub fn parameters_map(&self) -> BTreeMap<String, String> {
+        self.params()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.to_content().into_owned()))
+            .collect()
+    }
+
+    /// Iterate the parameters, with RFC 2231 continuations put back together.
+    ///
+    /// Segments like `title*0=a; title*1=b` are joined into one `title`
+    /// parameter, and extended values (`title*=utf-8''%E2%82%AC`) are
+    /// percent-decoded, using the charset of the first segment. Parameters
+    /// are yielded in the order their first segment appears, and their
+    /// values are unquoted.
+    ///
+    /// Segments are joined by their number, so they may appear out of
+    /// order. A value ends at the first missing number, and any later
+    /// segments are ignored. If segments and a plain parameter share a
+    /// name, the segments win.
+    ///
+    /// Names with segments need `*`, so parse them with
+    /// [`parse_rfc2231`](MediaType::parse_rfc2231).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse_rfc2231(
+    ///     "message/external-body; url*1=\"/file.txt\"; access-type=URL; url*0=\"ftp://example.com\""
+    /// ).unwrap();
+    ///
+    /// let params = mt.iter_rfc2231_groups().collect::<Vec<_>>();
+    /// assert_eq!(params[0], ("url", "ftp://example.com/file.txt".into()));
+    /// assert_eq!(params[1], ("access-type", "URL".into()));
+    /// ```
+    pub fn iter_rfc2231_groups(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        crate::rfc2231::groups(&self.mime).into_iter()
+    }
+
+    /// Returns true if the media type has at last one parameter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let plain_text: mime::MediaType = "text/plain".parse().unwrap();
+    /// assert_eq!(plain_text.has_params(), false);
+    ///
+    /// let plain_text_utf8: mime::MediaType = "text/plain; charset=utf-8".parse().unwrap();
+    /// assert_eq!(plain_text_utf8.has_params(), true);
+    /// ```
+    #[inline]
+    pub fn has_params(&self) -> bool {
+        self.mime.has_params()
+    }
+
+    /// Get the essence of this `MediaType`, as an owned `MediaType`.
+    ///
+    /// The essence is the type, subtype and any +suffix, without
+    /// parameters, like the WHATWG MIME Sniffing "essence". Known media
+    /// types reuse their constant, so this doesn't allocate for them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/html; charset=utf-8").unwrap();
+    /// assert_eq!(mt.essence(), mime::TEXT_HTML);
+    ///
+    /// let json = mime::MediaType::parse("application/vnd.api+json; ext=bulk").unwrap();
+    /// assert_eq!(json.essence(), "application/vnd.api+json");
+    /// ```
+    pub fn essence(&self) -> MediaType {
+        self.clone().without_params()
+    }
+
+    /// Get the essence of this `MediaType` as bytes, without allocating.
+    ///
+    /// This is the part of [`as_str`](MediaType::as_str) before any
+    /// parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(mime::TEXT_HTML_UTF_8.essence_bytes(), b"text/html");
+    ///
+    /// let mt = mime::MediaType::parse("image/svg+xml ; charset=utf-8").unwrap();
+    /// assert_eq!(mt.essence_bytes(), b"image/svg+xml");
+    /// ```
+    #[inline]
+    pub fn essence_bytes(&self) -> &[u8] {
+        self.mime.essence().as_bytes()
+    }
+
+    /// Get how many leading bytes the essences of two `MediaType`s share.
+    ///
+    /// Bytes are compared ignoring ASCII case, and parameters are ignored,
+    /// so this is at most the length of the shorter essence. It is meant
+    /// for showing where two media types differ.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let plain = mime::TEXT_PLAIN;
+    /// let other = mime::MediaType::parse("text/plein").unwrap();
+    ///
+    /// let len = plain.common_prefix_len(&other);
+    /// assert_eq!(len, 7);
+    /// assert_eq!(format!("{}|{}", &plain.as_str()[..len], &plain.as_str()[len..]), "text/pl|ain");
+    /// ```
+    pub fn common_prefix_len(&self, other: &MediaType) -> usize {
+        self.essence_bytes()
+            .iter()
+            .zip(other.essence_bytes())
+            .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+            .count()
+    }
+
+    /// Checks if a string is the same media type, once both are normalized.
+    ///
+    /// The string is always parsed, so unlike comparing with `==`, this
+    /// ignores surrounding whitespace and an empty parameter list even
+    /// when `self` has no parameters. Parameters can be in any order.
+    /// Returns `false` if the string isn't a valid media type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    ///
+    /// assert!(mt.canonical_eq("Text/Plain; Format=flowed; charset=UTF-8"));
+    /// assert!(!mt.canonical_eq("text/plain; charset=utf-8"));
+    ///
+    /// assert!(mime::TEXT_PLAIN.canonical_eq(" text/plain; "));
+    /// assert!(!mime::TEXT_PLAIN.canonical_eq("text/"));
+    /// ```
+    pub fn canonical_eq(&self, s: &str) -> bool {
+        crate::cmp::canonical_eq(&self.mime, s)
+    }
+
+    /// Checks if two media types have the same parameters, in any order.
+    ///
+    /// The type and subtype aren't compared at all. Parameter values are
+    /// compared the same way as with `==`. A repeated name has to be
+    /// repeated with the same values in both, in any order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let a = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    /// let b = mime::MediaType::parse("text/markdown; format=flowed; charset=UTF-8").unwrap();
+    ///
+    /// assert!(a.parameters_eq_ignore_order(&b));
+    /// assert!(!a.parameters_eq_ignore_order(&mime::TEXT_PLAIN_UTF_8));
+    /// assert!(mime::TEXT_PLAIN.parameters_eq_ignore_order(&mime::IMAGE_PNG));
+    /// ```
+    pub fn parameters_eq_ignore_order(&self, other: &MediaType) -> bool {
+        crate::cmp::parameters_eq(&self.mime, &other.mime)
+    }
+
+    /// Checks if the parameters of two media types have their names in the
+    /// same order.
+    ///
+    /// Only the names are compared, case-insensitively, one position at a
+    /// time, and both must have the same number of parameters. Values
+    /// and the essence aren't compared, so combine this with `==` to check
+    /// that two equal media types also agree on order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let a = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    /// let b = mime::MediaType::parse("text/plain; format=flowed; charset=utf-8").unwrap();
+    ///
+    /// assert_eq!(a, b);
+    /// assert!(!a.parameter_order_matches(&b));
+    /// assert!(a.parameter_order_matches(&a));
+    /// ```
+    pub fn parameter_order_matches(&self, other: &MediaType) -> bool {
+        // The size_hint of params is exact.
+        self.params().size_hint() == other.params().size_hint() &&
+            self.params()
+                .zip(other.params())
+                .all(|((a, _), (b, _))| a.eq_ignore_ascii_case(b))
+    }
+
+    /// Checks if two media types are equal, apart from any `charset`.
+    ///
+    /// The essence and every other parameter are compared the same way
+    /// as with `==`, so this is useful when routing by type, where the
+    /// charset doesn't matter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert!(mime::TEXT_HTML.compare_ignoring_charset(&mime::TEXT_HTML_UTF_8));
+    /// assert!(!mime::TEXT_HTML.compare_ignoring_charset(&mime::TEXT_PLAIN_UTF_8));
+    /// ```
+    pub fn compare_ignoring_charset(&self, other: &MediaType) -> bool {
+        crate::cmp::eq_ignoring_charset(&self.mime, &other.mime)
+    }
+
+    /// Get a stable 64-bit hash of the top level type only.
+    ///
+    /// This is the 64-bit [FNV-1a][fnv] hash of the lowercased type, so
+    /// it is the same for `TEXT/plain` and `text/html`, and doesn't depend
+    /// on the process or platform. Like
+    /// [`params_fingerprint`](MediaType::params_fingerprint), it is meant
+    /// for bucketing, and different types can still collide.
+    ///
+    /// [fnv]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let upper = mime::MediaType::parse_preserving("TEXT/plain").unwrap();
+    ///
+    /// assert_eq!(upper.type_hash(), mime::TEXT_HTML.type_hash());
+    /// assert_ne!(mime::TEXT_HTML.type_hash(), mime::IMAGE_PNG.type_hash());
+    /// ```
+    pub fn type_hash(&self) -> u64 {
+        crate::cmp::type_hash(&self.mime)
+    }
+
+    /// Get a stable 64-bit hash of the essence, ignoring parameters.
+    ///
+    /// This is the 64-bit [FNV-1a][fnv] hash of the lowercased
+    /// `type/subtype`, including any +suffix. Media types with equal
+    /// essences, in any case, have the same hash.
+    ///
+    /// [fnv]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let upper = mime::MediaType::parse_preserving("TEXT/PLAIN").unwrap();
+    ///
+    /// assert_eq!(upper.essence_hash(), mime::TEXT_PLAIN_UTF_8.essence_hash());
+    /// assert_ne!(mime::TEXT_PLAIN.essence_hash(), mime::TEXT_HTML.essence_hash());
+    /// ```
+    pub fn essence_hash(&self) -> u64 {
+        crate::cmp::essence_hash(&self.mime)
+    }
+
+    /// Get a stable 64-bit hash of the parameters only.
+    ///
+    /// Media types with equal parameters, as compared by
+    /// [`parameters_eq_ignore_order`](MediaType::parameters_eq_ignore_order),
+    /// have the same fingerprint, in whatever order the parameters are.
+    /// The type and subtype aren't included.
+    ///
+    /// Each parameter is hashed with 64-bit [FNV-1a][fnv] over its
+    /// lowercased name, a `=`, and its unquoted value (lowercased for
+    /// `charset`), and the hashes are added together, wrapping on
+    /// overflow. The result doesn't depend on the process or platform, so
+    /// it can be stored, but different parameters can still collide.
+    ///
+    /// [fnv]: http://www.isthe.com/chongo/tech/comp/fnv/
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let a = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    /// let b = mime::MediaType::parse("text/markdown; format=\"flowed\"; charset=UTF-8").unwrap();
+    ///
+    /// assert_eq!(a.params_fingerprint(), b.params_fingerprint());
+    /// assert_ne!(a.params_fingerprint(), mime::TEXT_PLAIN_UTF_8.params_fingerprint());
+    /// assert_eq!(mime::TEXT_PLAIN.params_fingerprint(), 0);
+    /// ```
+    pub fn params_fingerprint(&self) -> u64 {
+        crate::cmp::params_fingerprint(&self.mime)
+    }
+
+    /// Checks if every parameter of this `MediaType` is also in another.
+    ///
+    /// This is a subset check, not equality: `other` may have extra
+    /// parameters, so `a.parameters_are_subset_of(&b)` doesn't imply the
+    /// reverse. Names are compared case-insensitively, and values the same
+    /// way as with `==`. The type and subtype aren't compared at all.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let wanted = mime::MediaType::parse("text/html; charset=utf-8").unwrap();
+    /// let actual = mime::MediaType::parse("text/html; level=1; Charset=UTF-8").unwrap();
+    ///
+    /// assert!(wanted.parameters_are_subset_of(&actual));
+    /// assert!(!actual.parameters_are_subset_of(&wanted));
+    /// assert!(mime::TEXT_HTML.parameters_are_subset_of(&actual));
+    /// ```
+    pub fn parameters_are_subset_of(&self, other: &MediaType) -> bool {
+        crate::cmp::params_subset(self.params(), &other.mime)
+    }
+
+    /// Splits the media type into its non-parametrized form and its parameters.
+    ///
+    /// The returned `MediaType` is an owned copy without parameters, while
+    /// the iterator borrows the parameters from `self`. This makes it easy
+    /// to route on the essence, and then check the original parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+    ///
+    /// let (essence, mut params) = mt.split_params();
+    /// assert_eq!(essence, mime::TEXT_PLAIN);
+    ///
+    /// assert_eq!(params.next().unwrap(), (mime::CHARSET, mime::UTF_8));
+    /// let (name, value) = params.next().unwrap();
+    /// assert_eq!(name, "format");
+    /// assert_eq!(value, "flowed");
+    /// assert!(params.next().is_none());
+    /// ```
+    pub fn split_params(&self) -> (MediaType, Params<'_>) {
+        (self.essence(), self.params())
+    }
+
+    /// Transforms the media type into its non-parametrized form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mime::MediaType;
+    ///
+    /// let html_xml_utf8 = MediaType::parse("text/html+xml; charset=utf-8").unwrap();
+    /// assert!(html_xml_utf8.has_params());
+    ///
+    /// let html_xml = html_xml_utf8.without_params();
+    /// assert!(!html_xml.has_params());
+    ///
+    /// assert_eq!(html_xml, "text/html+xml");
+    /// ```
+    #[inline]
+    pub fn without_params(mut self) -> Self {
+        self.mime = self.mime.without_params();
+        self
+    }
+
+    /// Retains only the parameters for which the predicate returns `true`.
+    ///
+    /// Parameters are kept in their original order. If every parameter is
+    /// removed, this is the same as [`without_params`](MediaType::without_params).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut mt = mime::MediaType::parse(
+    ///     "text/plain; x-trace=abc; charset=utf-8; x-debug=1"
+    /// ).unwrap();
+    ///
+    /// mt.retain_params(|name, _| !name.starts_with("x-"));
+    /// assert_eq!(mt, mime::TEXT_PLAIN_UTF_8);
+    /// ```
+    pub fn retain_params<F>(&mut self, f: F)
+    where
+        F: FnMut(&str, Value<'_>) -> bool,
+    {
+        crate::value::retain_params(&mut self.mime, f)
+    }
+
+    /// Switch to the shared storage of an equal constant, if there is one.
+    ///
+    /// Common media types, like the constants in this crate, don't
+    /// allocate. Parsing and the methods that edit a `MediaType` already
+    /// use them when they can, but some equal forms still allocate, such
+    /// as a quoted `charset="utf-8"`. This frees that allocation when the
+    /// essence is lowercase, and at most a known `charset` parameter is
+    /// left. The string form may change, such as by losing quotes.
+    ///
+    /// Returns whether the `MediaType` now uses shared storage.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut mt = mime::MediaType::parse(r#"text/plain; charset="utf-8""#).unwrap();
+    ///
+    /// assert!(mt.try_intern());
+    /// assert_eq!(mt, mime::TEXT_PLAIN_UTF_8);
+    /// assert_eq!(mt.as_str(), "text/plain; charset=utf-8");
+    ///
+    /// let mut custom = mime::MediaType::parse("text/x-custom").unwrap();
+    /// assert!(!custom.try_intern());
+    /// ```
+    pub fn try_intern(&mut self) -> bool {
+        self.mime.try_intern()
+    }
+
+    /// Returns a copy without the `boundary` parameter.
+    ///
+    /// Multipart boundaries are picked at random for each message, so this
+    /// is useful for cache keys and comparisons. The name is compared
+    /// case-insensitively, and all other parameters are kept in order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let form = mime::MediaType::parse("multipart/form-data; boundary=xyz").unwrap();
+    /// assert_eq!(form.strip_boundary(), "multipart/form-data");
+    /// ```
+    pub fn strip_boundary(&self) -> MediaType {
+        let mut mt = self.clone();
+        mt.retain_params(|name, _| !name.eq_ignore_ascii_case(crate::BOUNDARY));
+        mt
+    }
+
+    /// Returns a copy without the `charset` parameter.
+    ///
+    /// This is for when the content is re-encoded, so the old charset is
+    /// wrong. The name is compared case-insensitively, and all other
+    /// parameters are kept in order. If no parameters are left, a common
+    /// media type like `text/plain` uses the same storage as its constant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// assert_eq!(mime::TEXT_HTML_UTF_8.without_charset(), mime::TEXT_HTML);
+    /// ```
+    pub fn without_charset(&self) -> MediaType {
+        let mut mt = self.clone();
+        mt.retain_params(|name, _| !name.eq_ignore_ascii_case(crate::CHARSET));
+        mt
+    }
+
+    /// Returns this `MediaType` with the parameters of `other` added.
+    ///
+    /// The essence of `other` is ignored. Each parameter of `self` is kept
+    /// in order, unless `other` has a parameter with the same name
+    /// (ignoring case), in which case it is dropped. Then all of the
+    /// parameters of `other` are added, in their order. So on a conflict
+    /// `other` wins, and its parameter moves to the end. If `other` has
+    /// the same name more than once, every copy is added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mt = mime::MediaType::parse("text/html; level=1").unwrap();
+    /// let defaults = mime::MediaType::parse("text/plain; charset=utf-8").unwrap();
+    ///
+    /// assert_eq!(mt.merge_params(&defaults).unwrap(), "text/html; level=1; charset=utf-8");
+    /// assert_eq!(mime::TEXT_HTML.merge_params(&defaults).unwrap(), mime::TEXT_HTML_UTF_8);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result would be longer than a `MediaType`
+    /// can be, which is 65,535 bytes.
+    pub fn merge_params(&self, other: &MediaType) -> Result<MediaType, InvalidMime> {
+        self.mime
+            .merge_params(&other.mime)
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Returns a `MediaRange` that matches only this `MediaType`.
+    ///
+    /// Parameters are kept, so the range only matches media types that
+    /// have at least the same parameters. This is the same as
+    /// `MediaRange::from(mt.clone())`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let range = mime::TEXT_PLAIN_UTF_8.as_media_range();
+    ///
+    /// assert!(range.matches(&mime::TEXT_PLAIN_UTF_8));
+    /// assert!(!range.matches(&mime::TEXT_PLAIN));
+    /// assert!(!range.matches(&mime::TEXT_HTML_UTF_8));
+    /// ```
+    #[inline]
+    pub fn as_media_range(&self) -> MediaRange {
+        MediaRange::from(self.clone())
+    }
+
+    /// Checks if any of the `MediaRange`s matches this `MediaType`.
+    ///
+    /// This stops at the first match. Quality values are ignored, so a
+    /// range with `q=0` still matches.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let accept = [
+    ///     mime::MediaRange::parse("application/json").unwrap(),
+    ///     mime::IMAGE_STAR,
+    /// ];
+    ///
+    /// assert!(mime::IMAGE_PNG.matches_any(&accept));
+    /// assert!(!mime::TEXT_HTML.matches_any(&accept));
+    /// ```
+    pub fn matches_any(&self, ranges: &[MediaRange]) -> bool {
+        ranges.iter().any(|range| range.matches(self))
+    }
+
+    /// Checks whether this `MediaType` matches a simple glob pattern.
+    ///
+    /// The pattern is a `type/subtype` pair where either side may be `*`
+    /// to match anything, so `*/json` matches any type with a subtype of
+    /// exactly `json`. Other parts must match exactly, ignoring ASCII case.
+    /// Parameters on either side are ignored, and a pattern without a `/`
+    /// never matches.
+    ///
+    /// This is meant for configuration values; for `Accept` header
+    /// semantics, use a [`MediaRange`] instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let json = mime::APPLICATION_JSON;
+    ///
+    /// assert!(json.matches_glob("application/*"));
+    /// assert!(json.matches_glob("*/json"));
+    /// assert!(json.matches_glob("*/*"));
+    /// assert!(!json.matches_glob("text/*"));
+    /// assert!(!mime::IMAGE_SVG.matches_glob("*/xml"));
+    /// ```
+    pub fn matches_glob(&self, pattern: &str) -> bool {
+        let pattern = match pattern.find(';') {
+            Some(semicolon) => &pattern[..semicolon],
+            None => pattern,
+        };
+        let (type_, subtype) = match pattern.trim().split_once('/') {
+            Some(parts) => parts,
+            None => return false,
+        };
+        let glob_eq = |glob: &str, part: &str| glob == "*" || glob.eq_ignore_ascii_case(part);
+        glob_eq(type_, self.type_()) && glob_eq(subtype, self.subtype())
+    }
+
+    /// Parses the string form of this `MediaType` again.
+    ///
+    /// Every way of making a `MediaType` should produce a source that
+    /// parses back to an equal value, so this is a check for bugs in
+    /// code that builds or edits them. An empty subtype, or RFC 2231
+    /// parameter names, are allowed if this `MediaType` has them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut mt = mime::MediaType::parse("text/plain; charset=utf-8; x=1").unwrap();
+    /// mt.retain_params(|name, _| name != "x");
+    ///
+    /// assert_eq!(mt.reparse().unwrap(), mt);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string form doesn't parse, which would be
+    /// a bug in this crate.
+    pub fn reparse(&self) -> Result<MediaType, InvalidMime> {
+        let mut parser = mime_parse::Parser::cannot_range();
+        if self.subtype().is_empty() {
+            parser = parser.allow_empty_subtype();
+        }
+        if self.params().any(|(name, _)| name.contains('*')) {
+            parser = parser.rfc2231_names();
+        }
+        parser
+            .parse(self.as_str())
+            .map(|mime| MediaType { mime })
+            .map_err(|e| InvalidMime { inner: e.into() })
+    }
+
+    /// Writes the canonical form of this `MediaType` to a writer.
+    ///
+    /// This is the same string as `Display` and `as_ref()`, written
+    /// without any formatting options or intermediate `String`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut header = String::from("Content-Type: ");
+    /// mime::TEXT_HTML_UTF_8.write_to(&mut header).unwrap();
+    ///
+    /// assert_eq!(header, "Content-Type: text/html; charset=utf-8");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer does.
+    #[inline]
+    pub fn write_to<W: fmt::Write>(&self, mut w: W) -> fmt::Result {
+        w.write_str(self.as_ref())
+    }
+
+    #[cfg(test)]
+    pub(super) fn test_assert_asterisks(&self) {
+        assert!(!self.as_ref().contains('*'), "{:?} contains an asterisk", self);
+    }
+
+    #[cfg(test)]
+    pub(super) fn test_assert_canonical(&self) {
+        let reparsed = self.reparse().unwrap_or_else(|e| panic!("{:?} doesn't reparse: {}", self, e));
+        assert_eq!(&reparsed, self);
+        assert_eq!(reparsed.as_str(), self.as_str(), "{:?} isn't canonical", self);
+    }
+}
+
+/// The default `MediaType` is `application/octet-stream`.
+///
+/// This is the conventional type for unknown binary content. It's backed
+/// by the same constant as [`APPLICATION_OCTET_STREAM`](crate::APPLICATION_OCTET_STREAM),
+/// so it doesn't allocate.
+///
+/// # Example
+///
+/// ```
+/// let mt = mime::MediaType::default();
+/// assert_eq!(mt, mime::APPLICATION_OCTET_STREAM);
+/// ```
+impl Default for MediaType {
+    #[inline]
+    fn default() -> MediaType {
+        crate::APPLICATION_OCTET_STREAM
+    }
+}
+
+impl<'a> IntoIterator for &'a MediaType {
+    type Item = (&'a str, Value<'a>);
+    type IntoIter = Params<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Params<'a> {
+        self.params()
+    }
+}
+
+impl PartialEq for MediaType {
+    fn eq(&self, other: &MediaType) -> bool {
+        crate::cmp::mime_eq(&self.mime, &other.mime)
+    }
+}
+
+impl PartialEq<str> for MediaType {
+    fn eq(&self, s: &str) -> bool {
+        crate::cmp::str_eq(&self.mime, s)
+    }
+}
+
+impl<'a> PartialEq<&'a str> for MediaType {
+    #[inline]
+    fn eq(&self, s: & &'a str) -> bool {
+        self == *s
+    }
+}
+
+impl PartialEq<MediaType> for &str {
+    #[inline]
+    fn eq(&self, mt: &MediaType) -> bool {
+        mt == self
+    }
+}
+
+impl PartialEq<MediaType> for str {
+    #[inline]
+    fn eq(&self, mt: &MediaType) -> bool {
+        mt == self
+    }
+}
+
+impl FromStr for MediaType {
+    type Err = InvalidMime;
+
+    fn from_str(s: &str) -> Result<MediaType, Self::Err> {
+        MediaType::parse(s)
+    }
+}
+
+impl AsRef<str> for MediaType {
+    fn as_ref(&self) -> &str {
+        self.mime.as_ref()
+    }
+}
+
+impl fmt::Debug for MediaType {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            debug_structured("MediaType", &self.mime, f)
+        } else {
+            fmt::Debug::fmt(&self.mime, f)
+        }
+    }
+}
+
+/// The `{:#?}` form, showing how the source was split up.
+pub(crate) fn debug_structured(name: &str, mime: &Mime, f: &mut fmt::Formatter) -> fmt::Result {
+    struct ParamList<'a>(&'a Mime);
+
+    impl fmt::Debug for ParamList<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_list().entries(self.0.params()).finish()
+        }
+    }
+
+    f.debug_struct(name)
+        .field("type", &mime.type_())
+        .field("subtype", &mime.subtype())
+        .field("suffix", &mime.suffix())
+        .field("params", &ParamList(mime))
+        .finish()
+}
+
+impl fmt::Display for MediaType {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.mime, f)
+    }
+}
+
+struct DisplayLowercase<'a>(&'a str);
+
+impl fmt::Display for DisplayLowercase<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use std::fmt::Write;
+
+        if crate::is_ascii_lowercase(self.0) {
+            return fmt::Display::fmt(self.0, f);
+        }
+
+        let s = match f.precision() {
+            Some(max) => self.0.char_indices().nth(max).map_or(self.0, |(i, _)| &self.0[..i]),
+            None => self.0,
+        };
+        let pad = f.width().map_or(0, |width| width.saturating_sub(s.chars().count()));
+        let (before, after) = match f.align() {
+            Some(fmt::Alignment::Right) => (pad, 0),
+            Some(fmt::Alignment::Center) => (pad / 2, pad - pad / 2),
+            _ => (0, pad),
+        };
+
+        let fill = f.fill();
+        for _ in 0..before {
+            f.write_char(fill)?;
+        }
+        // Write runs without uppercase letters as they are.
+        let mut rest = s;
+        while let Some(i) = rest.find(|c: char| c.is_ascii_uppercase()) {
+            f.write_str(&rest[..i])?;
+            f.write_char(rest.as_bytes()[i].to_ascii_lowercase().into())?;
+            rest = &rest[i + 1..];
+        }
+        f.write_str(rest)?;
+        for _ in 0..after {
+            f.write_char(fill)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::*;
+
+    #[test]
+    fn test_size_of() {
+        assert!(
+            std::mem::size_of::<MediaType>() < 100,
+            "just to be warned if the size grows suddenly"
+        );
+
+        assert_eq!(
+            std::mem::size_of::<MediaType>(),
+            std::mem::size_of::<Option<MediaType>>(),
+            "option size optimization"
+        );
+    }
+
+    #[test]
+    fn test_default() {
+        let mt = MediaType::default();
+        assert_eq!(mt, APPLICATION_OCTET_STREAM);
+        assert_eq!(mt.mime.private_atom(), APPLICATION_OCTET_STREAM.mime.private_atom());
+        assert_ne!(mt.mime.private_atom(), 0);
+    }
+
+    #[test]
+    fn test_type_() {
+        assert_eq!(TEXT_PLAIN.type_(), TEXT);
+    }
+
+
+    #[test]
+    fn test_same_type() {
+        assert!(IMAGE_PNG.same_type(&IMAGE_JPEG));
+        assert!(!IMAGE_PNG.same_type(&TEXT_PLAIN));
+        assert!(TEXT_PLAIN.same_type(&TEXT_PLAIN_UTF_8));
+
+        let dynamic = MediaType::parse("IMAGE/x-custom; foo=bar").unwrap();
+        assert!(dynamic.same_type(&IMAGE_PNG));
+        assert!(IMAGE_PNG.same_type(&dynamic));
+        assert!(!dynamic.same_type(&TEXT_CSS));
+    }
+
+    #[test]
+    fn test_retain_params() {
+        let mut mt = MediaType::parse("text/x-custom; X-A=1; b=2; x-c=\"3\"; d=4").unwrap();
+        mt.retain_params(|name, _| !name.starts_with("x-"));
+        assert_eq!(mt.to_string(), "text/x-custom; b=2; d=4");
+        assert_eq!(mt.param("b").unwrap(), "2");
+        assert_eq!(mt.param("d").unwrap(), "4");
+
+        mt.retain_params(|_, value| value == "2");
+        assert_eq!(mt.to_string(), "text/x-custom; b=2");
+
+        mt.retain_params(|_, _| false);
+        assert!(!mt.has_params());
+        assert_eq!(mt.to_string(), "text/x-custom");
+
+        let mut utf8 = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
+        utf8.retain_params(|name, _| name != "foo");
+        assert_eq!(utf8, TEXT_PLAIN_UTF_8);
+        assert_eq!(utf8.mime.private_atom(), TEXT_PLAIN_UTF_8.mime.private_atom());
+
+        let mut plain = TEXT_PLAIN_UTF_8;
+        plain.retain_params(|_, _| false);
+        assert_eq!(plain.mime.private_atom(), TEXT_PLAIN.mime.private_atom());
+
+        for mt in &[&mt, &utf8, &plain] {
+            mt.test_assert_canonical();
+        }
+    }
+
+    #[test]
+    fn test_as_media_range() {
+        let mt = MediaType::parse("application/vnd.api+json; ext=bulk").unwrap();
+        let range = mt.as_media_range();
+
+        assert_eq!(range, "application/vnd.api+json; ext=bulk");
+        assert!(range.matches(&mt));
+
+        let extra = MediaType::parse("application/vnd.api+json; ext=bulk; charset=utf-8").unwrap();
+        assert!(range.matches(&extra));
+
+        let other_ext = MediaType::parse("application/vnd.api+json; ext=other").unwrap();
+        assert!(!range.matches(&other_ext));
+        assert!(!range.matches(&mt.clone().without_params()));
+        assert!(!range.matches(&APPLICATION_JSON));
+    }
+
+    #[test]
+    fn test_is_rfc6838_name() {
+        assert!(TEXT_PLAIN.is_rfc6838_name());
+        assert!(APPLICATION_WWW_FORM_URLENCODED.is_rfc6838_name());
+        assert!(MediaType::parse("application/vnd.api+json; q=%").unwrap().is_rfc6838_name());
+        assert!(MediaType::parse("text/x-foo_bar").unwrap().is_rfc6838_name());
+
+        for s in &["text/foo%bar", "text/foo'", "text/`foo", "te|xt/plain", "text/~foo", "_text/plain", "text/.foo"] {
+            let mt = MediaType::parse(*s).expect(s);
+            assert!(!mt.is_rfc6838_name(), "{:?} should not be an RFC 6838 name", s);
+        }
+    }
+
+    #[test]
+    fn test_subtype() {
+        assert_eq!(TEXT_PLAIN.subtype(), PLAIN);
+        assert_eq!(TEXT_PLAIN_UTF_8.subtype(), PLAIN);
+        let mime = MediaType::parse("text/html+xml").unwrap();
+        assert_eq!(mime.subtype(), "html+xml");
+    }
+
+    #[test]
+    fn test_type_and_subtype_len() {
+        for mt in &[
+            TEXT_PLAIN,
+            TEXT_PLAIN_UTF_8,
+            IMAGE_SVG,
+            MediaType::parse("application/vnd.api+json ; ext=bulk").unwrap(),
+            MediaType::parse("text/x").unwrap(),
+        ] {
+            assert_eq!(mt.type_len(), mt.type_().len(), "{:?}", mt);
+            assert_eq!(mt.subtype_len(), mt.subtype().len(), "{:?}", mt);
+        }
+    }
+
+    #[test]
+    fn test_matching() {
+        match (TEXT_PLAIN.type_(), TEXT_PLAIN.subtype()) {
+            (TEXT, PLAIN) => (),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_suffix() {
+        assert_eq!(TEXT_PLAIN.suffix(), None);
+        let mime = MediaType::parse("text/html+xml").unwrap();
+        assert_eq!(mime.suffix(), Some(XML));
+    }
+
+    #[test]
+    fn test_mime_fmt() {
+        let mime = TEXT_PLAIN;
+        assert_eq!(mime.to_string(), "text/plain");
+        let mime = TEXT_PLAIN_UTF_8;
+        assert_eq!(mime.to_string(), "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn test_media_type_from_str() {
+        assert_eq!(MediaType::parse("text/plain").unwrap(), TEXT_PLAIN);
+        assert_eq!(MediaType::parse("TEXT/PLAIN").unwrap(), TEXT_PLAIN);
+        assert_eq!(MediaType::parse("text/plain; charset=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
+        assert_eq!(MediaType::parse("text/plain;charset=\"utf-8\"").unwrap(), TEXT_PLAIN_UTF_8);
+
+        // quotes + semi colon
+        MediaType::parse("text/plain;charset=\"utf-8\"; foo=bar").unwrap();
+        MediaType::parse("text/plain;charset=\"utf-8\" ; foo=bar").unwrap();
+
+        let upper = MediaType::parse("TEXT/PLAIN").unwrap();
+        assert_eq!(upper, TEXT_PLAIN);
+        assert_eq!(upper.type_(), TEXT);
+        assert_eq!(upper.subtype(), PLAIN);
+
+
+        assert_eq!(MediaType::parse(" text/plain\t").unwrap(), TEXT_PLAIN);
+
+        let extended = MediaType::parse("TEXT/PLAIN; CHARSET=UTF-8; FOO=BAR").unwrap();
+        assert_eq!(extended, "text/plain; charset=utf-8; foo=BAR");
+        assert_eq!(extended.param("charset").unwrap(), "utf-8");
+        assert_eq!(extended.param("foo").unwrap(), "BAR");
+
+        MediaType::parse("multipart/form-data; boundary=--------foobar").unwrap();
+
+        // empty quotes
+        MediaType::parse("audo/wave; codecs=\"\"").expect("param value with empty quotes");
+
+        // parse errors
+        MediaType::parse("f o o / bar").unwrap_err();
+        MediaType::parse("text\n/plain").unwrap_err();
+        MediaType::parse("text\r/plain").unwrap_err();
+        MediaType::parse("text/\r\nplain").unwrap_err();
+        MediaType::parse("text/plain;\r\ncharset=utf-8").unwrap_err();
+        MediaType::parse("text/plain; charset=\r\nutf-8").unwrap_err();
+        MediaType::parse("text/plain; charset=\"\r\nutf-8\"").unwrap_err();
+    }
+
+
+    #[test]
+    fn test_from_str_empty_parameter_list() {
+        static CASES: &[&str] = &[
+            "text/event-stream;",
+            "text/event-stream; ",
+            "text/event-stream;       ",
+        ];
+
+        for &case in CASES {
+            let mime = MediaType::parse(case).expect(case);
+            assert_eq!(mime, TEXT_EVENT_STREAM, "case = {:?}", case);
+            assert_eq!(mime.type_(), TEXT, "case = {:?}", case);
+            assert_eq!(mime.subtype(), EVENT_STREAM, "case = {:?}", case);
+            assert!(!mime.has_params(), "case = {:?}", case);
+        }
+    }
+
+    #[test]
+    fn test_parse_too_long() {
+        let mut source = vec![b'a'; u16::MAX as usize];
+        source[5] = b'/';
+
+        let mut s = String::from_utf8(source).unwrap();
+
+        MediaType::parse(&s).expect("parses AT max length");
+
+        s.push('a');
+        MediaType::parse(&s).expect_err("errors OVER max length");
+    }
+
+    #[test]
+    fn test_parse_long_quoted_pairs() {
+        let prefix = "application/x-custom; name=\"";
+        let pairs = (u16::MAX as usize - prefix.len() - 1) / 2;
+
+        let mut s = String::with_capacity(u16::MAX as usize);
+        s.push_str(prefix);
+        for _ in 0..pairs {
+            s.push_str("\\\\");
+        }
+        s.push('"');
+        assert_eq!(s.len(), u16::MAX as usize);
+
+        let mime = MediaType::parse(&s).expect("parses long quoted-pairs");
+        let value = mime.param("name").unwrap();
+        let content = value.to_content();
+        assert_eq!(content.len(), pairs);
+        assert!(content.bytes().all(|b| b == b'\\'));
+        assert_eq!(value, &*content);
+
+        // A 1 MB value can't be represented, since sources are indexed
+        // with `u16`s, so it is rejected up front, without scanning.
+        let mut huge = String::from(prefix);
+        huge.push_str(&"\\\"".repeat(512 * 1024));
+        huge.push('"');
+        MediaType::parse(&huge).expect_err("errors OVER max length");
+    }
+
+    #[test]
+    fn test_parse_rfc6838() {
+        assert_eq!(MediaType::parse_rfc6838("TEXT/PLAIN; charset=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
+        MediaType::parse_rfc6838("application/vnd.ms-excel").unwrap();
+
+        for s in &["text/foo%bar", "text/foo'", "text/`foo", "te|xt/plain", "text/~foo", "-text/plain", "text/*"] {
+            MediaType::parse_rfc6838(*s).expect_err(s);
+        }
+    }
+
+    #[test]
+    fn test_parse_params_only() {
+        let params = MediaType::parse_params_only("; A=1; b=\"two \\\"2\\\"\"").unwrap();
+        assert_eq!(params, [
+            ("a".to_owned(), "1".to_owned()),
+            ("b".to_owned(), "two \"2\"".to_owned()),
+        ]);
+
+        let err = MediaType::parse_params_only("a=1; b=\r\n2").unwrap_err();
+        assert!(err.to_string().contains("position 7"), "{}", err);
+    }
+
+    #[test]
+    fn test_case_sensitive_values() {
+        let mime = MediaType::parse("multipart/form-data; charset=BASE64; boundary=ABCDEFG").unwrap();
+        assert_eq!(mime.param(CHARSET).unwrap(), "bAsE64");
+        assert_eq!(mime.param(BOUNDARY).unwrap(), "ABCDEFG");
+        assert_eq!(mime.param(BOUNDARY).unwrap().as_str_repr(), "ABCDEFG");
+        assert_ne!(mime.param(BOUNDARY).unwrap(), "abcdefg");
+    }
+
+    #[test]
+    fn test_get_param() {
+        assert_eq!(TEXT_PLAIN.param("charset"), None);
+        assert_eq!(TEXT_PLAIN.param("baz"), None);
+
+        assert_eq!(TEXT_PLAIN_UTF_8.param("charset"), Some(UTF_8));
+        assert_eq!(TEXT_PLAIN_UTF_8.param("baz"), None);
+
+        let mime = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
+        assert_eq!(mime.param(CHARSET).unwrap(), "utf-8");
+        assert_eq!(mime.param("foo").unwrap(), "bar");
+        assert_eq!(mime.param("baz"), None);
+
+
+        let mime = MediaType::parse("text/plain;charset=\"utf-8\"").unwrap();
+        assert_eq!(mime.param(CHARSET), Some(UTF_8));
+    }
+
+    #[test]
+    fn test_get_param_name_case_insensitive() {
+        let mime = MediaType::parse(
+            r#"message/external-body; access-type=URL; URL="ftp://EXAMPLE.com/Doc""#
+        ).unwrap();
+        assert_eq!(mime, r#"message/external-body; access-type=URL; url="ftp://EXAMPLE.com/Doc""#);
+        assert_eq!(mime.param("url").unwrap(), "ftp://EXAMPLE.com/Doc");
+        assert_eq!(mime.param("URL").unwrap(), "ftp://EXAMPLE.com/Doc");
+        assert_eq!(mime.param("Access-Type").unwrap(), "URL");
+    }
+
+    #[test]
+    fn test_param_eq() {
+        let mime = MediaType::parse(r#"multipart/form-data; Charset="UTF-8"; boundary=abc; title="a \"b\"""#).unwrap();
+        assert!(mime.param_eq("charset", "utf-8"));
+        assert!(mime.param_eq("CHARSET", "UTF-8"));
+        assert!(mime.param_eq("boundary", "abc"));
+        assert!(!mime.param_eq("boundary", "ABC"));
+        assert!(mime.param_eq("title", "a \"b\""));
+        assert!(!mime.param_eq("title", r#""a \"b\"""#));
+        assert!(!mime.param_eq("missing", ""));
+    }
+
+    #[test]
+    fn test_mime_with_dquote_quoted_pair() {
+        let mime = MediaType::parse(r#"application/x-custom; title="the \" char""#).unwrap();
+        assert_eq!(mime.param("title").unwrap(), "the \" char");
+    }
+
+    #[test]
+    fn test_params() {
+        let mime = TEXT_PLAIN;
+        let mut params = mime.params();
+        assert_eq!(params.next(), None);
+
+        let mime = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
+        let mut params = mime.params();
+        assert_eq!(params.next(), Some((CHARSET, UTF_8)));
+
+        let (second_param_left, second_param_right) = params.next().unwrap();
+        assert_eq!(second_param_left, "foo");
+        assert_eq!(second_param_right, "bar");
+
+        assert_eq!(params.next(), None);
+    }
+
+    #[test]
+    fn test_parameters_map() {
+        assert!(TEXT_PLAIN.parameters_map().is_empty());
+
+        let map = TEXT_PLAIN_UTF_8.parameters_map();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["charset"], "utf-8");
+
+        let mime = MediaType::parse("text/x-custom; b=2; A=1; b=\"3\"; c=\"\"").unwrap();
+        let map = mime.parameters_map();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), [
+            ("a".to_owned(), "1".to_owned()),
+            ("b".to_owned(), "3".to_owned()),
+            ("c".to_owned(), "".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_split_params() {
+        let (essence, mut params) = TEXT_PLAIN.split_params();
+        assert_eq!(essence, TEXT_PLAIN);
+        assert!(params.next().is_none());
+
+        let mime = MediaType::parse("application/x-custom+json; a=1; b=\"2\"").unwrap();
+        let (essence, params) = mime.split_params();
+        assert_eq!(essence, "application/x-custom+json");
+        assert!(!essence.has_params());
+        assert_eq!(essence.suffix(), Some("json"));
+
+        let params = params.collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].0, "a");
+        assert_eq!(params[0].1, "1");
+        assert_eq!(params[1].0, "b");
+        assert_eq!(params[1].1, "2");
+    }
+
+    #[test]
+    fn test_matches_any() {
+        let ranges = [
+            MediaRange::parse("text/html; level=1").unwrap(),
+            MediaRange::parse("application/*; q=0.5").unwrap(),
+            MediaRange::parse("image/png; q=0").unwrap(),
+        ];
+
+        assert!(MediaType::parse("text/html; level=1; charset=utf-8").unwrap().matches_any(&ranges));
+        assert!(!TEXT_HTML.matches_any(&ranges));
+        assert!(APPLICATION_JSON.matches_any(&ranges));
+        assert!(IMAGE_PNG.matches_any(&ranges));
+        assert!(!IMAGE_JPEG.matches_any(&ranges));
+
+        assert!(!TEXT_PLAIN.matches_any(&[]));
+        assert!(TEXT_PLAIN.matches_any(&[STAR_STAR]));
+    }
+
+    #[test]
+    fn test_param_bytes() {
+        assert_eq!(TEXT_PLAIN_UTF_8.param_bytes("charset"), Some(&b"utf-8"[..]));
+        assert_eq!(TEXT_PLAIN.param_bytes("charset"), None);
+
+        let mt = MediaType::parse(r#"multipart/mixed; Boundary="x\yz"; a=B"#).unwrap();
+        assert_eq!(mt.param_bytes("boundary"), Some(&br#""x\yz""#[..]));
+        assert_eq!(mt.param_bytes("BOUNDARY"), Some(&br#""x\yz""#[..]));
+        assert_eq!(mt.param_bytes("a"), Some(&b"B"[..]));
+        assert_eq!(mt.param("boundary").unwrap().to_content(), "xyz");
+    }
+
+    #[test]
+    fn test_essence() {
+        let html = MediaType::parse("TEXT/HTML; Charset=UTF-8; level=1").unwrap();
+        let essence = html.essence();
+        assert_eq!(essence, TEXT_HTML);
+        assert_ne!(essence.mime.private_atom(), 0);
+        assert!(!essence.has_params());
+
+        assert_eq!(IMAGE_SVG.essence(), IMAGE_SVG);
+        assert_eq!(TEXT_PLAIN_UTF_8.essence(), TEXT_PLAIN);
+
+        let custom = MediaType::parse("application/x-thing+cbor; v=2").unwrap();
+        let essence = custom.essence();
+        assert_eq!(essence, "application/x-thing+cbor");
+        assert_eq!(essence.suffix(), Some("cbor"));
+        assert_eq!(essence.mime.private_atom(), 0);
+
+        html.essence().test_assert_canonical();
+        custom.essence().test_assert_canonical();
+    }
+
+    #[test]
+    fn test_parse_ascii_quoted() {
+        let src = "text/plain; name=\"a\u{80}\"";
+        assert_eq!(MediaType::parse(src).unwrap().param("name").unwrap(), "a\u{80}");
+
+        let err = MediaType::parse_ascii_quoted(src).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid MIME: non-ASCII byte in a quoted parameter value, '\\xc2' at position 19"
+        );
+
+        assert_eq!(MediaType::parse_ascii_quoted("text/plain; charset=\"UTF-8\"").unwrap(), TEXT_PLAIN_UTF_8);
+    }
+
+    #[test]
+    fn test_charset() {
+        assert!(matches!(TEXT_PLAIN_UTF_8.charset(), Some(Cow::Borrowed("utf-8"))));
+        assert_eq!(TEXT_PLAIN.charset(), None);
+
+        let ascii = MediaType::parse("text/html; charset=US-ASCII").unwrap();
+        assert!(matches!(ascii.charset(), Some(Cow::Borrowed("us-ascii"))));
+        assert_eq!(ascii, MediaType::parse("text/html; charset=\"us-ascii\"").unwrap());
+        assert_eq!(ascii.to_string(), "text/html; charset=us-ascii");
+
+        let unquoted = MediaType::parse("text/plain; charset=ISO-8859-1").unwrap();
+        assert!(matches!(unquoted.charset(), Some(Cow::Borrowed("iso-8859-1"))));
+
+        let quoted = MediaType::parse(r#"text/plain; format=flowed; charset="utf-16""#).unwrap();
+        assert!(matches!(quoted.charset(), Some(Cow::Borrowed("utf-16"))));
+
+        let escaped = MediaType::parse(r#"text/plain; charset="x-\"odd\"""#).unwrap();
+        match escaped.charset() {
+            Some(Cow::Owned(s)) => assert_eq!(s, r#"x-"odd""#),
+            other => panic!("expected an owned charset, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_and_collect() {
+        let (mt, params) = MediaType::parse_and_collect(
+            r#"Text/Plain; Charset=UTF-8; Title="a \"b\"""#
+        ).unwrap();
+        assert_eq!(mt, "text/plain; charset=utf-8; title=\"a \\\"b\\\"\"");
+        assert_eq!(params, [
+            ("charset".to_owned(), "utf-8".to_owned()),
+            ("title".to_owned(), "a \"b\"".to_owned()),
+        ]);
+
+        let (mt, params) = MediaType::parse_and_collect("image/png").unwrap();
+        assert_eq!(mt, IMAGE_PNG);
+        assert!(params.is_empty());
+
+        MediaType::parse_and_collect("text/plain; a").unwrap_err();
+    }
+
+    #[test]
+    fn test_canonical_eq() {
+        let mt = MediaType::parse("multipart/mixed; boundary=abc; charset=utf-8; x=\"1\"").unwrap();
+        assert!(mt.canonical_eq("multipart/mixed; boundary=abc; charset=utf-8; x=1"));
+        assert!(mt.canonical_eq("multipart/mixed; x=1; boundary=abc; charset=utf-8"));
+        assert!(mt.canonical_eq("MULTIPART/MIXED; CHARSET=\"UTF-8\"; X=1; BOUNDARY=abc"));
+        assert!(!mt.canonical_eq("multipart/mixed; x=1; boundary=ABC; charset=utf-8"));
+        assert!(!mt.canonical_eq("multipart/mixed; boundary=abc; charset=utf-8"));
+        assert!(!mt.canonical_eq("multipart/mixed; boundary=abc; charset=utf-8; x=1; y=2"));
+
+        assert!(TEXT_PLAIN_UTF_8.canonical_eq("text/plain;charset=utf-8"));
+        assert!(TEXT_PLAIN.canonical_eq("\ttext/plain"));
+        assert!(!TEXT_PLAIN.canonical_eq("text/*"));
+        assert!(!TEXT_PLAIN.canonical_eq(""));
+    }
+
+    #[test]
+    fn test_write_to() {
+        let mut out = String::new();
+        TEXT_PLAIN_UTF_8.write_to(&mut out).unwrap();
+        out.push_str(", ");
+        MediaType::parse("Application/Vnd.Api+JSON; A=\"B\"").unwrap().write_to(&mut out).unwrap();
+        assert_eq!(out, "text/plain; charset=utf-8, application/vnd.api+json; a=\"B\"");
+
+        // Formatting options don't apply.
+        let mut padded = String::new();
+        {
+            use std::fmt::Write;
+            write!(padded, "{:>12}|", IMAGE_PNG).unwrap();
+        }
+        let mut raw = String::new();
+        IMAGE_PNG.write_to(&mut raw).unwrap();
+        assert_eq!(padded, "   image/png|");
+        assert_eq!(raw, "image/png");
+    }
+
+    #[test]
+    fn test_is_registered() {
+        for mt in &[TEXT_PLAIN, TEXT_HTML_UTF_8, IMAGE_SVG, APPLICATION_OCTET_STREAM, FONT_WOFF2] {
+            assert!(mt.is_registered(), "{:?}", mt);
+        }
+        assert!(MediaType::parse("Application/EDIFACT; x=1").unwrap().is_registered());
+        assert!(MediaType::parse("application/vnd.api+json").unwrap().is_registered());
+        for src in &["application/cms", "application/xml-patch+xml", "image/avif"] {
+            assert!(MediaType::parse(*src).unwrap().is_registered(), "{:?}", src);
+        }
+
+        assert!(!MediaType::parse("application/x-unknown").unwrap().is_registered());
+        assert!(!MediaType::parse("text/plain+json").unwrap().is_registered());
+        assert!(!MediaType::parse("video/webm").unwrap().is_registered());
+    }
+
+    #[test]
+    fn test_deprecated_alias() {
+        assert_eq!(APPLICATION_JAVASCRIPT.deprecated_alias().unwrap().unwrap(), TEXT_JAVASCRIPT);
+        assert_eq!(APPLICATION_JAVASCRIPT_UTF_8.deprecated_alias().unwrap().unwrap(), "text/javascript; charset=utf-8");
+
+        let icon = MediaType::parse("Image/X-Icon").unwrap();
+        assert_eq!(icon.deprecated_alias().unwrap().unwrap(), "image/vnd.microsoft.icon");
+
+        let yaml = MediaType::parse("text/yaml; charset=utf-8; x=\"a b\"").unwrap();
+        let preferred = yaml.deprecated_alias().unwrap().unwrap();
+        assert_eq!(preferred.essence(), "application/yaml");
+        assert_eq!(preferred.param("x").unwrap(), "a b");
+        assert_eq!(preferred.charset().unwrap(), "utf-8");
+
+        assert!(TEXT_PLAIN.deprecated_alias().unwrap().is_none());
+        assert!(MediaType::parse("application/yaml").unwrap().deprecated_alias().unwrap().is_none());
+
+        preferred.test_assert_canonical();
+        icon.deprecated_alias().unwrap().unwrap().test_assert_canonical();
+
+        // The params are kept however the source was parsed.
+        let rfc2231 = MediaType::parse_rfc2231("application/javascript; title*=us-ascii''x").unwrap();
+        let preferred = rfc2231.deprecated_alias().unwrap().unwrap();
+        assert_eq!(preferred.as_str(), "text/javascript; title*=us-ascii''x");
+        preferred.test_assert_canonical();
+
+        let spaced = MediaType::parse_content_type_header("application/javascript; charset = utf-8").unwrap();
+        assert_eq!(spaced.deprecated_alias().unwrap().unwrap(), "text/javascript; charset=utf-8");
+
+        // The replacement is longer, so this no longer fits.
+        let long = format!("image/x-icon; a={}", "x".repeat(u16::MAX as usize - "image/x-icon; a=".len()));
+        let long = MediaType::parse(&long).unwrap();
+        long.deprecated_alias().unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_content_type_header() {
+        for src in &[
+            "text/plain; charset=utf-8",
+            "\ttext/plain; charset=utf-8 ",
+            "text/plain; charset=utf-8;",
+            "text/plain;; charset=utf-8",
+            "text/plain; charset =utf-8",
+            "text/plain; charset= \"utf-8\"",
+            "TEXT/PLAIN ; CHARSET  =  UTF-8 ;",
+        ] {
+            let mt = MediaType::parse_content_type_header(*src).expect(src);
+            assert_eq!(mt, TEXT_PLAIN_UTF_8, "{:?}", src);
+            mt.test_assert_canonical();
+        }
+
+        let mt = MediaType::parse_content_type_header("multipart/form-data; boundary = abc; x=").unwrap();
+        assert_eq!(mt.param("boundary").unwrap(), "abc");
+        assert_eq!(mt.param("x").unwrap(), "");
+        assert_eq!(mt.as_str(), "multipart/form-data; boundary=abc; x=");
+        mt.test_assert_canonical();
+
+        let mt = MediaType::parse_content_type_header("text/html; level = 1").unwrap();
+        assert_eq!(mt.to_string(), "text/html; level=1");
+        mt.test_assert_canonical();
+        assert_eq!(mt.replace_type("application").unwrap(), "application/html; level=1");
+        assert_eq!(mt.with_suffix(Some("xml")).unwrap(), "text/html+xml; level=1");
+
+        for src in &["text/plain; char set=utf-8", "text /plain", "text/plain; a=b c", "*/*"] {
+            MediaType::parse_content_type_header(*src).expect_err(src);
+        }
+    }
+
+    #[test]
+    fn test_parse_preserving() {
+        let src = "Multipart/Form-Data; Boundary=AbC; CHARSET=UTF-8; X=\"Y\"";
+        let mt = MediaType::parse_preserving(src).unwrap();
+        assert_eq!(mt.as_ref(), src);
+        assert_eq!(mt.type_(), "Multipart");
+        assert_eq!(mt.subtype(), "Form-Data");
+        assert_eq!(mt.param("boundary").unwrap(), "AbC");
+        assert_eq!(mt.param("charset").unwrap(), "utf-8");
+        assert_eq!(mt.param("X").unwrap(), "Y");
+        assert!(mt.param_eq("x", "Y"));
+        assert!(!mt.param_eq("x", "y"));
+        assert_eq!(mt.parameters_map().keys().collect::<Vec<_>>(), ["boundary", "charset", "x"]);
+
+        let lower = MediaType::parse(src).unwrap();
+        assert_ne!(lower.as_ref(), src);
+        assert_eq!(mt, lower);
+        assert_eq!(lower, mt);
+        assert!(mt.canonical_eq("multipart/form-data; x=Y; charset=utf-8; boundary=AbC"));
+        assert_ne!(mt, MediaType::parse("multipart/form-data; boundary=abc; charset=utf-8; x=Y").unwrap());
+
+        let plain = MediaType::parse_preserving(" TEXT/Plain; ").unwrap();
+        assert_eq!(plain.as_ref(), "TEXT/Plain");
+        assert_eq!(plain, TEXT_PLAIN);
+        assert_eq!(plain, "text/plain");
+        assert!(plain.same_type(&TEXT_HTML));
+        assert!(plain.is_registered());
+        assert!(TEXT_STAR.matches(&plain));
+        assert!(MediaRange::parse("text/plain").unwrap().matches(&plain));
+        assert_eq!(plain.essence(), TEXT_PLAIN);
+
+        let js = MediaType::parse_preserving("Application/JavaScript").unwrap();
+        assert_eq!(js.deprecated_alias().unwrap().unwrap(), TEXT_JAVASCRIPT);
+
+        // Already lowercase sources are still interned.
+        let utf8 = MediaType::parse_preserving("text/plain; charset=utf-8").unwrap();
+        assert_ne!(utf8.mime.private_atom(), 0);
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let inputs = ["text/plain; charset=utf-8", "", "image/svg+xml", "text/*", "font/woff"];
+
+        let results = MediaType::parse_many(&inputs);
+        assert_eq!(results.len(), inputs.len());
+        let ok = results.iter().map(Result::is_ok).collect::<Vec<_>>();
+        assert_eq!(ok, [true, false, true, false, true]);
+        assert_eq!(results[0].as_ref().unwrap(), &TEXT_PLAIN_UTF_8);
+        assert_eq!(results[4].as_ref().unwrap(), &FONT_WOFF);
+        assert!(MediaType::parse_many(&[]).is_empty());
+
+        match MediaType::parse_all_ok(&inputs) {
+            Err((1, _)) => (),
+            other => panic!("expected the first error at 1, got {:?}", other),
+        }
+        match MediaType::parse_all_ok(&inputs[2..]) {
+            Err((1, _)) => (),
+            other => panic!("expected the first error at 1, got {:?}", other),
+        }
+        assert_eq!(MediaType::parse_all_ok(&[inputs[0], inputs[2]]).unwrap(), [TEXT_PLAIN_UTF_8, IMAGE_SVG]);
+    }
+
+    #[test]
+    fn test_matches_glob() {
+        let svg = MediaType::parse("Image/SVG+XML; charset=utf-8").unwrap();
+
+        for pattern in &["*/*", "image/*", "IMAGE/*", "*/svg+xml", "image/svg+xml", "Image/Svg+Xml", " image/svg+xml ; q=1"] {
+            assert!(svg.matches_glob(pattern), "{:?} should match", pattern);
+        }
+        for pattern in &["", "*", "image", "image/", "/svg+xml", "text/*", "*/svg", "*/xml", "image/svg*", "im*/svg+xml"] {
+            assert!(!svg.matches_glob(pattern), "{:?} should not match", pattern);
+        }
+
+        assert!(APPLICATION_JSON.matches_glob("*/json"));
+        assert!(!MediaType::parse("application/ld+json").unwrap().matches_glob("*/json"));
+    }
+
+    #[test]
+    fn test_parse_strict_token_type() {
+        for &(src, pos) in &[
+            ("*/*", 0),
+            ("*/plain", 0),
+            ("te*t/plain", 2),
+            ("text/*", 5),
+            ("text/pl*n", 7),
+            ("text/plain; *=a", 12),
+            ("text/plain; a=\"b*\"", 16),
+        ] {
+            let err = MediaType::parse_strict_token_type(src).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid MIME: unexpected asterisk, '*' at position {}", pos), "{:?}", src);
+        }
+        assert_eq!(MediaType::parse_strict_token_type("text/plain; charset=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
+    }
+
+    #[test]
+    fn test_parameters_eq_ignore_order() {
+        let eq = |a: &str, b: &str| {
+            MediaType::parse(a).unwrap().parameters_eq_ignore_order(&MediaType::parse(b).unwrap())
+        };
+
+        assert!(eq("text/plain", "image/png"));
+        assert!(eq("text/plain; charset=utf-8", "text/html; charset=utf-8"));
+        assert!(eq("a/b; x=1; y=2; z=3", "c/d; z=3; x=1; y=2"));
+        assert!(eq("a/b; X=1", "a/b; x=1"));
+        assert!(!eq("a/b; x=1", "a/b; x=2"));
+        assert!(!eq("a/b; x=1", "a/b; x=1; y=2"));
+        assert!(!eq("a/b; x=1; y=2", "a/b; x=1"));
+        assert!(!eq("a/b", "a/b; x=1"));
+        assert!(!eq("a/b; x=1; x=2", "a/b; x=1; y=2"));
+
+        // Duplicates are compared both ways, not just by the first one.
+        assert!(eq("a/b; x=1; x=2", "a/b; x=1; x=2"));
+        assert!(eq("a/b; x=1; x=2", "a/b; x=2; X=1"));
+        assert!(!eq("a/b; x=1; x=2", "a/b; x=1; x=1"));
+        assert!(!eq("a/b; x=1; x=1", "a/b; x=1; x=2"));
+        assert!(!eq("a/b; x=1; x=1; x=2", "a/b; x=1; x=2; x=2"));
+
+        let dup = MediaType::parse("a/b; foo=x; foo=y").unwrap();
+        assert_eq!(dup, dup);
+        dup.test_assert_canonical();
+        let dup = MediaType::parse_rfc2231("a/b; foo*0=a; foo*0=x; foo*1=b").unwrap();
+        assert_eq!(dup, dup.reparse().unwrap());
+
+        assert!(TEXT_PLAIN_UTF_8.parameters_eq_ignore_order(&TEXT_PLAIN_UTF_8));
+        assert!(TEXT_PLAIN_UTF_8.parameters_eq_ignore_order(&TEXT_HTML_UTF_8));
+        assert!(!TEXT_PLAIN_UTF_8.parameters_eq_ignore_order(&TEXT_PLAIN));
+    }
+
+    #[test]
+    fn test_debug() {
+        let mt = MediaType::parse(r#"Image/SVG+XML; charset=utf-8; title="a b""#).unwrap();
+
+        assert_eq!(format!("{:?}", mt), r#""image/svg+xml; charset=utf-8; title=\"a b\"""#);
+        assert_eq!(format!("{:?}", TEXT_PLAIN_UTF_8), r#""text/plain; charset=utf-8""#);
+        assert_eq!(
+            format!("{:#?}", mt),
+            r#"MediaType {
+    type: "image",
+    subtype: "svg+xml",
+    suffix: Some(
+        "xml",
+    ),
+    params: [
+        (
+            "charset",
+            "utf-8",
+        ),
+        (
+            "title",
+            "\"a b\"",
+        ),
+    ],
+}"#
+        );
+        assert_eq!(
+            format!("{:#?}", TEXT_PLAIN),
+            r#"MediaType {
+    type: "text",
+    subtype: "plain",
+    suffix: None,
+    params: [],
+}"#
+        );
+    }
+
+    #[test]
+    fn test_as_str() {
+        for src in &["text/plain", "image/svg+xml; charset=utf-8", "a/b; c=\"d e\""] {
+            let mt = MediaType::parse(*src).unwrap();
+            assert_eq!(mt.as_str(), *src);
+            assert_eq!(mt.as_str(), mt.as_ref());
+            assert_eq!(mt.as_str(), mt.to_string());
+        }
+        assert_eq!(TEXT_PLAIN_UTF_8.as_str(), "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn test_validate_name() {
+        MediaType::validate_name("image", "svg+xml").unwrap();
+
+        for &(type_, subtype, message) in &[
+            ("", "plain", "the type was empty"),
+            ("text", "", "the subtype was empty"),
+            (".text", "plain", "invalid type, '.' at position 0"),
+            ("text", "pl\u{e4}in", "invalid subtype, '\\xc3' at position 2"),
+            ("text", "plain/html", "invalid subtype, '/' at position 5"),
+        ] {
+            let err = MediaType::validate_name(type_, subtype).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid MIME: {}", message), "{:?}", (type_, subtype));
+        }
+    }
+
+    #[test]
+    fn test_parameters_are_subset_of() {
+        let subset = |a: &str, b: &str| {
+            MediaType::parse(a).unwrap().parameters_are_subset_of(&MediaType::parse(b).unwrap())
+        };
+
+        assert!(subset("text/plain", "text/plain"));
+        assert!(subset("text/plain", "text/plain; a=1"));
+        assert!(!subset("text/plain; a=1", "text/plain"));
+        assert!(subset("a/b; x=1", "c/d; y=2; X=1"));
+        assert!(subset("a/b; x=1; y=2", "a/b; y=2; z=3; x=1"));
+        assert!(!subset("a/b; x=1; y=2", "a/b; x=1"));
+        assert!(!subset("a/b; x=1", "a/b; x=2"));
+        assert!(!subset("a/b; x=A", "a/b; x=a"));
+        assert!(subset("a/b; charset=UTF-8", "a/b; charset=utf-8; x=1"));
+        assert!(subset("a/b; x=\"1\"", "a/b; x=1"));
+
+        // The same check `MediaRange::matches` does, minus the essence.
+        let range = MediaRange::parse("text/html; charset=utf-8").unwrap();
+        let html = MediaType::parse("text/html; level=1; charset=utf-8").unwrap();
+        assert!(range.matches(&html));
+        assert!(MediaType::parse(range.as_str()).unwrap().parameters_are_subset_of(&html));
+        assert!(TEXT_HTML_UTF_8.parameters_are_subset_of(&html));
+        assert!(!html.parameters_are_subset_of(&TEXT_HTML_UTF_8));
+    }
+
+    #[test]
+    fn test_parse_data_url() {
+        let (mt, base64, data) = MediaType::parse_data_url("data:text/plain;charset=UTF-8;base64,SGk=").unwrap();
+        assert_eq!(mt, TEXT_PLAIN_UTF_8);
+        assert_eq!(mt.charset().unwrap(), "utf-8");
+        assert!(base64);
+        assert_eq!(data, "SGk=");
+
+        let (mt, base64, data) = MediaType::parse_data_url("data:;base64,").unwrap();
+        assert_eq!(mt.charset_or_default(Charset::Utf8), Charset::UsAscii);
+        assert_eq!(mt.essence(), TEXT_PLAIN);
+        assert!(base64);
+        assert_eq!(data, "");
+
+        let (mt, base64, _) = MediaType::parse_data_url("data:application/vnd.a+json;v=1,{}").unwrap();
+        assert_eq!(mt.param("v").unwrap(), "1");
+        assert!(!base64);
+
+        assert!(MediaType::parse_data_url("text/plain,abc").is_err());
+        assert!(MediaType::parse_data_url("data:text/plain").is_err());
+        assert!(MediaType::parse_data_url("data:text/*,abc").is_err());
+        let err = MediaType::parse_data_url("data:text/pl@in,abc").unwrap_err();
+        assert!(err.to_string().ends_with("position 12"), "{}", err);
+    }
+
+    #[test]
+    fn test_is_json_is_xml() {
+        for &(src, json, xml) in &[
+            ("application/json", true, false),
+            ("text/json", true, false),
+            ("application/vnd.api+json", true, false),
+            ("application/ld+json; profile=x", true, false),
+            ("application/xml", false, true),
+            ("text/xml; charset=utf-8", false, true),
+            ("image/svg+xml", false, true),
+            ("application/vnd.a+xml+json", true, false),
+            ("application/jsonp", false, false),
+            ("application/x-json-stream", false, false),
+            ("application/xml-dtd", false, false),
+            ("text/plain; format=json", false, false),
+        ] {
+            let mt = MediaType::parse(src).unwrap();
+            assert_eq!((mt.is_json(), mt.is_xml()), (json, xml), "{:?}", src);
+        }
+
+        let preserved = MediaType::parse_preserving("Application/Problem+JSON").unwrap();
+        assert!(preserved.is_json());
+        assert!(MediaType::parse_preserving("Text/XML").unwrap().is_xml());
+    }
+
+    #[test]
+    fn test_param_case_insensitive_value() {
+        let mt = MediaType::parse(r#"a/b; x=ABC; y=abc; z="A \"B\""; w="c d"; charset=UTF-8"#).unwrap();
+
+        assert!(matches!(mt.param_case_insensitive_value("x"), Some(Cow::Owned(ref v)) if v == "abc"));
+        assert!(matches!(mt.param_case_insensitive_value("Y"), Some(Cow::Borrowed("abc"))));
+        assert!(matches!(mt.param_case_insensitive_value("z"), Some(Cow::Owned(ref v)) if v == "a \"b\""));
+        assert!(matches!(mt.param_case_insensitive_value("w"), Some(Cow::Borrowed("c d"))));
+        assert!(matches!(mt.param_case_insensitive_value("charset"), Some(Cow::Borrowed("utf-8"))));
+        assert_eq!(mt.param_case_insensitive_value("v"), None);
+
+        // `param` itself is unchanged.
+        assert_eq!(mt.param("x").unwrap(), "ABC");
+
+        let preserved = MediaType::parse_preserving("a/b; Mode=Fast").unwrap();
+        assert_eq!(preserved.param_case_insensitive_value("mode").unwrap(), "fast");
+        assert_eq!(TEXT_PLAIN_UTF_8.param_case_insensitive_value("charset").unwrap(), "utf-8");
+    }
+
+    #[test]
+    fn test_parse_permissive_values() {
+        let mt = MediaType::parse_permissive_values("text/plain; name=a b").unwrap();
+        assert_eq!(mt.as_str(), r#"text/plain; name="a b""#);
+        assert_eq!(mt.param("name").unwrap(), "a b");
+
+        let mt = MediaType::parse_permissive_values("text/plain; name=caf\u{e9}").unwrap();
+        assert_eq!(mt.as_str(), "text/plain; name=\"caf\u{e9}\"");
+        assert_eq!(mt.param("name").unwrap(), "caf\u{e9}");
+        assert_eq!(MediaType::parse(mt.as_str()).unwrap(), mt);
+
+        let mt = MediaType::parse_permissive_values("text/html; charset=utf-8").unwrap();
+        assert_eq!(mt, TEXT_HTML_UTF_8);
+
+        assert!(MediaType::parse("text/plain; name=a b").is_err());
+        assert!(MediaType::parse("text/plain; name=caf\u{e9}").is_err());
+        assert!(MediaType::parse_permissive_values("text/pl ain; name=a b").is_err());
+
+        mt.test_assert_canonical();
+        MediaType::parse_permissive_values("a/b; x=1 2; y=3").unwrap().test_assert_canonical();
+        let spaced = MediaType::parse_permissive_values("a/b; x= 1; y=3").unwrap();
+        assert_eq!(spaced.as_str(), "a/b; x=1; y=3");
+        spaced.test_assert_canonical();
+    }
+
+    #[test]
+    fn test_essence_bytes() {
+        for src in &["text/plain", "text/plain; charset=utf-8", "a/b+c ; d=e", "a/b;d=\"e;f\""] {
+            let mt = MediaType::parse(*src).unwrap();
+            assert_eq!(mt.essence_bytes(), mt.essence().as_str().as_bytes(), "{:?}", src);
+        }
+        assert_eq!(TEXT_PLAIN_UTF_8.essence_bytes(), b"text/plain");
+        assert_eq!(MediaType::parse_preserving("Text/Plain; A=b").unwrap().essence_bytes(), b"Text/Plain");
+    }
+
+    #[test]
+    fn test_reparse() {
+        let mt = MediaType::parse(r#"Text/HTML; Charset=UTF-8; title="a \"b\"""#).unwrap();
+        mt.test_assert_canonical();
+
+        let preserved = MediaType::parse_preserving("Text/HTML; A=B").unwrap();
+        let reparsed = preserved.reparse().unwrap();
+        assert_eq!(reparsed, preserved);
+        assert_eq!(reparsed.as_str(), "text/html; a=B");
+    }
+
+    #[test]
+    fn test_from_static() {
+        let html = MediaType::from_static("text/html; charset=utf-8");
+        assert_eq!(html, TEXT_HTML_UTF_8);
+        assert_eq!(html.mime.private_atom(), TEXT_HTML_UTF_8.mime.private_atom());
+        assert_eq!(MediaType::from_static("a/b; c=d"), "a/b; c=d");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid media type literal \"text/pl@in\": invalid MIME: invalid token, '@' at position 7")]
+    fn test_from_static_invalid() {
+        MediaType::from_static("text/pl@in");
+    }
+
+    #[test]
+    fn test_parse_trim_params() {
+        let src = "text/plain; boundary=x; charset=UTF-8; format=flowed";
+
+        let utf8 = MediaType::parse_trim_params(src, &["charset"]).unwrap();
+        assert_eq!(utf8, TEXT_PLAIN_UTF_8);
+        assert_eq!(utf8.mime.private_atom(), TEXT_PLAIN_UTF_8.mime.private_atom());
+
+        let two = MediaType::parse_trim_params(src, &["FORMAT", "charset"]).unwrap();
+        assert_eq!(two.as_str(), "text/plain; charset=utf-8; format=flowed");
+        two.test_assert_canonical();
+
+        let none = MediaType::parse_trim_params(src, &[]).unwrap();
+        assert_eq!(none.mime.private_atom(), TEXT_PLAIN.mime.private_atom());
+
+        let all = MediaType::parse_trim_params(src, &["boundary", "charset", "format"]).unwrap();
+        assert_eq!(all, MediaType::parse(src).unwrap());
+
+        assert!(MediaType::parse_trim_params("text/plain; bad@=1", &["charset"]).is_err());
+
+        let first = MediaType::parse_trim_params("text/plain; charset=utf-8; boundary=x", &["charset"]).unwrap();
+        assert_eq!(first.as_str(), "text/plain; charset=utf-8");
+        assert_eq!(first.mime.private_atom(), TEXT_PLAIN_UTF_8.mime.private_atom());
+
+        // Not an atom, but the charset still skips the param indices.
+        let form = MediaType::parse_trim_params("multipart/form-data; boundary=x; charset=utf-8", &["charset"]).unwrap();
+        assert_eq!(form.as_str(), "multipart/form-data; charset=utf-8");
+        assert!(matches!(
+            form.mime.private_params_source(),
+            mime_parse::ParamSource::KnownCharset(_, mime_parse::KnownCharset::Utf8),
+        ));
+        form.test_assert_canonical();
+
+        // The separators are kept as they were, so this can't get longer.
+        let dense = MediaType::parse_trim_params("a/b;x=1; y=2;;z=3", &["y", "z"]).unwrap();
+        assert_eq!(dense.as_str(), "a/b; y=2;z=3");
+        dense.test_assert_canonical();
+
+        let mut s = String::from("a/b;y=1");
+        let value = "v".repeat(600);
+        while s.len() + ";x=".len() + value.len() <= u16::MAX as usize {
+            s.push_str(";x=");
+            s.push_str(&value);
+        }
+        let kept = MediaType::parse_trim_params(&s, &["x"]).unwrap();
+        assert_eq!(kept.as_str(), s.replacen(";y=1", "", 1));
+    }
+
+    #[test]
+    fn test_common_prefix_len() {
+        let len = |a: &str, b: &str| MediaType::parse(a).unwrap().common_prefix_len(&MediaType::parse(b).unwrap());
+
+        assert_eq!(TEXT_PLAIN.common_prefix_len(&TEXT_PLAIN), "text/plain".len());
+        assert_eq!(TEXT_PLAIN.common_prefix_len(&TEXT_HTML), "text/".len());
+        assert_eq!(TEXT_PLAIN.common_prefix_len(&IMAGE_PNG), 0);
+        assert_eq!(len("text/plain", "text/plain+xml"), 10);
+        assert_eq!(len("text/plain; a=1", "text/plain; a=2"), 10);
+        assert_eq!(len("text/plain; a=1", "text/plainer"), 10);
+        assert_eq!(len("text/plain", "text/plaintext"), 10);
+        assert_eq!(len("image/svg+xml", "image/svg+zip"), 10);
+
+        let preserved = MediaType::parse_preserving("Text/PLAIN").unwrap();
+        assert_eq!(preserved.common_prefix_len(&TEXT_PLAIN), 10);
+    }
+
+    #[test]
+    fn test_parse_returning_remainder() {
+        let (mt, rest) = MediaType::parse_returning_remainder("text/html, text/plain", b',').unwrap();
+        assert_eq!(mt, TEXT_HTML);
+        assert_eq!(rest, ", text/plain");
+
+        let (mt, rest) = MediaType::parse_returning_remainder("text/html; charset=utf-8; level=1\nnext", b'\n').unwrap();
+        assert_eq!(mt, "text/html; charset=utf-8; level=1");
+        assert_eq!(rest, "\nnext");
+
+        let (mt, rest) = MediaType::parse_returning_remainder("application/json", b',').unwrap();
+        assert_eq!(mt, APPLICATION_JSON);
+        assert_eq!(rest, "");
+
+        assert!(MediaType::parse_returning_remainder("text/*, next", b',').is_err());
+        assert!(MediaType::parse_returning_remainder(", text/plain", b',').is_err());
+    }
+
+    #[test]
+    fn test_parse_with_param_limits() {
+        let boundary = "x".repeat(70);
+        let under = format!("multipart/mixed; boundary={}", boundary);
+        let over = format!("multipart/mixed; boundary=\"{}\"", boundary);
+
+        let mt = MediaType::parse_with_param_limits(&under, 8, 70).unwrap();
+        assert_eq!(mt.param("boundary").unwrap(), &*boundary);
+        assert!(MediaType::parse_with_param_limits(&under, 8, 69).is_err());
+        assert!(MediaType::parse_with_param_limits(&over, 8, 71).is_err());
+        assert!(MediaType::parse_with_param_limits(&over, 8, 72).is_ok());
+        assert!(MediaType::parse_with_param_limits(&under, 7, 70).is_err());
+        assert_eq!(MediaType::parse_with_param_limits("text/plain", 0, 0).unwrap(), TEXT_PLAIN);
+    }
+
+    #[test]
+    fn test_strip_boundary() {
+        let mt = MediaType::parse("multipart/mixed; charset=utf-8; BOUNDARY=\"a b\"; x=1").unwrap();
+        assert_eq!(mt.strip_boundary(), "multipart/mixed; charset=utf-8; x=1");
+
+        let mt = MediaType::parse_preserving("Multipart/Form-Data; Boundary=AbC").unwrap();
+        assert_eq!(mt.strip_boundary(), "multipart/form-data");
+
+        assert_eq!(TEXT_PLAIN_UTF_8.strip_boundary(), TEXT_PLAIN_UTF_8);
+    }
+
+    #[test]
+    fn test_params_fingerprint() {
+        let fingerprint = |s: &str| MediaType::parse(s).unwrap().params_fingerprint();
+
+        // FNV-1a of "charset=utf-8", so it stays the same across runs.
+        assert_eq!(TEXT_PLAIN_UTF_8.params_fingerprint(), 0xb092_1246_cc0e_a45a);
+        assert_eq!(fingerprint("text/plain; charset=\"UTF-8\""), TEXT_PLAIN_UTF_8.params_fingerprint());
+        assert_eq!(fingerprint("a/b; x=1; y=2; z=3"), fingerprint("c/d; z=3; x=1; y=2"));
+        assert_eq!(
+            MediaType::parse_preserving("a/b; X=1; Charset=Utf-8").unwrap().params_fingerprint(),
+            fingerprint("a/b; charset=utf-8; x=1")
+        );
+
+        assert_ne!(fingerprint("a/b; x=A"), fingerprint("a/b; x=a"));
+        assert_ne!(fingerprint("a/b; x=1; y=2"), fingerprint("a/b; x=2; y=1"));
+        assert_ne!(fingerprint("a/b; xy=z"), fingerprint("a/b; x=yz"));
+        assert_ne!(fingerprint("a/b; x=1"), fingerprint("a/b; x=1; x=1"));
+    }
+
+    #[test]
+    fn test_split_type_subtype_suffix() {
+        let split = |s: &str| {
+            let mt = MediaType::parse(s).unwrap();
+            let (type_, subtype, suffix) = mt.split_type_subtype_suffix();
+            (type_.to_owned(), subtype.to_owned(), suffix.map(str::to_owned))
+        };
+        let owned = |type_: &str, subtype: &str, suffix: Option<&str>| {
+            (type_.to_owned(), subtype.to_owned(), suffix.map(str::to_owned))
+        };
+
+        assert_eq!(split("image/svg+xml"), owned("image", "svg", Some("xml")));
+        assert_eq!(
+            split("application/vnd.a+b+json; x=\"a+b\""),
+            owned("application", "vnd.a+b", Some("json"))
+        );
+        assert_eq!(split("text/plain; charset=utf-8"), owned("text", "plain", None));
+        assert_eq!(split("application/x+"), owned("application", "x", Some("")));
+
+        let mt = MediaType::parse_preserving("Application/Vnd.API+JSON").unwrap();
+        assert_eq!(mt.split_type_subtype_suffix(), ("Application", "Vnd.API", Some("JSON")));
+        assert_eq!(IMAGE_SVG.split_type_subtype_suffix(), ("image", "svg", Some("xml")));
+    }
+
+    #[test]
+    fn test_parse_cow() {
+        let owned = String::from("Text/X-Thing; A=1");
+        let ptr = owned.as_ptr();
+        let mt = MediaType::parse_cow(Cow::Owned(owned)).unwrap();
+        assert_eq!(mt.as_str(), "text/x-thing; a=1");
+        assert_eq!(mt.as_str().as_ptr(), ptr);
+        mt.test_assert_canonical();
+
+        let mt = MediaType::parse_cow(Cow::Owned("TEXT/PLAIN; CHARSET=UTF-8".into())).unwrap();
+        assert_eq!(mt, TEXT_PLAIN_UTF_8);
+        assert_eq!(mt.as_str(), TEXT_PLAIN_UTF_8.as_str());
+
+        let mt = MediaType::parse_cow(Cow::Borrowed(" a/b ")).unwrap();
+        assert_eq!(mt.as_str(), "a/b");
+
+        let err = MediaType::parse_cow(Cow::Owned("a/b c".into())).unwrap_err();
+        assert_eq!(err.to_string(), MediaType::parse("a/b c").unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_require_param() {
+        let mt = MediaType::parse("multipart/mixed; Boundary=\"a b\"; charset=UTF-8").unwrap();
+        assert_eq!(mt.require_param("boundary").unwrap(), "a b");
+        assert_eq!(mt.require_param("BOUNDARY").unwrap(), "a b");
+        assert_eq!(mt.require_param(CHARSET).unwrap(), UTF_8);
+
+        let err = mt.require_param("Start").unwrap_err();
+        assert_eq!(err.name(), "Start");
+        assert_eq!(err.to_string(), "missing \"Start\" parameter");
+        assert_eq!(TEXT_PLAIN.require_param(CHARSET).unwrap_err().name(), "charset");
+    }
+
+    #[test]
+    fn test_iter_with_positions() {
+        let srcs = &[
+            "text/plain; charset=utf-8",
+            "text/x-thing;charset=utf-8",
+            "text/html; charset=ISO-8859-1",
+            "a/b; x=\"caf\u{e9}\"; Y=1; charset=\"UTF-8\"",
+            "a/b; one=1; two=2; three=\"\\\"3\\\"\"",
+            "a/b",
+        ];
+        for src in srcs {
+            let mt = MediaType::parse(*src).unwrap();
+            let positions = mt.iter_with_positions().collect::<Vec<_>>();
+            assert_eq!(positions.len(), mt.params().count(), "{:?}", src);
+
+            for ((name, value, name_range, value_range), (p_name, p_value)) in positions.into_iter().zip(mt.params()) {
+                assert_eq!(name, p_name, "{:?}", src);
+                assert_eq!(value, p_value, "{:?}", src);
+                assert_eq!(value.as_str_repr(), p_value.as_str_repr(), "{:?}", src);
+                assert_eq!(&mt.as_str()[name_range], name, "{:?}", src);
+                assert_eq!(&mt.as_str()[value_range], value.as_str_repr(), "{:?}", src);
+            }
+        }
+
+        let mt = MediaType::parse("text/plain; charset=utf-8").unwrap();
+        let (_, value, _, value_range) = mt.iter_with_positions().next().unwrap();
+        assert_eq!(value, "UTF-8");
+        assert_eq!(value_range, 20..25);
+    }
+
+    #[test]
+    fn test_try_intern() {
+        let is_atom = |mt: &MediaType| mt.mime.private_atom() != 0;
+
+        // Edits that collapse to a known media type already use its atom.
+        let mut mt = MediaType::parse("text/plain; charset=utf-8; format=flowed").unwrap();
+        assert!(!is_atom(&mt));
+        mt.retain_params(|name, _| name == CHARSET);
+        assert!(is_atom(&mt));
+        assert_eq!(mt.as_str(), "text/plain; charset=utf-8");
+        let mt = mt.without_params();
+        assert!(is_atom(&mt));
+        assert!(is_atom(&MediaType::parse("image/svg+xml; x=1").unwrap().strip_boundary().without_params()));
+
+        for src in &["text/plain; charset=\"UTF-8\"", "text/html; charset=\"iso-8859-1\""] {
+            let mut mt = MediaType::parse(*src).unwrap();
+            let before = mt.clone();
+            assert!(!is_atom(&mt), "{:?}", src);
+            assert!(mt.try_intern(), "{:?}", src);
+            assert!(is_atom(&mt), "{:?}", src);
+            assert_eq!(mt, before);
+            mt.test_assert_canonical();
+        }
+
+        let mut mt = TEXT_PLAIN;
+        assert!(mt.try_intern());
+
+        for src in &["text/x-thing", "text/plain; charset=\"UTF\\-8\"", "text/plain; a=b", "text/plain; charset=koi8-r"] {
+            let mut mt = MediaType::parse(*src).unwrap();
+            let before = mt.as_str().to_owned();
+            assert!(!mt.try_intern(), "{:?}", src);
+            assert_eq!(mt.as_str(), before);
+        }
+
+        let mut mt = MediaType::parse_preserving("Text/Plain").unwrap();
+        assert!(!mt.try_intern());
+        assert_eq!(mt.as_str(), "Text/Plain");
+    }
+
+    #[test]
+    fn test_parse_first_of() {
+        let mt = MediaType::parse_first_of(&["text/plain; charset", "te xt/html", "Application/JSON", "text/csv"]);
+        assert_eq!(mt, Some(APPLICATION_JSON));
+
+        assert_eq!(MediaType::parse_first_of(&["text/csv"]), Some(TEXT_CSV));
+        assert_eq!(MediaType::parse_first_of(&["a", "b/c d"]), None);
+        assert_eq!(MediaType::parse_first_of(&[]), None);
     }
-}
 
-impl PartialEq for MediaType {
-    fn eq(&self, other: &MediaType) -> bool {
-        crate::cmp::mime_eq(&self.mime, &other.mime)
+    #[test]
+    fn test_compare_ignoring_charset() {
+        let mt = |s: &str| MediaType::parse(s).unwrap();
+
+        assert!(mt("text/html").compare_ignoring_charset(&mt("text/html; charset=utf-8")));
+        assert!(mt("text/html; charset=latin1").compare_ignoring_charset(&mt("Text/HTML; Charset=UTF-8")));
+        assert!(mt("multipart/mixed; boundary=x; charset=utf-8").compare_ignoring_charset(&mt("multipart/mixed; boundary=x")));
+        assert!(mt("a/b; x=1; y=2").compare_ignoring_charset(&mt("a/b; charset=ascii; y=2; x=1")));
+
+        assert!(!mt("multipart/mixed; boundary=x").compare_ignoring_charset(&mt("multipart/mixed; boundary=y")));
+        assert!(!mt("multipart/mixed; boundary=x").compare_ignoring_charset(&mt("multipart/mixed")));
+        assert!(!mt("multipart/mixed").compare_ignoring_charset(&mt("multipart/mixed; boundary=x")));
+        assert!(!mt("text/html; charset=utf-8").compare_ignoring_charset(&mt("text/plain; charset=utf-8")));
     }
-}
 
-impl PartialEq<str> for MediaType {
-    fn eq(&self, s: &str) -> bool {
-        crate::cmp::str_eq(&self.mime, s)
+    #[test]
+    fn test_parse_bytes_lossy() {
+        let mt = MediaType::parse_bytes_lossy(b"text/plain; charset=utf-8").unwrap();
+        assert_eq!(mt, TEXT_PLAIN_UTF_8);
+
+        let mt = MediaType::parse_bytes_lossy(b"Text/Plain; a=\"\xFF\xFE\"; b=\"ok\"").unwrap();
+        assert_eq!(mt.as_str(), "text/plain; a=\"\u{FFFD}\u{FFFD}\"; b=\"ok\"");
+        assert_eq!(mt.param("b").unwrap(), "ok");
+        mt.test_assert_canonical();
+
+        // Valid non-ASCII UTF-8 is kept as is.
+        let mt = MediaType::parse_bytes_lossy("a/b; x=\"\u{e9}\"".as_bytes()).unwrap();
+        assert_eq!(mt.param("x").unwrap(), "\u{e9}");
+
+        for src in &[&b"te\xFFt/plain"[..], b"text/plain; a=\xFF", b"text/plain; \xFF=a", b"\xFF"] {
+            MediaType::parse_bytes_lossy(src).unwrap_err();
+        }
     }
-}
 
-impl<'a> PartialEq<&'a str> for MediaType {
-    #[inline]
-    fn eq(&self, s: & &'a str) -> bool {
-        self == *s
+    #[test]
+    fn test_nth_param() {
+        let srcs = &[
+            "text/plain",
+            "text/plain; charset=utf-8",
+            "text/x-thing; charset=UTF-8",
+            "text/html; charset=us-ascii",
+            "a/b; x=1",
+            "a/b; x=1; Y=\"2\"",
+            "a/b; x=1; y=2; z=3; charset=UTF-8",
+        ];
+        for src in srcs {
+            let mt = MediaType::parse(*src).unwrap();
+            let params = mt.params().collect::<Vec<_>>();
+            for i in 0..=params.len() {
+                let nth = mt.nth_param(i);
+                assert_eq!(nth, params.get(i).copied(), "{:?}[{}]", src, i);
+                if let Some((_, value)) = nth {
+                    assert_eq!(value.as_str_repr(), params[i].1.as_str_repr());
+                }
+            }
+        }
+
+        assert_eq!(TEXT_PLAIN_UTF_8.nth_param(0), Some((CHARSET, UTF_8)));
+        assert_eq!(TEXT_PLAIN_UTF_8.nth_param(1), None);
+        assert_eq!(TEXT_PLAIN.nth_param(0), None);
     }
-}
 
-impl<'a> PartialEq<MediaType> for &'a str {
-    #[inline]
-    fn eq(&self, mt: &MediaType) -> bool {
-        mt == self
+    #[test]
+    fn test_empty_subtype() {
+        for src in &["text/", "text/;a=b", " image/ ", "text/ ; charset=utf-8"] {
+            let err = MediaType::parse(*src).unwrap_err();
+            assert!(err.to_string().starts_with("invalid MIME: the subtype was empty"), "{:?}", src);
+            assert!(MediaRange::parse(*src).is_err(), "{:?}", src);
+
+            let mt = MediaType::parse_allowing_empty_subtype(*src).unwrap();
+            assert_eq!(mt.subtype(), "", "{:?}", src);
+            assert!(mt.suffix().is_none());
+            mt.test_assert_canonical();
+        }
+
+        let mt = MediaType::parse_allowing_empty_subtype("TEXT/; A=\"b\"").unwrap();
+        assert_eq!(mt.as_str(), "text/; a=\"b\"");
+        assert_eq!(MediaType::parse_allowing_empty_subtype("text/plain").unwrap(), TEXT_PLAIN);
+        assert!(MediaType::parse_allowing_empty_subtype("text").is_err());
+        assert!(MediaType::parse_allowing_empty_subtype("/plain").is_err());
     }
-}
 
-impl PartialEq<MediaType> for str {
-    #[inline]
-    fn eq(&self, mt: &MediaType) -> bool {
-        mt == self
+    #[test]
+    fn test_type_and_essence_hash() {
+        // The FNV-1a test vector for "a", so these stay the same across runs.
+        assert_eq!(MediaType::parse("a/b").unwrap().type_hash(), 0xaf63_dc4c_8601_ec8c);
+
+        let preserved = MediaType::parse_preserving("Image/SVG+xml; Charset=X").unwrap();
+        assert_eq!(preserved.type_hash(), IMAGE_PNG.type_hash());
+        assert_eq!(preserved.essence_hash(), IMAGE_SVG.essence_hash());
+        assert_ne!(IMAGE_SVG.essence_hash(), IMAGE_PNG.essence_hash());
+        assert_ne!(IMAGE_SVG.essence_hash(), MediaType::parse("image/svg").unwrap().essence_hash());
+        assert_ne!(TEXT_PLAIN.type_hash(), IMAGE_PNG.type_hash());
+        assert_ne!(TEXT_PLAIN.type_hash(), TEXT_PLAIN.essence_hash());
     }
-}
 
-impl FromStr for MediaType {
-    type Err = InvalidMime;
+    #[test]
+    fn test_parse_and_validate_charset() {
+        let allowed = ["UTF-8", "iso-8859-1"];
+        let check = |s: &str| MediaType::parse_and_validate_charset(s, &allowed);
 
-    fn from_str(s: &str) -> Result<MediaType, Self::Err> {
-        MediaType::parse(s)
+        assert_eq!(check("text/plain; charset=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
+        assert_eq!(check("text/html; charset=\"ISO-8859-1\"; level=1").unwrap().param(CHARSET).unwrap(), "iso-8859-1");
+        assert_eq!(check("text/plain").unwrap(), TEXT_PLAIN);
+        assert_eq!(check("image/png; x=utf-16").unwrap(), "image/png; x=utf-16");
+
+        let positions = [
+            ("text/plain; charset=utf-16", 20),
+            ("  text/plain;charset=\"us-ascii\"", 21),
+            ("text/plain; charset=utf-8; Charset=utf-16", 35),
+            ("text/plain; charset=", 20),
+        ];
+        for &(src, pos) in &positions {
+            let err = check(src).unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid MIME: unsupported charset at position {}", pos), "{:?}", src);
+        }
+
+        // Parse errors come first.
+        let err = check("text/plain; charset=utf-16; x").unwrap_err();
+        assert!(err.to_string().contains("equals sign"), "{}", err);
+        assert!(MediaType::parse_and_validate_charset("text/plain; charset=utf-8", &[]).is_err());
     }
-}
 
-impl AsRef<str> for MediaType {
-    fn as_ref(&self) -> &str {
-        self.mime.as_ref()
+    #[test]
+    fn test_parameter_order_matches() {
+        let mt = |s: &str| MediaType::parse(s).unwrap();
+
+        let a = mt("multipart/mixed; boundary=x; charset=utf-8; a=1");
+        let b = mt("multipart/mixed; a=1; boundary=x; charset=utf-8");
+        assert_eq!(a, b);
+        assert!(!a.parameter_order_matches(&b));
+        assert!(!b.parameter_order_matches(&a));
+
+        assert!(a.parameter_order_matches(&mt("text/plain; boundary=y; Charset=latin1; A=2")));
+        assert!(!a.parameter_order_matches(&mt("multipart/mixed; boundary=x; charset=utf-8")));
+        assert!(!mt("a/b; x=1").parameter_order_matches(&mt("a/b; x=1; y=2")));
+        assert!(TEXT_PLAIN.parameter_order_matches(&IMAGE_PNG));
+        assert!(TEXT_PLAIN_UTF_8.parameter_order_matches(&mt("text/x; charset=koi8-r")));
     }
-}
 
-impl fmt::Debug for MediaType {
-    #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(&self.mime, f)
+    #[test]
+    fn test_parse_with_default_charset() {
+        let parse = MediaType::parse_with_default_charset;
+        let is_atom = |mt: &MediaType| mt.mime.private_atom() != 0;
+
+        // Injected.
+        let mt = parse("text/plain", "utf-8").unwrap();
+        assert_eq!(mt, TEXT_PLAIN_UTF_8);
+        assert!(is_atom(&mt));
+        assert!(is_atom(&parse(" Text/HTML ", "UTF-8").unwrap()));
+        assert_eq!(parse("text/plain", "us-ascii").unwrap(), "text/plain; charset=us-ascii");
+        assert!(is_atom(&parse("text/plain", "us-ascii").unwrap()));
+
+        let mt = parse("text/x-thing; format=flowed", "Windows-1252").unwrap();
+        assert_eq!(mt.as_str(), "text/x-thing; format=flowed; charset=windows-1252");
+        mt.test_assert_canonical();
+        assert_eq!(parse("text/a", "x y").unwrap().as_str(), "text/a; charset=\"x y\"");
+        assert_eq!(parse("text/a", "").unwrap().as_str(), "text/a; charset=\"\"");
+
+        // Not injected.
+        let mt = parse("text/plain; Charset=\"Latin1\"; a=b", "utf-8").unwrap();
+        assert_eq!(mt.as_str(), "text/plain; charset=\"latin1\"; a=b");
+        assert_eq!(parse("text/html; charset=utf-8", "latin1").unwrap(), TEXT_HTML_UTF_8);
+
+        assert!(parse("text", "utf-8").is_err());
+        assert!(parse("text/plain", "a\nb").is_err());
     }
-}
 
-impl fmt::Display for MediaType {
-    #[inline]
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Display::fmt(&self.mime, f)
+    #[test]
+    fn test_without_charset() {
+        let is_atom = |mt: &MediaType| mt.mime.private_atom() != 0;
+
+        let mt = TEXT_PLAIN_UTF_8.without_charset();
+        assert_eq!(mt.as_str(), "text/plain");
+        assert!(is_atom(&mt));
+        assert!(!mt.has_params());
+
+        let mt = MediaType::parse("text/x-thing; charset=utf-8").unwrap().without_charset();
+        assert_eq!(mt.as_str(), "text/x-thing");
+
+        let mt = MediaType::parse("text/html; a=1; CHARSET=\"latin1\"; b=2; c=3").unwrap();
+        let stripped = mt.without_charset();
+        assert_eq!(stripped.as_str(), "text/html; a=1; b=2; c=3");
+        stripped.test_assert_canonical();
+
+        assert_eq!(IMAGE_PNG.without_charset(), IMAGE_PNG);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::*;
+    #[test]
+    fn test_parse_collecting_errors() {
+        let errors = MediaType::parse_collecting_errors("text/plain; a; b=\"c").unwrap_err();
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0], MediaType::parse("text/plain; a; b=\"c").unwrap_err().to_string());
+        assert_eq!(errors[1], MediaType::parse("text/plain; b=\"c").unwrap_err().to_string());
+
+        // An invalid essence, and two invalid parameters.
+        let errors = MediaType::parse_collecting_errors("te@xt/plain; a=b@; ; c=\"d;e\"; f").unwrap_err();
+        let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].contains("position 2"), "{:?}", errors);
+        assert!(errors[1].contains("position 16"), "{:?}", errors);
+
+        let mt = MediaType::parse_collecting_errors("text/plain; charset=utf-8; a=\"b;c\";").unwrap();
+        assert_eq!(mt, MediaType::parse("text/plain; charset=utf-8; a=\"b;c\"").unwrap());
+
+        // Errors that only the whole source has are still reported.
+        let long = format!("text/plain; a={}", "b".repeat(u16::MAX as usize));
+        assert_eq!(MediaType::parse_collecting_errors(&*long).unwrap_err().len(), 1);
+    }
 
     #[test]
-    fn test_size_of() {
-        assert!(
-            std::mem::size_of::<MediaType>() < 100,
-            "just to be warned if the size grows suddenly"
-        );
+    fn test_parse_preferring_atoms() {
+        static ATOMS: &[&str] = &["application/vnd.acme+json", "text/x-acme; charset=utf-8"];
 
-        assert_eq!(
-            std::mem::size_of::<MediaType>(),
-            std::mem::size_of::<Option<MediaType>>(),
-            "option size optimization"
-        );
+        let mt = MediaType::parse_preferring_atoms("application/VND.acme+json", ATOMS).unwrap();
+        assert!(std::ptr::eq(mt.as_ref(), ATOMS[0]));
+        assert_eq!(mt.suffix(), Some("json"));
+        assert_eq!(mt, MediaType::parse("application/vnd.acme+json").unwrap());
+        mt.test_assert_canonical();
+
+        let mt = MediaType::parse_preferring_atoms("text/x-acme;charset=UTF-8", ATOMS).unwrap();
+        assert!(!std::ptr::eq(mt.as_ref(), ATOMS[1]));
+        let mt = MediaType::parse_preferring_atoms("text/x-acme; charset=UTF-8", ATOMS).unwrap();
+        assert!(std::ptr::eq(mt.as_ref(), ATOMS[1]));
+        assert_eq!(mt.param(CHARSET), Some(UTF_8));
+
+        let mt = MediaType::parse_preferring_atoms("text/plain", ATOMS).unwrap();
+        assert_eq!(mt.mime.private_atom(), TEXT_PLAIN.mime.private_atom());
+
+        assert!(MediaType::parse_preferring_atoms("text/x-acme;", ATOMS).is_ok());
+        assert!(MediaType::parse_preferring_atoms("text", ATOMS).is_err());
     }
 
     #[test]
-    fn test_type_() {
-        assert_eq!(TEXT_PLAIN.type_(), TEXT);
+    fn test_display_lowercase() {
+        let mt = MediaType::parse_preserving("Text/HTML; Charset=UTF-8; Name=\"A B\"").unwrap();
+        let lower = mt.as_str().to_ascii_lowercase();
+        assert_eq!(mt.display_lowercase().to_string(), lower);
+
+        assert_eq!(format!("{:<12}|", MediaType::parse_preserving("Text/A").unwrap().display_lowercase()), "text/a      |");
+        assert_eq!(format!("{:*^10}", MediaType::parse_preserving("Text/A").unwrap().display_lowercase()), "**text/a**");
+        assert_eq!(format!("{:.6}", mt.display_lowercase()), "text/h");
+        assert_eq!(format!("{:4.6}", mt.display_lowercase()), "text/h");
+
+        // Already lowercase sources format the same as a `str`.
+        assert_eq!(format!("{:>12.6}", TEXT_HTML.display_lowercase()), format!("{:>12.6}", "text/html"));
+        assert_eq!(format!("{:>12.6}", mt.display_lowercase()), format!("{:>12.6}", lower));
+
+        let mt = MediaType::parse_preserving("text/plain; name=\"Ünï Côdé\"").unwrap();
+        assert_eq!(format!("{:^30}", mt.display_lowercase()), format!("{:^30}", mt.as_str().to_ascii_lowercase()));
     }
 
+    #[test]
+    fn test_parse_csv_header() {
+        let entries = MediaType::parse_csv_header(" text/plain; charset=UTF-8 ,image/svg+xml;a=\"b,c\"\t")
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries, [TEXT_PLAIN_UTF_8, MediaType::parse("image/svg+xml;a=\"b,c\"").unwrap()]);
+
+        // Stopping early leaves later entries unparsed.
+        let first = MediaType::parse_csv_header("image/png, not a type").next().unwrap();
+        assert_eq!(first.unwrap(), IMAGE_PNG);
+
+        let results = MediaType::parse_csv_header("text/html,,*/*").map(|r| r.is_ok()).collect::<Vec<_>>();
+        assert_eq!(results, [true, false, false]);
+
+        fn assert_fused<I: std::iter::FusedIterator>(_: I) {}
+        assert_fused(MediaType::parse_csv_header("text/html"));
+    }
 
     #[test]
-    fn test_subtype() {
-        assert_eq!(TEXT_PLAIN.subtype(), PLAIN);
-        assert_eq!(TEXT_PLAIN_UTF_8.subtype(), PLAIN);
-        let mime = MediaType::parse("text/html+xml").unwrap();
-        assert_eq!(mime.subtype(), "html+xml");
+    fn test_subtype_contains() {
+        let rss = MediaType::parse("application/rss+xml; charset=utf-8").unwrap();
+        assert!(rss.subtype_contains("xml"));
+        assert!(rss.subtype_contains("XML"));
+        assert!(rss.subtype_contains("rss+xml"));
+        assert!(rss.subtype_contains(""));
+        assert!(!rss.subtype_contains("charset"));
+        assert!(!rss.subtype_contains("application"));
+        assert!(!rss.subtype_contains("rss+xml+"));
+
+        let mt = MediaType::parse_preserving("Application/XHTML+XML").unwrap();
+        assert!(mt.subtype_contains("html"));
+        assert!(!TEXT_PLAIN.subtype_contains("xml"));
     }
 
     #[test]
-    fn test_matching() {
-        match (TEXT_PLAIN.type_(), TEXT_PLAIN.subtype()) {
-            (TEXT, PLAIN) => (),
-            _ => unreachable!(),
-        }
+    fn test_parse_preserving_param_names() {
+        let mt = MediaType::parse_preserving_param_names("Application/JSON; X-Custom-Param=\"A b\"; Charset=UTF-8").unwrap();
+        assert_eq!(mt.as_ref(), "application/json; X-Custom-Param=\"A b\"; Charset=utf-8");
+        assert_eq!(mt.param("X-CUSTOM-PARAM").unwrap(), "A b");
+        assert_eq!(mt.param("x-custom-param").unwrap(), "A b");
+        assert_eq!(mt.charset().unwrap(), "utf-8");
+        assert_eq!(mt, MediaType::parse("application/json; x-custom-param=\"A b\"; charset=utf-8").unwrap());
+
+        let mt = MediaType::parse_preserving_param_names("text/plain; charset=utf-8").unwrap();
+        assert_eq!(mt.mime.private_atom(), TEXT_PLAIN_UTF_8.mime.private_atom());
     }
 
     #[test]
-    fn test_suffix() {
-        assert_eq!(TEXT_PLAIN.suffix(), None);
-        let mime = MediaType::parse("text/html+xml").unwrap();
-        assert_eq!(mime.suffix(), Some(XML));
+    fn test_merge_params() {
+        let base = MediaType::parse("text/plain; charset=us-ascii; format=flowed").unwrap();
+
+        // Overlapping names, with different case.
+        let other = MediaType::parse_preserving("image/png; CHARSET=UTF-8; delsp=yes").unwrap();
+        let merged = base.merge_params(&other).unwrap();
+        assert_eq!(merged, "text/plain; format=flowed; charset=utf-8; delsp=yes");
+        merged.test_assert_canonical();
+
+        // Disjoint names.
+        let other = MediaType::parse("text/plain; a=\"b c\"; d=E").unwrap();
+        assert_eq!(base.merge_params(&other).unwrap(), "text/plain; charset=us-ascii; format=flowed; a=\"b c\"; d=E");
+
+        // Every param of `self` overridden, and duplicates in `self` all go.
+        let dup = MediaType::parse("text/plain; a=1; a=2; b=3").unwrap();
+        let other = MediaType::parse("text/plain; b=4; a=5").unwrap();
+        assert_eq!(dup.merge_params(&other).unwrap(), "text/plain; b=4; a=5");
+
+        assert_eq!(base.merge_params(&TEXT_HTML).unwrap(), base);
+        assert_eq!(TEXT_PLAIN.merge_params(&TEXT_HTML_UTF_8).unwrap(), TEXT_PLAIN_UTF_8);
+        assert_eq!(TEXT_PLAIN.merge_params(&TEXT_PLAIN).unwrap(), TEXT_PLAIN);
+
+        let empty = MediaType::parse_allowing_empty_subtype("text/").unwrap();
+        assert_eq!(empty.merge_params(&TEXT_PLAIN_UTF_8).unwrap().as_str(), "text/; charset=utf-8");
+
+        // Every copy of a duplicate in `other` is added.
+        let other = MediaType::parse("text/plain; a=1; charset=utf-8; a=2").unwrap();
+        assert_eq!(base.merge_params(&other).unwrap(), "text/plain; format=flowed; a=1; charset=utf-8; a=2");
+
+        // Too long for one source is an error, not a panic.
+        let long = format!("text/plain; a={}", "x".repeat(u16::MAX as usize - 20));
+        let long = MediaType::parse(&long).unwrap();
+        let other = format!("text/plain; b={}", "y".repeat(20));
+        let other = MediaType::parse(&other).unwrap();
+        long.merge_params(&other).unwrap_err();
     }
 
     #[test]
-    fn test_mime_fmt() {
-        let mime = TEXT_PLAIN;
-        assert_eq!(mime.to_string(), "text/plain");
-        let mime = TEXT_PLAIN_UTF_8;
-        assert_eq!(mime.to_string(), "text/plain; charset=utf-8");
+    fn test_retain_params_near_max_len() {
+        // Without spaces after the `;`s, rejoining with `"; "` would no
+        // longer fit.
+        let mut s = String::from("a/b;y=1;charset=utf-8;boundary=z");
+        let value = "v".repeat(600);
+        while s.len() + ";x=".len() + value.len() <= u16::MAX as usize {
+            s.push_str(";x=");
+            s.push_str(&value);
+        }
+        s.push_str(";p=");
+        s.push_str(&value[..u16::MAX as usize - s.len()]);
+        assert_eq!(s.len(), u16::MAX as usize);
+        let mt = MediaType::parse(&s).unwrap();
+
+        let mut retained = mt.clone();
+        retained.retain_params(|name, _| name != "y");
+        assert_eq!(retained.as_str(), s.replacen(";y=1", "", 1));
+        retained.test_assert_canonical();
+
+        assert_eq!(mt.without_charset().as_str(), s.replacen(";charset=utf-8", "", 1));
+        assert_eq!(mt.strip_boundary().as_str(), s.replacen(";boundary=z", "", 1));
     }
 
     #[test]
-    fn test_media_type_from_str() {
-        assert_eq!(MediaType::parse("text/plain").unwrap(), TEXT_PLAIN);
-        assert_eq!(MediaType::parse("TEXT/PLAIN").unwrap(), TEXT_PLAIN);
-        assert_eq!(MediaType::parse("text/plain; charset=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
-        assert_eq!(MediaType::parse("text/plain;charset=\"utf-8\"").unwrap(), TEXT_PLAIN_UTF_8);
+    fn test_param_iter_rev() {
+        let mt = MediaType::parse("multipart/mixed; boundary=x; charset=utf-8; name=\"a b\"").unwrap();
+        let rev = mt.param_iter_rev().map(|(n, v)| (n, v.to_content())).collect::<Vec<_>>();
+        assert_eq!(rev, [
+            ("name", Cow::Borrowed("a b")),
+            ("charset", Cow::Borrowed("utf-8")),
+            ("boundary", Cow::Borrowed("x")),
+        ]);
+        assert_eq!(mt.param_iter_rev().nth(1).unwrap().1, UTF_8);
 
-        // quotes + semi colon
-        MediaType::parse("text/plain;charset=\"utf-8\"; foo=bar").unwrap();
-        MediaType::parse("text/plain;charset=\"utf-8\" ; foo=bar").unwrap();
+        assert_eq!(TEXT_PLAIN_UTF_8.param_iter_rev().collect::<Vec<_>>(), [(CHARSET, UTF_8)]);
+        assert!(TEXT_PLAIN.param_iter_rev().next().is_none());
+    }
 
-        let upper = MediaType::parse("TEXT/PLAIN").unwrap();
-        assert_eq!(upper, TEXT_PLAIN);
-        assert_eq!(upper.type_(), TEXT);
-        assert_eq!(upper.subtype(), PLAIN);
+    #[test]
+    fn test_parse_and_intern() {
+        let mut cache = std::collections::HashMap::new();
 
+        let mt = MediaType::parse_and_intern("text/plain; charset=UTF-8", &mut cache).unwrap();
+        assert_eq!(mt, TEXT_PLAIN_UTF_8);
+        assert_eq!(cache.len(), 1);
 
-        let extended = MediaType::parse("TEXT/PLAIN; CHARSET=UTF-8; FOO=BAR").unwrap();
-        assert_eq!(extended, "text/plain; charset=utf-8; foo=BAR");
-        assert_eq!(extended.param("charset").unwrap(), "utf-8");
-        assert_eq!(extended.param("foo").unwrap(), "BAR");
+        // A hit returns the cached value, without parsing again.
+        cache.insert("not parsed".to_owned(), IMAGE_PNG);
+        assert_eq!(MediaType::parse_and_intern("not parsed", &mut cache).unwrap(), IMAGE_PNG);
+        assert_eq!(MediaType::parse_and_intern("text/plain; charset=UTF-8", &mut cache).unwrap(), mt);
 
-        MediaType::parse("multipart/form-data; boundary=--------foobar").unwrap();
+        // Keys are exact, and errors aren't cached.
+        MediaType::parse_and_intern("Text/Plain; charset=UTF-8", &mut cache).unwrap();
+        assert_eq!(cache.len(), 3);
+        MediaType::parse_and_intern("text", &mut cache).unwrap_err();
+        assert_eq!(cache.len(), 3);
+    }
 
-        // empty quotes
-        MediaType::parse("audo/wave; codecs=\"\"").expect("param value with empty quotes");
+    #[test]
+    fn test_parse_header_multi() {
+        let mt = MediaType::parse_header_multi(&["Application/JSON"]).unwrap();
+        assert_eq!(mt, APPLICATION_JSON);
+        let mt = MediaType::parse_header_multi(&[" ", "image/svg+xml", ""]).unwrap();
+        assert_eq!(mt, IMAGE_SVG);
 
-        // parse errors
-        MediaType::parse("f o o / bar").unwrap_err();
-        MediaType::parse("text\n/plain").unwrap_err();
-        MediaType::parse("text\r/plain").unwrap_err();
-        MediaType::parse("text/\r\nplain").unwrap_err();
-        MediaType::parse("text/plain;\r\ncharset=utf-8").unwrap_err();
-        MediaType::parse("text/plain; charset=\r\nutf-8").unwrap_err();
-        MediaType::parse("text/plain; charset=\"\r\nutf-8\"").unwrap_err();
+        let err = MediaType::parse_header_multi(&["text/plain", "text/html"]).unwrap_err();
+        assert_eq!(err.to_string(), "invalid MIME: more than one value for a header that allows only one");
+        MediaType::parse_header_multi(&["text/plain; charset=utf-8", "text/plain; charset=utf-8"]).unwrap_err();
+        MediaType::parse_header_multi(&["text/plain,"]).unwrap();
+
+        MediaType::parse_header_multi(&[]).unwrap_err();
+        MediaType::parse_header_multi(&["*/*"]).unwrap_err();
     }
 
+    #[test]
+    fn test_parse_or() {
+        assert_eq!(MediaType::parse_or_octet_stream(" Text/Plain; Charset=UTF-8"), TEXT_PLAIN_UTF_8);
+        for src in &["", "text", "text/plain; a", "*/*", "text/*"] {
+            let mt = MediaType::parse_or_octet_stream(*src);
+            assert_eq!(mt, APPLICATION_OCTET_STREAM, "{:?}", src);
+            assert_ne!(mt.mime.private_atom(), 0);
+        }
+
+        assert_eq!(MediaType::parse_or("image/svg+xml", TEXT_PLAIN), IMAGE_SVG);
+        assert_eq!(MediaType::parse_or("image/svg xml", TEXT_PLAIN), TEXT_PLAIN);
+    }
 
     #[test]
-    fn test_from_str_empty_parameter_list() {
-        static CASES: &'static [&'static str] = &[
-            "text/event-stream;",
-            "text/event-stream; ",
-            "text/event-stream;       ",
-        ];
+    fn test_parse_list() {
+        let list = MediaType::parse_list("text/plain; charset=UTF-8 , image/svg+xml").unwrap();
+        assert_eq!(list, [TEXT_PLAIN_UTF_8, IMAGE_SVG]);
 
-        for &case in CASES {
-            let mime = MediaType::parse(case).expect(case);
-            assert_eq!(mime, TEXT_EVENT_STREAM, "case = {:?}", case);
-            assert_eq!(mime.type_(), TEXT, "case = {:?}", case);
-            assert_eq!(mime.subtype(), EVENT_STREAM, "case = {:?}", case);
-            assert!(!mime.has_params(), "case = {:?}", case);
+        assert_eq!(MediaType::parse_list("image/png").unwrap(), [IMAGE_PNG]);
+
+        for s in &["", "text/plain,", "text/plain,,image/png", "text/plain, */*", "text/*"] {
+            MediaType::parse_list(*s).expect_err(s);
         }
     }
 
     #[test]
-    fn test_parse_too_long() {
-        let mut source = vec![b'a'; ::std::u16::MAX as usize];
-        source[5] = b'/';
+    fn test_with_suffix() {
+        let xml = TEXT_PLAIN_UTF_8.with_suffix(Some("xml")).unwrap();
+        assert_eq!(xml.to_string(), "text/plain+xml; charset=utf-8");
+        assert_eq!(MediaType::parse(&xml.to_string()).unwrap(), xml);
 
-        let mut s = String::from_utf8(source).unwrap();
+        let svg = MediaType::parse("image/svg").unwrap();
+        assert_eq!(svg.with_suffix(Some("xml")).unwrap(), IMAGE_SVG);
+        assert_eq!(IMAGE_SVG.with_suffix(None).unwrap().to_string(), "image/svg");
 
-        MediaType::parse(&s).expect("parses AT max length");
+        let replaced = IMAGE_SVG.with_suffix(Some("zip")).unwrap();
+        assert_eq!(replaced.to_string(), "image/svg+zip");
+        assert_eq!(replaced.suffix(), Some("zip"));
+        assert_eq!(MediaType::parse(&replaced.to_string()).unwrap(), replaced);
 
-        s.push('a');
-        MediaType::parse(&s).expect_err("errors OVER max length");
-    }
+        IMAGE_SVG.with_suffix(Some("")).unwrap_err();
+        IMAGE_SVG.with_suffix(Some("a+b")).unwrap_err();
 
-    #[test]
-    fn test_case_sensitive_values() {
-        let mime = MediaType::parse("multipart/form-data; charset=BASE64; boundary=ABCDEFG").unwrap();
-        assert_eq!(mime.param(CHARSET).unwrap(), "bAsE64");
-        assert_eq!(mime.param(BOUNDARY).unwrap(), "ABCDEFG");
-        assert_eq!(mime.param(BOUNDARY).unwrap().as_str_repr(), "ABCDEFG");
-        assert_ne!(mime.param(BOUNDARY).unwrap(), "abcdefg");
+        for mt in &[&xml, &replaced] {
+            mt.test_assert_canonical();
+        }
+        IMAGE_SVG.with_suffix(None).unwrap().test_assert_canonical();
+
+        let rfc2231 = MediaType::parse_rfc2231("application/foo; a*=x").unwrap();
+        let json = rfc2231.with_suffix(Some("json")).unwrap();
+        assert_eq!(json.as_str(), "application/foo+json; a*=x");
+        json.test_assert_canonical();
+        assert_eq!(json.with_suffix(None).unwrap().as_str(), "application/foo; a*=x");
     }
 
     #[test]
-    fn test_get_param() {
-        assert_eq!(TEXT_PLAIN.param("charset"), None);
-        assert_eq!(TEXT_PLAIN.param("baz"), None);
+    fn test_replace_type() {
+        let svg = IMAGE_SVG.replace_type("Application").unwrap();
+        assert_eq!(svg, "application/svg+xml");
+        assert_eq!(svg.type_len(), "application".len());
+        assert_eq!(svg.subtype(), "svg+xml");
+        assert_eq!(svg.suffix(), Some("xml"));
 
-        assert_eq!(TEXT_PLAIN_UTF_8.param("charset"), Some(UTF_8));
-        assert_eq!(TEXT_PLAIN_UTF_8.param("baz"), None);
+        let back = svg.replace_type("image").unwrap();
+        assert_eq!(back, IMAGE_SVG);
 
-        let mime = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
-        assert_eq!(mime.param(CHARSET).unwrap(), "utf-8");
-        assert_eq!(mime.param("foo").unwrap(), "bar");
-        assert_eq!(mime.param("baz"), None);
+        let mt = MediaType::parse("application/json; charset=utf-8; q=x").unwrap();
+        let text = mt.replace_type("text").unwrap();
+        assert_eq!(text, "text/json; charset=utf-8; q=x");
+        assert_eq!(text.param("q").unwrap(), "x");
+        assert_eq!(text.replace_type("application").unwrap(), mt);
+
+        for s in &["", "te/xt", "te xt", "*", "t;"] {
+            TEXT_PLAIN.replace_type(s).expect_err(s);
+        }
 
+        for mt in &[&svg, &back, &text] {
+            mt.test_assert_canonical();
+        }
 
-        let mime = MediaType::parse("text/plain;charset=\"utf-8\"").unwrap();
-        assert_eq!(mime.param(CHARSET), Some(UTF_8));
+        let rfc2231 = MediaType::parse_rfc2231("application/foo; a*=x").unwrap();
+        let text = rfc2231.replace_type("text").unwrap();
+        assert_eq!(text.as_str(), "text/foo; a*=x");
+        text.test_assert_canonical();
+
+        let empty = MediaType::parse_allowing_empty_subtype("text/; a=1").unwrap();
+        assert_eq!(empty.replace_type("image").unwrap().as_str(), "image/; a=1");
     }
 
     #[test]
-    fn test_mime_with_dquote_quoted_pair() {
-        let mime = MediaType::parse(r#"application/x-custom; title="the \" char""#).unwrap();
-        assert_eq!(mime.param("title").unwrap(), "the \" char");
+    fn test_subtype_facets() {
+        fn facets(src: &str) -> Vec<String> {
+            let mt = MediaType::parse(src).unwrap();
+            mt.subtype_facets().map(str::to_owned).collect()
+        }
+
+        assert_eq!(facets("text/plain"), ["plain"]);
+        assert_eq!(facets("application/vnd.ms-excel"), ["vnd", "ms-excel"]);
+        assert_eq!(facets("application/vnd.oasis.opendocument.text; x=a.b"), ["vnd", "oasis", "opendocument", "text"]);
+        assert_eq!(facets("application/x.a+b.c+json"), ["x", "a+b", "c"]);
+        assert_eq!(facets("application/vnd..x"), ["vnd", "", "x"]);
     }
 
     #[test]
-    fn test_params() {
-        let mime = TEXT_PLAIN;
-        let mut params = mime.params();
-        assert_eq!(params.next(), None);
+    fn test_byte_len() {
+        let list = [TEXT_PLAIN, IMAGE_SVG, MediaType::parse("Text/X-Thing; A=\"b\"").unwrap()];
+        let mut out = String::with_capacity(list.iter().map(MediaType::byte_len).sum());
+        let capacity = out.capacity();
+        for mt in &list {
+            assert_eq!(mt.byte_len(), mt.to_string().len());
+            mt.write_to(&mut out).unwrap();
+        }
+        assert_eq!(out.capacity(), capacity);
+        assert_eq!(out, "text/plainimage/svg+xmltext/x-thing; a=\"b\"");
+    }
 
-        let mime = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
-        let mut params = mime.params();
-        assert_eq!(params.next(), Some((CHARSET, UTF_8)));
+    #[test]
+    fn test_quote_value() {
+        assert!(matches!(MediaType::quote_value("flowed"), Cow::Borrowed("flowed")));
+        assert!(matches!(MediaType::quote_value("my file.txt"), Cow::Owned(_)));
+        assert_eq!(MediaType::quote_value("my file.txt"), "\"my file.txt\"");
+        assert_eq!(MediaType::quote_value("a=\"b\\c\""), "\"a=\\\"b\\\\c\\\"\"");
 
-        let (second_param_left, second_param_right) = params.next().unwrap();
-        assert_eq!(second_param_left, "foo");
-        assert_eq!(second_param_right, "bar");
+        for value in &["flowed", "my file.txt", "a=\"b\\c\"", ""] {
+            let src = format!("text/plain; v={}", MediaType::quote_value(value));
+            let mt = MediaType::parse(&src).unwrap();
+            assert_eq!(mt.param("v").unwrap().to_content(), *value);
+        }
+    }
 
-        assert_eq!(params.next(), None);
+    #[test]
+    fn test_into_iter() {
+        let mt = MediaType::parse("multipart/mixed; boundary=abc; charset=utf-8").unwrap();
+
+        let mut seen = Vec::new();
+        for (name, value) in &mt {
+            seen.push((name, value.as_str_repr()));
+        }
+        assert_eq!(seen, [("boundary", "abc"), ("charset", "utf-8")]);
+
+        assert_eq!((&TEXT_PLAIN).into_iter().count(), 0);
+        assert_eq!((&TEXT_PLAIN_UTF_8).into_iter().size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn test_params_fused() {
+        fn assert_fused<I: std::iter::FusedIterator>(_: I) {}
+
+        assert_fused(TEXT_PLAIN.params());
+        assert_fused(TEXT_PLAIN_UTF_8.split_params().1);
     }
 
     #[test]
     fn test_has_params() {
         let mime = TEXT_PLAIN;
-        assert_eq!(mime.has_params(), false);
+        assert!(!mime.has_params());
 
         let mime = MediaType::parse("text/plain; charset=utf-8").unwrap();
-        assert_eq!(mime.has_params(), true);
+        assert!(mime.has_params());
 
         let mime = MediaType::parse("text/plain; charset=utf-8; foo=bar").unwrap();
-        assert_eq!(mime.has_params(), true);
+        assert!(mime.has_params());
     }
 
     #[test]
@@ -477,6 +4425,17 @@ mod tests {
         assert_eq!(mime.param("param").unwrap(), "");
     }
 
+    #[test]
+    fn test_mime_param_with_empty_value() {
+        let mime = MediaType::parse("application/x-custom; a=; b=2").unwrap();
+        assert_eq!(mime.param("a").unwrap(), "");
+        assert_eq!(mime.param("b").unwrap(), "2");
+
+        let mime = MediaType::parse("application/x-custom; a=").unwrap();
+        assert_eq!(mime.param("a").unwrap(), "");
+        assert_eq!(mime, "application/x-custom; a=\"\"");
+    }
+
     #[test]
     fn test_mime_param_with_tab() {
         let mime = MediaType::parse("application/x-custom;param=\"\t\"").unwrap();
@@ -509,6 +4468,33 @@ mod tests {
         assert_eq!(mime_a, mime_b);
     }
 
+    #[test]
+    fn test_str_eq_is_symmetric() {
+        let mt = MediaType::parse("application/json; charset=utf-8; a=b").unwrap();
+        let same = "Application/JSON; a=b; charset=UTF-8";
+        let other = "application/json; charset=utf-8";
+
+        // MediaType == str
+        assert!(mt == *same);
+        assert!(mt != *other);
+        // str == MediaType
+        assert!(*same == mt);
+        assert!(*other != mt);
+        // MediaType == &str
+        assert!(mt == same);
+        assert!(mt != other);
+        // &str == MediaType
+        assert!(same == mt);
+        assert!(other != mt);
+
+        for s in &["text/plain", "TEXT/PLAIN", "text/html", "text/plain; charset=utf-8", "", "text"] {
+            assert_eq!(TEXT_PLAIN == *s, *s == TEXT_PLAIN, "{:?}", s);
+            assert_eq!(TEXT_PLAIN == **s, **s == TEXT_PLAIN, "{:?}", s);
+        }
+        assert!("text/plain" == TEXT_PLAIN);
+        assert!("text/plain; charset=utf-8" == TEXT_PLAIN_UTF_8);
+    }
+
     #[test]
     fn test_name_eq_is_case_insensitive() {
         let mime1 = MediaType::parse(r#"text/x-custom; abc=a"#).unwrap();
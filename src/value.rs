@@ -1,5 +1,7 @@
 use std::cmp::PartialEq;
 use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::Range;
 use std::borrow::Cow;
 
 use mime_parse::Mime;
@@ -26,15 +28,80 @@ pub struct Value<'a> {
     ascii_case_insensitive: bool,
 }
 
-pub(crate) fn params(mime: &Mime) -> impl Iterator<Item = (&str, Value)> {
-    mime.params().map(|(n, v)| {
-        let value = Value::new(v).for_name(n);
-        (n, value)
-    })
+/// An iterator over the parameters of a `MediaType` or `MediaRange`.
+///
+/// Returned by `params()`, or by iterating over a borrowed `MediaType`
+/// or `MediaRange`.
+pub struct Params<'a>(mime_parse::Params<'a>);
+
+pub(crate) fn params(mime: &Mime) -> Params<'_> {
+    Params(mime.params())
 }
 
 pub(crate) fn param<'a>(mime: &'a Mime, key: &str) -> Option<Value<'a>> {
-    params(mime).find(|e| key == e.0).map(|e| e.1)
+    // Names are lowercased when parsed, but the key may not be.
+    params(mime).find(|e| key.eq_ignore_ascii_case(e.0)).map(|e| e.1)
+}
+
+pub(crate) fn nth_param(mime: &Mime, index: usize) -> Option<(&str, Value<'_>)> {
+    mime.nth_param(index).map(|(n, v)| (n, Value::new(v).for_name(n)))
+}
+
+pub(crate) fn params_with_positions(
+    mime: &Mime,
+) -> impl Iterator<Item = (&str, Value<'_>, Range<usize>, Range<usize>)> {
+    let source = mime.as_ref();
+    mime.param_ranges().map(move |(name, value)| {
+        let name_str = &source[name.clone()];
+        let value_str = Value::new(&source[value.clone()]).for_name(name_str);
+        (name_str, value_str, name, value)
+    })
+}
+
+pub(crate) fn unquote(source: &str) -> Cow<'_, str> {
+    quoted_string::unquote_unchecked(source)
+}
+
+pub(crate) fn retain_params<F>(mime: &mut Mime, mut f: F)
+where
+    F: FnMut(&str, Value<'_>) -> bool,
+{
+    mime.retain_params(|n, v| f(n, Value::new(v).for_name(n)))
+}
+
+impl<'a> Iterator for Params<'a> {
+    type Item = (&'a str, Value<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(n, v)| {
+            let value = Value::new(v).for_name(n);
+            (n, value)
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Params<'a> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(n, v)| {
+            let value = Value::new(v).for_name(n);
+            (n, value)
+        })
+    }
+}
+
+impl<'a> FusedIterator for Params<'a> {}
+
+impl<'a> fmt::Debug for Params<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Params").finish()
+    }
 }
 
 impl<'a> Value<'a> {
@@ -46,8 +113,7 @@ impl<'a> Value<'a> {
     }
 
     fn for_name(mut self, name: &str) -> Self {
-        debug_assert!(crate::is_ascii_lowercase(name));
-        self.ascii_case_insensitive = name == crate::CHARSET;
+        self.ascii_case_insensitive = name.eq_ignore_ascii_case(crate::CHARSET);
         self
     }
 
@@ -107,7 +173,7 @@ impl<'a> Value<'a> {
     /// ```
     ///
     pub fn to_content(&self) -> Cow<'a, str> {
-        quoted_string::unquote_unchecked(self.source)
+        unquote(self.source)
     }
 
 }
@@ -151,7 +217,7 @@ impl<'a, 'b> PartialEq<&'b str> for Value<'a> {
 }
 
 
-impl<'a, 'b> PartialEq<Value<'b>> for &'a str {
+impl<'b> PartialEq<Value<'b>> for &str {
     #[inline]
     fn eq(&self, other: &Value<'b>) -> bool {
         other == self
@@ -0,0 +1,215 @@
+use std::fmt;
+
+use mime_parse::{Parser, ParseError};
+
+use crate::MediaType;
+
+/// A style or correctness issue in an otherwise valid `MediaType`.
+///
+/// Returned by [`MediaType::validate`](crate::MediaType::validate).
+/// Positions are byte offsets into the `MediaType`'s string form.
+/// More kinds of checks may be added in the future.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Violation {
+    /// The type, subtype, or a parameter name isn't lowercase.
+    ///
+    /// This only happens when parsing with
+    /// [`parse_preserving`](crate::MediaType::parse_preserving).
+    Uppercase {
+        /// The first uppercase letter of that name.
+        pos: usize,
+    },
+    /// The type or subtype has a character that RFC 7231 allows in a token,
+    /// but RFC 6838 doesn't allow in a registered name.
+    NotRestrictedName {
+        /// The first character that isn't allowed.
+        pos: usize,
+    },
+    /// A `charset` parameter on a type other than `text`.
+    CharsetOnNonText {
+        /// The start of the parameter name.
+        pos: usize,
+    },
+    /// The essence is a deprecated alias of another one.
+    DeprecatedAlias {
+        /// The preferred essence.
+        preferred: &'static str,
+    },
+    /// A parameter name that is already used earlier.
+    DuplicateParam {
+        /// The start of the repeated parameter name.
+        pos: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::Uppercase { pos } => write!(f, "uppercase name at position {}", pos),
+            Violation::NotRestrictedName { pos } => {
+                write!(f, "character not allowed by RFC 6838 at position {}", pos)
+            },
+            Violation::CharsetOnNonText { pos } => {
+                write!(f, "charset parameter on a non-text type at position {}", pos)
+            },
+            Violation::DeprecatedAlias { preferred } => {
+                write!(f, "deprecated alias, {} is preferred", preferred)
+            },
+            Violation::DuplicateParam { pos } => write!(f, "duplicate parameter at position {}", pos),
+        }
+    }
+}
+
+type Check = fn(&MediaType, &mut Vec<Violation>);
+
+// Each check pushes whatever it finds; add new ones here.
+static CHECKS: &[Check] = &[
+    check_uppercase,
+    check_restricted_name,
+    check_charset_on_non_text,
+    check_deprecated_alias,
+    check_duplicate_params,
+];
+
+pub(crate) fn validate(mt: &MediaType) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for check in CHECKS {
+        check(mt, &mut violations);
+    }
+    violations
+}
+
+fn check_uppercase(mt: &MediaType, violations: &mut Vec<Violation>) {
+    let subtype_start = mt.type_len() + 1;
+    let names = [
+        0..mt.type_len(),
+        subtype_start..subtype_start + mt.subtype_len(),
+    ];
+    let param_names = mt.mime.param_ranges().map(|(name, _)| name);
+
+    for name in names.iter().cloned().chain(param_names) {
+        let start = name.start;
+        if let Some(i) = mt.as_str()[name].bytes().position(|c| c.is_ascii_uppercase()) {
+            violations.push(Violation::Uppercase { pos: start + i });
+        }
+    }
+}
+
+fn check_restricted_name(mt: &MediaType, violations: &mut Vec<Violation>) {
+    let essence = &mt.as_str()[..mt.type_len() + 1 + mt.subtype_len()];
+    if let Err(ParseError::InvalidToken { pos, .. }) = Parser::cannot_range().rfc6838_names().parse(essence) {
+        violations.push(Violation::NotRestrictedName { pos });
+    }
+}
+
+fn check_charset_on_non_text(mt: &MediaType, violations: &mut Vec<Violation>) {
+    if mt.type_().eq_ignore_ascii_case(crate::TEXT) {
+        return;
+    }
+    for (name, _) in mt.mime.param_ranges() {
+        if mt.as_str()[name.clone()].eq_ignore_ascii_case(crate::CHARSET) {
+            violations.push(Violation::CharsetOnNonText { pos: name.start });
+        }
+    }
+}
+
+fn check_deprecated_alias(mt: &MediaType, violations: &mut Vec<Violation>) {
+    if let Some(preferred) = crate::registry::deprecated_alias(mt.mime.essence()) {
+        violations.push(Violation::DeprecatedAlias { preferred });
+    }
+}
+
+fn check_duplicate_params(mt: &MediaType, violations: &mut Vec<Violation>) {
+    let names = mt.mime.param_ranges().map(|(name, _)| name).collect::<Vec<_>>();
+    for (i, name) in names.iter().enumerate() {
+        let this = &mt.as_str()[name.clone()];
+        let repeated = names[..i]
+            .iter()
+            .any(|earlier| mt.as_str()[earlier.clone()].eq_ignore_ascii_case(this));
+        if repeated {
+            violations.push(Violation::DuplicateParam { pos: name.start });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn violations(mt: &MediaType) -> Vec<Violation> {
+        mt.validate().err().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_validate_clean() {
+        for mt in &[TEXT_PLAIN, TEXT_PLAIN_UTF_8, IMAGE_SVG, APPLICATION_JSON] {
+            assert_eq!(mt.validate(), Ok(()), "{:?}", mt);
+        }
+        let mt = MediaType::parse("multipart/form-data; boundary=ABC; name=\"x y\"").unwrap();
+        assert_eq!(mt.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_uppercase() {
+        let mt = MediaType::parse_preserving("Text/plAin; Charset=UTF-8; fOO=Bar").unwrap();
+        assert_eq!(violations(&mt), [
+            Violation::Uppercase { pos: 0 },
+            Violation::Uppercase { pos: 7 },
+            Violation::Uppercase { pos: 12 },
+            Violation::Uppercase { pos: 28 },
+        ]);
+
+        // Parsing normally lowercases everything that is case-insensitive.
+        let mt = MediaType::parse("Text/plAin; Charset=UTF-8; fOO=Bar").unwrap();
+        assert_eq!(mt.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_restricted_name() {
+        let mt = MediaType::parse("application/x~thing").unwrap();
+        assert_eq!(violations(&mt), [Violation::NotRestrictedName { pos: 13 }]);
+
+        let mt = MediaType::parse("_x/y").unwrap();
+        assert_eq!(violations(&mt), [Violation::NotRestrictedName { pos: 0 }]);
+    }
+
+    #[test]
+    fn test_validate_charset_on_non_text() {
+        let mt = MediaType::parse("application/json; charset=utf-8").unwrap();
+        assert_eq!(violations(&mt), [Violation::CharsetOnNonText { pos: 18 }]);
+
+        assert_eq!(TEXT_HTML_UTF_8.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_deprecated_alias() {
+        let mt = MediaType::parse("application/javascript").unwrap();
+        assert_eq!(violations(&mt), [Violation::DeprecatedAlias { preferred: "text/javascript" }]);
+    }
+
+    #[test]
+    fn test_validate_duplicate_params() {
+        let mt = MediaType::parse("text/plain; a=1; b=2; A=3; a=4").unwrap();
+        assert_eq!(violations(&mt), [
+            Violation::DuplicateParam { pos: 22 },
+            Violation::DuplicateParam { pos: 27 },
+        ]);
+    }
+
+    #[test]
+    fn test_validate_many() {
+        let mt = MediaType::parse_preserving("Application/X-JavaScript; Charset=utf-8").unwrap();
+        let found = violations(&mt);
+
+        assert_eq!(found, [
+            Violation::Uppercase { pos: 0 },
+            Violation::Uppercase { pos: 12 },
+            Violation::Uppercase { pos: 26 },
+            Violation::CharsetOnNonText { pos: 26 },
+            Violation::DeprecatedAlias { preferred: "text/javascript" },
+        ]);
+        assert_eq!(found[4].to_string(), "deprecated alias, text/javascript is preferred");
+        assert_eq!(found[3].to_string(), "charset parameter on a non-text type at position 26");
+    }
+}